@@ -0,0 +1,285 @@
+use std::{
+    fmt,
+    iter::Sum,
+    ops::{Add, AddAssign, Neg, Sub, SubAssign},
+    str::FromStr,
+};
+
+use anyhow::{anyhow, bail};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::{Number, Value};
+
+/// 1 BTC = 100_000_000 聪
+pub const SAT_PER_BTC: i64 = 100_000_000;
+/// 最大发行量, 单位聪
+pub const MAX_MONEY: i64 = 21_000_000 * SAT_PER_BTC;
+
+/// 金额, 以整数聪保存. 与节点的 CAmount 一致为有符号数 (钱包流水中支出为负)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Amount(i64);
+
+/// 金额单位
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Denomination {
+    Btc,
+    Sat,
+}
+
+impl Amount {
+    pub const ZERO: Amount = Amount(0);
+    pub const ONE_SAT: Amount = Amount(1);
+    pub const ONE_BTC: Amount = Amount(SAT_PER_BTC);
+    pub const MAX_MONEY: Amount = Amount(MAX_MONEY);
+
+    pub const fn from_sat(sat: i64) -> Self {
+        Amount(sat)
+    }
+
+    pub const fn to_sat(self) -> i64 {
+        self.0
+    }
+
+    /// 由 BTC 浮点数转换, 四舍五入到聪. f64 只有 53 位精度, 超过 2^53 聪 (约 9000 万个币,
+    /// DOGE 的大额余额可达) 时会丢失聪; 节点返回的金额应使用 from_btc_str 按十进制文本解析
+    pub fn from_btc(btc: f64) -> Result<Self, anyhow::Error> {
+        if !btc.is_finite() {
+            bail!("invalid amount: {}", btc);
+        }
+        let sat = (btc * SAT_PER_BTC as f64).round();
+        if sat.abs() > i64::MAX as f64 / 2.0 {
+            bail!("amount out of range: {}", btc);
+        }
+        Ok(Amount(sat as i64))
+    }
+
+    /// 精确解析十进制 BTC 字符串, 如 "0.00012345", 超过 8 位的非零小数报错
+    pub fn from_btc_str(s: &str) -> Result<Self, anyhow::Error> {
+        let s = s.trim();
+        let (negative, digits) = match s.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, s.strip_prefix('+').unwrap_or(s)),
+        };
+        let (int_part, frac_part) = digits.split_once('.').unwrap_or((digits, ""));
+        if int_part.is_empty() && frac_part.is_empty()
+            || !int_part.bytes().all(|b| b.is_ascii_digit())
+            || !frac_part.bytes().all(|b| b.is_ascii_digit())
+        {
+            bail!("invalid amount: {:?}", s);
+        }
+
+        let frac_part = frac_part.trim_end_matches('0');
+        if frac_part.len() > 8 {
+            bail!("amount has more than 8 decimal places: {:?}", s);
+        }
+
+        let int_sat = if int_part.is_empty() { 0 } else { int_part.parse::<i64>()? }
+            .checked_mul(SAT_PER_BTC)
+            .ok_or_else(|| anyhow!("amount out of range: {:?}", s))?;
+        let frac_sat = if frac_part.is_empty() { 0 } else { format!("{:0<8}", frac_part).parse::<i64>()? };
+        let sat = int_sat.checked_add(frac_sat).ok_or_else(|| anyhow!("amount out of range: {:?}", s))?;
+
+        Ok(Amount(if negative { -sat } else { sat }))
+    }
+
+    /// 仅用于展示/统计, 不要用于记账
+    pub fn to_btc(self) -> f64 {
+        self.0 as f64 / SAT_PER_BTC as f64
+    }
+
+    /// 8 位小数的 BTC 字符串, 如 "0.00012345"
+    pub fn to_btc_string(self) -> String {
+        let sign = if self.0 < 0 { "-" } else { "" };
+        let abs = self.0.unsigned_abs();
+        format!("{}{}.{:08}", sign, abs / SAT_PER_BTC as u64, abs % SAT_PER_BTC as u64)
+    }
+
+    pub fn fmt_in(self, denomination: Denomination) -> String {
+        match denomination {
+            Denomination::Btc => format!("{} BTC", self.to_btc_string()),
+            Denomination::Sat => format!("{} sat", self.0),
+        }
+    }
+
+    pub fn checked_add(self, rhs: Amount) -> Option<Amount> {
+        self.0.checked_add(rhs.0).map(Amount)
+    }
+
+    pub fn checked_sub(self, rhs: Amount) -> Option<Amount> {
+        self.0.checked_sub(rhs.0).map(Amount)
+    }
+
+    pub fn checked_mul(self, rhs: i64) -> Option<Amount> {
+        self.0.checked_mul(rhs).map(Amount)
+    }
+
+    pub fn checked_div(self, rhs: i64) -> Option<Amount> {
+        self.0.checked_div(rhs).map(Amount)
+    }
+
+    /// 逐项 checked_add, 溢出返回 None
+    pub fn checked_sum<I: IntoIterator<Item = Amount>>(iter: I) -> Option<Amount> {
+        iter.into_iter().try_fold(Amount::ZERO, Amount::checked_add)
+    }
+
+    pub fn is_negative(self) -> bool {
+        self.0 < 0
+    }
+
+    pub fn abs(self) -> Amount {
+        Amount(self.0.abs())
+    }
+
+    /// 是否在 [0, MAX_MONEY] 范围内
+    pub fn is_valid_money(self) -> bool {
+        (0..=MAX_MONEY).contains(&self.0)
+    }
+}
+
+impl fmt::Display for Amount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.fmt_in(Denomination::Btc))
+    }
+}
+
+/// 支持 "0.1", "0.1 BTC", "10000 sat" / "10000 sats"
+impl FromStr for Amount {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if let Some(sat) = s.strip_suffix("sats").or_else(|| s.strip_suffix("sat")) {
+            return Ok(Amount(sat.trim().parse::<i64>()?));
+        }
+        Amount::from_btc_str(s.strip_suffix("BTC").unwrap_or(s))
+    }
+}
+
+// 运算符溢出时 panic, 与整数运算一致; 需要处理溢出时使用 checked_*
+impl Add for Amount {
+    type Output = Amount;
+
+    fn add(self, rhs: Amount) -> Amount {
+        self.checked_add(rhs).expect("amount addition overflow")
+    }
+}
+
+impl Sub for Amount {
+    type Output = Amount;
+
+    fn sub(self, rhs: Amount) -> Amount {
+        self.checked_sub(rhs).expect("amount subtraction overflow")
+    }
+}
+
+impl AddAssign for Amount {
+    fn add_assign(&mut self, rhs: Amount) {
+        *self = *self + rhs;
+    }
+}
+
+impl SubAssign for Amount {
+    fn sub_assign(&mut self, rhs: Amount) {
+        *self = *self - rhs;
+    }
+}
+
+impl Neg for Amount {
+    type Output = Amount;
+
+    fn neg(self) -> Amount {
+        Amount(-self.0)
+    }
+}
+
+impl Sum for Amount {
+    fn sum<I: Iterator<Item = Amount>>(iter: I) -> Amount {
+        iter.fold(Amount::ZERO, Add::add)
+    }
+}
+
+impl<'a> Sum<&'a Amount> for Amount {
+    fn sum<I: Iterator<Item = &'a Amount>>(iter: I) -> Amount {
+        iter.copied().sum()
+    }
+}
+
+/// 与节点一致, 序列化为 8 位小数的 BTC 数值, 不经过 f64
+impl Serialize for Amount {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let number = Number::from_str(&self.to_btc_string()).map_err(serde::ser::Error::custom)?;
+        number.serialize(serializer)
+    }
+}
+
+/// 接受 BTC 数值或十进制字符串. serde_json 开启了 arbitrary_precision, 数值按原始十进制文本精确解析
+impl<'de> Deserialize<'de> for Amount {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let amount = match Value::deserialize(deserializer)? {
+            Value::Number(number) => {
+                let text = number.to_string();
+                // 节点输出定点小数, 科学计数法只来自本地由 f64 构造的数值
+                match number.as_f64() {
+                    Some(btc) if text.contains(['e', 'E']) => Amount::from_btc(btc),
+                    _ => Amount::from_btc_str(&text),
+                }
+            }
+            Value::String(s) => Amount::from_btc_str(&s),
+            other => Err(anyhow!("expected a BTC amount as number or decimal string, got {}", other)),
+        };
+        amount.map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_amount_parse() {
+        assert_eq!(Amount::from_btc_str("0.00012345").unwrap(), Amount::from_sat(12_345));
+        assert_eq!(Amount::from_btc_str("21000000").unwrap(), Amount::MAX_MONEY);
+        assert_eq!(Amount::from_btc_str("-.5").unwrap(), Amount::from_sat(-50_000_000));
+        assert_eq!(Amount::from_btc_str("1.100000000").unwrap(), Amount::from_sat(110_000_000));
+        assert!(Amount::from_btc_str("0.000000001").is_err());
+        assert!(Amount::from_btc_str("1e-8").is_err());
+        assert!(Amount::from_btc_str(".").is_err());
+
+        assert_eq!("1000 sat".parse::<Amount>().unwrap(), Amount::from_sat(1000));
+        assert_eq!("0.1 BTC".parse::<Amount>().unwrap(), Amount::from_sat(10_000_000));
+    }
+
+    #[test]
+    fn test_amount_serde() {
+        // 0.1 + 0.2 这类值浮点累加会漂移, 按聪累加则精确
+        let values: Vec<Amount> = serde_json::from_value(json!([0.1, 0.2, "0.30000001", 3, 1e-8])).unwrap();
+        assert_eq!(values.iter().sum::<Amount>(), Amount::from_sat(360_000_002));
+        assert_eq!(values[4], Amount::ONE_SAT);
+
+        assert_eq!(serde_json::to_value(Amount::from_sat(12_345)).unwrap(), json!(0.00012345));
+        let back: Amount = serde_json::from_value(serde_json::to_value(Amount::from_sat(2_099_999_997_690_000)).unwrap()).unwrap();
+        assert_eq!(back, Amount::from_sat(2_099_999_997_690_000));
+        assert!(serde_json::from_value::<Amount>(json!(true)).is_err());
+
+        // 超过 2^53 聪的金额 (DOGE 大额余额) 按十进制文本解析, 往返不丢失聪
+        let data = r#"{"mine": {"trusted": 123456789.12345679}}"#;
+        let value = serde_json::from_str::<Value>(data).unwrap();
+        let amount: Amount = serde_json::from_value(value["mine"]["trusted"].clone()).unwrap();
+        assert_eq!(amount, Amount::from_sat(12_345_678_912_345_679));
+        assert_eq!(serde_json::to_string(&amount).unwrap(), "123456789.12345679");
+        assert_eq!(serde_json::from_str::<Amount>("123456789.12345679").unwrap(), amount);
+    }
+
+    #[test]
+    fn test_amount_fmt_and_arith() {
+        assert_eq!(Amount::from_sat(12_345).to_string(), "0.00012345 BTC");
+        assert_eq!(Amount::from_sat(-150_000_000).to_btc_string(), "-1.50000000");
+        assert_eq!(Amount::from_sat(1).fmt_in(Denomination::Sat), "1 sat");
+
+        assert_eq!(Amount::from_sat(i64::MAX).checked_add(Amount::ONE_SAT), None);
+        assert_eq!(Amount::ONE_BTC.checked_sub(Amount::ONE_SAT), Some(Amount::from_sat(99_999_999)));
+        assert_eq!(Amount::checked_sum([Amount::ONE_BTC, Amount::from_sat(i64::MAX)]), None);
+        assert!(!Amount::from_sat(-1).is_valid_money());
+    }
+}
@@ -0,0 +1,545 @@
+use anyhow::{anyhow, bail};
+use sha2::{Digest, Sha256};
+
+use super::{amount::Amount, response_type::Transaction};
+
+/// 双重 sha256
+pub fn sha256d(data: &[u8]) -> [u8; 32] {
+    let digest = Sha256::digest(Sha256::digest(data));
+    digest.into()
+}
+
+/// 哈希按节点习惯以字节逆序的 hex 显示
+pub fn hash_to_hex(hash: &[u8; 32]) -> String {
+    let mut hash = *hash;
+    hash.reverse();
+    hex::encode(hash)
+}
+
+/// 逆序 hex (txid/区块哈希) 转为内部字节序
+pub fn hash_from_hex(hex_str: &str) -> Result<[u8; 32], anyhow::Error> {
+    let mut hash: [u8; 32] = hex::decode(hex_str)?
+        .try_into()
+        .map_err(|_| anyhow!("hash must be 32 bytes: {}", hex_str))?;
+    hash.reverse();
+    Ok(hash)
+}
+
+// 共识格式读取器
+pub(crate) struct Decoder<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Decoder<'a> {
+    pub(crate) fn new(data: &'a [u8]) -> Self {
+        Decoder { data, pos: 0 }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.pos >= self.data.len()
+    }
+
+    // 剩余未读取的字节
+    pub(crate) fn remaining(&self) -> &'a [u8] {
+        &self.data[self.pos.min(self.data.len())..]
+    }
+
+    pub(crate) fn peek_u8(&self) -> Option<u8> {
+        self.data.get(self.pos).copied()
+    }
+
+    pub(crate) fn read_bytes(&mut self, len: usize) -> Result<&'a [u8], anyhow::Error> {
+        let end = self.pos.checked_add(len).filter(|&end| end <= self.data.len());
+        match end {
+            Some(end) => {
+                let bytes = &self.data[self.pos..end];
+                self.pos = end;
+                Ok(bytes)
+            }
+            None => bail!("unexpected end of data at {} reading {} bytes", self.pos, len),
+        }
+    }
+
+    pub(crate) fn read_u8(&mut self) -> Result<u8, anyhow::Error> {
+        Ok(self.read_bytes(1)?[0])
+    }
+
+    pub(crate) fn read_u16(&mut self) -> Result<u16, anyhow::Error> {
+        Ok(u16::from_le_bytes(self.read_bytes(2)?.try_into()?))
+    }
+
+    pub(crate) fn read_u32(&mut self) -> Result<u32, anyhow::Error> {
+        Ok(u32::from_le_bytes(self.read_bytes(4)?.try_into()?))
+    }
+
+    pub(crate) fn read_u64(&mut self) -> Result<u64, anyhow::Error> {
+        Ok(u64::from_le_bytes(self.read_bytes(8)?.try_into()?))
+    }
+
+    pub(crate) fn read_hash(&mut self) -> Result<[u8; 32], anyhow::Error> {
+        Ok(self.read_bytes(32)?.try_into()?)
+    }
+
+    /// CompactSize, 拒绝非最短编码
+    pub(crate) fn read_varint(&mut self) -> Result<u64, anyhow::Error> {
+        let (value, min) = match self.read_u8()? {
+            0xfd => (self.read_u16()? as u64, 0xfd),
+            0xfe => (self.read_u32()? as u64, 0x1_0000),
+            0xff => (self.read_u64()?, 0x1_0000_0000),
+            n => return Ok(n as u64),
+        };
+        if value < min {
+            bail!("non-canonical varint at {}", self.pos);
+        }
+        Ok(value)
+    }
+
+    // 读取长度前缀的数组长度, 防止恶意数据导致超大内存分配
+    pub(crate) fn read_len(&mut self) -> Result<usize, anyhow::Error> {
+        let len = self.read_varint()?;
+        if len > (self.data.len() - self.pos) as u64 {
+            bail!("length {} exceeds remaining data at {}", len, self.pos);
+        }
+        Ok(len as usize)
+    }
+
+    pub(crate) fn read_var_bytes(&mut self) -> Result<Vec<u8>, anyhow::Error> {
+        let len = self.read_len()?;
+        Ok(self.read_bytes(len)?.to_vec())
+    }
+}
+
+pub(crate) fn write_varint(buf: &mut Vec<u8>, n: u64) {
+    match n {
+        0..=0xfc => buf.push(n as u8),
+        0xfd..=0xffff => {
+            buf.push(0xfd);
+            buf.extend((n as u16).to_le_bytes());
+        }
+        0x1_0000..=0xffff_ffff => {
+            buf.push(0xfe);
+            buf.extend((n as u32).to_le_bytes());
+        }
+        _ => {
+            buf.push(0xff);
+            buf.extend(n.to_le_bytes());
+        }
+    }
+}
+
+pub(crate) fn write_var_bytes(buf: &mut Vec<u8>, bytes: &[u8]) {
+    write_varint(buf, bytes.len() as u64);
+    buf.extend(bytes);
+}
+
+/// 原始交易 (legacy / segwit)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawTransaction {
+    pub version: i32,
+    pub inputs: Vec<RawTxIn>,
+    pub outputs: Vec<RawTxOut>,
+    pub lock_time: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawTxIn {
+    /// 内部字节序, 显示用 hash_to_hex
+    pub prev_txid: [u8; 32],
+    pub prev_vout: u32,
+    pub script_sig: Vec<u8>,
+    pub sequence: u32,
+    pub witness: Vec<Vec<u8>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawTxOut {
+    pub value: Amount,
+    pub script_pubkey: Vec<u8>,
+}
+
+impl RawTxIn {
+    pub fn prev_txid_hex(&self) -> String {
+        hash_to_hex(&self.prev_txid)
+    }
+
+    pub fn is_coinbase(&self) -> bool {
+        self.prev_txid == [0; 32] && self.prev_vout == u32::MAX
+    }
+}
+
+impl RawTxOut {
+    /// 解析输出. 金额在线上为无符号 8 字节, 超过 i64::MAX 的不可信数据报错, 避免得到负数金额;
+    /// 各链 MAX_MONEY 不同, 由调用方检查
+    pub(crate) fn decode(decoder: &mut Decoder) -> Result<Self, anyhow::Error> {
+        let value = decoder.read_u64()?;
+        let value = i64::try_from(value).map_err(|_| anyhow!("output value {} out of range", value))?;
+        Ok(RawTxOut {
+            value: Amount::from_sat(value),
+            script_pubkey: decoder.read_var_bytes()?,
+        })
+    }
+}
+
+impl RawTransaction {
+    pub fn from_hex(hex_str: &str) -> Result<Self, anyhow::Error> {
+        Self::from_bytes(&hex::decode(hex_str)?)
+    }
+
+    /// 解析完整交易, 不允许多余字节
+    pub fn from_bytes(data: &[u8]) -> Result<Self, anyhow::Error> {
+        let mut decoder = Decoder::new(data);
+        let tx = Self::decode(&mut decoder)?;
+        if !decoder.is_empty() {
+            bail!("{} trailing bytes after transaction", data.len() - decoder.pos);
+        }
+        Ok(tx)
+    }
+
+    pub(crate) fn decode(decoder: &mut Decoder) -> Result<Self, anyhow::Error> {
+        let version = decoder.read_u32()? as i32;
+
+        // BIP144: marker 0x00 + flag 0x01
+        let segwit = decoder.peek_u8() == Some(0);
+        if segwit {
+            decoder.read_u8()?;
+            let flag = decoder.read_u8()?;
+            if flag != 1 {
+                bail!("unknown segwit flag {}", flag);
+            }
+        }
+
+        let input_len = decoder.read_len()?;
+        let mut inputs = Vec::with_capacity(input_len);
+        for _ in 0..input_len {
+            inputs.push(RawTxIn {
+                prev_txid: decoder.read_hash()?,
+                prev_vout: decoder.read_u32()?,
+                script_sig: decoder.read_var_bytes()?,
+                sequence: decoder.read_u32()?,
+                witness: vec![],
+            });
+        }
+
+        let output_len = decoder.read_len()?;
+        let mut outputs = Vec::with_capacity(output_len);
+        for _ in 0..output_len {
+            outputs.push(RawTxOut::decode(decoder)?);
+        }
+
+        if segwit {
+            for input in inputs.iter_mut() {
+                let item_len = decoder.read_len()?;
+                for _ in 0..item_len {
+                    input.witness.push(decoder.read_var_bytes()?);
+                }
+            }
+            if inputs.iter().all(|input| input.witness.is_empty()) {
+                bail!("segwit marker set but all witnesses are empty");
+            }
+        }
+
+        Ok(RawTransaction {
+            version,
+            inputs,
+            outputs,
+            lock_time: decoder.read_u32()?,
+        })
+    }
+
+    pub fn has_witness(&self) -> bool {
+        self.inputs.iter().any(|input| !input.witness.is_empty())
+    }
+
+    pub fn is_coinbase(&self) -> bool {
+        self.inputs.len() == 1 && self.inputs[0].is_coinbase()
+    }
+
+    /// 序列化, with_witness=false 时为计算txid用的旧格式
+    pub fn encode(&self, with_witness: bool) -> Vec<u8> {
+        let with_witness = with_witness && self.has_witness();
+        let mut buf = Vec::new();
+
+        buf.extend(self.version.to_le_bytes());
+        if with_witness {
+            buf.extend([0, 1]);
+        }
+
+        write_varint(&mut buf, self.inputs.len() as u64);
+        for input in &self.inputs {
+            buf.extend(input.prev_txid);
+            buf.extend(input.prev_vout.to_le_bytes());
+            write_var_bytes(&mut buf, &input.script_sig);
+            buf.extend(input.sequence.to_le_bytes());
+        }
+
+        write_varint(&mut buf, self.outputs.len() as u64);
+        for output in &self.outputs {
+            buf.extend(output.value.to_sat().to_le_bytes());
+            write_var_bytes(&mut buf, &output.script_pubkey);
+        }
+
+        if with_witness {
+            for input in &self.inputs {
+                write_varint(&mut buf, input.witness.len() as u64);
+                for item in &input.witness {
+                    write_var_bytes(&mut buf, item);
+                }
+            }
+        }
+
+        buf.extend(self.lock_time.to_le_bytes());
+        buf
+    }
+
+    pub fn to_hex(&self) -> String {
+        hex::encode(self.encode(true))
+    }
+
+    pub fn txid(&self) -> String {
+        hash_to_hex(&sha256d(&self.encode(false)))
+    }
+
+    /// 无见证数据时与 txid 相同
+    pub fn wtxid(&self) -> String {
+        hash_to_hex(&sha256d(&self.encode(true)))
+    }
+
+    /// 含见证数据的字节数
+    pub fn size(&self) -> usize {
+        self.encode(true).len()
+    }
+
+    pub fn base_size(&self) -> usize {
+        self.encode(false).len()
+    }
+
+    pub fn weight(&self) -> usize {
+        self.base_size() * 3 + self.size()
+    }
+
+    pub fn vsize(&self) -> usize {
+        self.weight().div_ceil(4)
+    }
+}
+
+/// 80 字节区块头
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawBlockHeader {
+    pub version: i32,
+    pub prev_blockhash: [u8; 32],
+    pub merkle_root: [u8; 32],
+    pub time: u32,
+    pub bits: u32,
+    pub nonce: u32,
+}
+
+impl RawBlockHeader {
+    pub const SIZE: usize = 80;
+
+    pub fn from_hex(hex_str: &str) -> Result<Self, anyhow::Error> {
+        Self::from_bytes(&hex::decode(hex_str)?)
+    }
+
+    pub fn from_bytes(data: &[u8]) -> Result<Self, anyhow::Error> {
+        if data.len() != Self::SIZE {
+            bail!("block header must be {} bytes, got {}", Self::SIZE, data.len());
+        }
+        Self::decode(&mut Decoder::new(data))
+    }
+
+    pub(crate) fn decode(decoder: &mut Decoder) -> Result<Self, anyhow::Error> {
+        Ok(RawBlockHeader {
+            version: decoder.read_u32()? as i32,
+            prev_blockhash: decoder.read_hash()?,
+            merkle_root: decoder.read_hash()?,
+            time: decoder.read_u32()?,
+            bits: decoder.read_u32()?,
+            nonce: decoder.read_u32()?,
+        })
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(Self::SIZE);
+        buf.extend(self.version.to_le_bytes());
+        buf.extend(self.prev_blockhash);
+        buf.extend(self.merkle_root);
+        buf.extend(self.time.to_le_bytes());
+        buf.extend(self.bits.to_le_bytes());
+        buf.extend(self.nonce.to_le_bytes());
+        buf
+    }
+
+    pub fn hash(&self) -> String {
+        hash_to_hex(&sha256d(&self.encode()))
+    }
+
+    pub fn prev_blockhash_hex(&self) -> String {
+        hash_to_hex(&self.prev_blockhash)
+    }
+
+    pub fn merkle_root_hex(&self) -> String {
+        hash_to_hex(&self.merkle_root)
+    }
+
+    /// 与节点返回的 bits 字段格式一致
+    pub fn bits_hex(&self) -> String {
+        format!("{:08x}", self.bits)
+    }
+}
+
+/// 原始区块, 对应 getblock <hash> 0
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawBlock {
+    pub header: RawBlockHeader,
+    pub txdata: Vec<RawTransaction>,
+}
+
+impl RawBlock {
+    pub fn from_hex(hex_str: &str) -> Result<Self, anyhow::Error> {
+        Self::from_bytes(&hex::decode(hex_str)?)
+    }
+
+    pub fn from_bytes(data: &[u8]) -> Result<Self, anyhow::Error> {
+        let mut decoder = Decoder::new(data);
+        let header = RawBlockHeader::decode(&mut decoder)?;
+
+        let tx_len = decoder.read_len()?;
+        let mut txdata = Vec::with_capacity(tx_len);
+        for _ in 0..tx_len {
+            txdata.push(RawTransaction::decode(&mut decoder)?);
+        }
+
+        if !decoder.is_empty() {
+            bail!("{} trailing bytes after block", data.len() - decoder.pos);
+        }
+
+        Ok(RawBlock { header, txdata })
+    }
+
+    pub fn hash(&self) -> String {
+        self.header.hash()
+    }
+
+    pub fn size(&self) -> usize {
+        self.stripped_size() + self.txdata.iter().map(|tx| tx.size() - tx.base_size()).sum::<usize>()
+    }
+
+    /// 不含见证数据的字节数
+    pub fn stripped_size(&self) -> usize {
+        let mut count = Vec::new();
+        write_varint(&mut count, self.txdata.len() as u64);
+        RawBlockHeader::SIZE + count.len() + self.txdata.iter().map(|tx| tx.base_size()).sum::<usize>()
+    }
+
+    pub fn weight(&self) -> usize {
+        self.stripped_size() * 3 + self.size()
+    }
+
+    /// 由交易计算默克尔根 (内部字节序)
+    pub fn compute_merkle_root(&self) -> Option<[u8; 32]> {
+        let mut hashes = self
+            .txdata
+            .iter()
+            .map(|tx| sha256d(&tx.encode(false)))
+            .collect::<Vec<_>>();
+
+        if hashes.is_empty() {
+            return None;
+        }
+
+        while hashes.len() > 1 {
+            hashes = hashes
+                .chunks(2)
+                .map(|pair| {
+                    // 奇数个时复制最后一个
+                    let right = pair.get(1).unwrap_or(&pair[0]);
+                    sha256d(&[pair[0], *right].concat())
+                })
+                .collect();
+        }
+
+        Some(hashes[0])
+    }
+
+    pub fn check_merkle_root(&self) -> bool {
+        self.compute_merkle_root() == Some(self.header.merkle_root)
+    }
+}
+
+/// 解析节点返回交易中的 hex, 并校验 txid/hash/size/vsize/weight 与节点返回一致.
+/// 分叉链节点未返回的 vsize/weight 跳过校验
+pub fn verify_transaction(tx: &Transaction) -> Result<RawTransaction, anyhow::Error> {
+    let raw = RawTransaction::from_hex(&tx.hex)?;
+
+    let checks = [
+        ("txid", raw.txid(), Some(tx.txid.clone())),
+        ("hash", raw.wtxid(), Some(tx.hash.clone())),
+        ("size", raw.size().to_string(), Some(tx.size.to_string())),
+        ("vsize", raw.vsize().to_string(), tx.vsize.map(|v| v.to_string())),
+        ("weight", raw.weight().to_string(), tx.weight.map(|v| v.to_string())),
+    ];
+    for (field, local, node) in checks {
+        let Some(node) = node else { continue };
+        if local != node {
+            bail!("{} mismatch for {}: local {} node {}", field, tx.txid, local, node);
+        }
+    }
+
+    Ok(raw)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    pub(crate) const GENESIS_BLOCK: &str = "0100000000000000000000000000000000000000000000000000000000000000000000003ba3edfd7a7b12b27ac72c3e67768f617fc81bc3888a51323a9fb8aa4b1e5e4a29ab5f49ffff001d1dac2b7c0101000000010000000000000000000000000000000000000000000000000000000000000000ffffffff4d04ffff001d0104455468652054696d65732030332f4a616e2f32303039204368616e63656c6c6f72206f6e206272696e6b206f66207365636f6e64206261696c6f757420666f722062616e6b73ffffffff0100f2052a01000000434104678afdb0fe5548271967f1a67130b7105cd6a828e03909a67962e0ea1f61deb649f6bc3f4cef38c4f35504e51ec112de5c384df7ba0b8d578a4c702b6bf11d5fac00000000";
+
+    // BIP143 native P2WPKH 示例
+    pub(crate) const SEGWIT_TX: &str = "01000000000102fff7f7881a8099afa6940d42d1e7f6362bec38171ea3edf433541db4e4ad969f00000000494830450221008b9d1dc26ba6a9cb62127b02742fa9d754cd3bebf337f7a55d114c8e5cdd30be022040529b194ba3f9281a99f2b1c0a19c0489bc22ede944ccf4ecbab4cc618ef3ed01eeffffffef51e1b804cc89d182d279655c3aa89e815b1b309fe287d9b2b55d57b90ec68a0100000000ffffffff02202cb206000000001976a9148280b37df378db99f66f85c95a783a76ac7a6d5988ac9093510d000000001976a9143bde42dbee7e4dbe6a21b2d50ce2f0167faa815988ac000247304402203609e17b84f6a7d30c80bfa610b5b4542f32a8a0d5447a12fb1366d7f01cc44a0220573a954c4518331561406f90300e8f3358f51928d43c212a8caed02de67eebee0121025476c2e83188368da1ff3e292e7acafcdb3566bb0ad253f62fc70f07aeee635711000000";
+
+    #[test]
+    fn test_genesis_block() {
+        let block = RawBlock::from_hex(GENESIS_BLOCK).unwrap();
+
+        assert_eq!(block.hash(), "000000000019d6689c085ae165831e934ff763ae46a2a6c172b3f1b60a8ce26f");
+        assert_eq!(block.header.bits_hex(), "1d00ffff");
+        assert_eq!(block.txdata.len(), 1);
+        assert!(block.txdata[0].is_coinbase());
+        assert_eq!(block.txdata[0].txid(), "4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b");
+        assert_eq!(block.txdata[0].outputs[0].value, Amount::from_sat(50 * 100_000_000));
+        assert!(block.check_merkle_root());
+        assert_eq!(block.size(), 285);
+        assert_eq!(block.weight(), 285 * 4);
+    }
+
+    #[test]
+    fn test_segwit_tx() {
+        let tx = RawTransaction::from_hex(SEGWIT_TX).unwrap();
+
+        assert_eq!(tx.to_hex(), SEGWIT_TX);
+        assert!(tx.has_witness());
+        assert_eq!(tx.inputs.len(), 2);
+        assert!(tx.inputs[0].witness.is_empty());
+        assert_eq!(tx.inputs[1].witness.len(), 2);
+        assert_eq!(tx.lock_time, 0x11);
+        assert_eq!(tx.txid(), "e8151a2af31c368a35053ddd4bdb285a8595c769a3ad83e0fa02314a602d4609");
+        assert_eq!(tx.wtxid(), "c36c38370907df2324d9ce9d149d191192f338b37665a82e78e76a12c909b762");
+        assert_eq!((tx.base_size(), tx.size()), (233, 343));
+        assert_eq!(tx.weight(), 1042);
+        assert_eq!(tx.vsize(), 261);
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert!(RawTransaction::from_hex(&SEGWIT_TX[..SEGWIT_TX.len() - 2]).is_err());
+        assert!(RawTransaction::from_hex(&format!("{}00", SEGWIT_TX)).is_err());
+        // 输入数量远超剩余数据
+        assert!(RawTransaction::from_hex("01000000ff0000000000000010").is_err());
+
+        // 输出金额超过 i64::MAX
+        let tx = |value: &str| format!("0100000001{}ffffffff00ffffffff01{}0000000000", "00".repeat(32), value);
+        assert!(RawTransaction::from_hex(&tx("ffffffffffffff7f")).is_ok());
+        assert!(RawTransaction::from_hex(&tx("0000000000000080")).is_err());
+    }
+}
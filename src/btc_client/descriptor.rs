@@ -0,0 +1,602 @@
+use std::{fmt, ops::Range, str::FromStr};
+
+use anyhow::{anyhow, bail};
+use sha2::{Digest, Sha256};
+
+use crate::utils::{
+    address_convert::hash160,
+    bip32::{format_path, parse_path, tweak_add, ExtendedPubKey},
+};
+
+use super::{network::NetworkParams, psbt::KeySource, script::ScriptType};
+
+const INPUT_CHARSET: &str =
+    "0123456789()[],'/*abcdefgh@:$%{}IJKLMNOPQRSTUVWXYZ&+-.;<=>?!^_|~ijklmnopqrstuvwxyzABCDEFGH`#\"\\ ";
+const CHECKSUM_CHARSET: &[u8] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+
+fn checksum_polymod(symbols: &[u64]) -> u64 {
+    const GEN: [u64; 5] = [0xf5dee51989, 0xa9fdca3312, 0x1bab10e32d, 0x3706b1677a, 0x644d626ffd];
+
+    let mut chk: u64 = 1;
+    for &value in symbols {
+        let top = chk >> 35;
+        chk = ((chk & 0x7ffffffff) << 5) ^ value;
+        for (i, g) in GEN.iter().enumerate() {
+            if (top >> i) & 1 == 1 {
+                chk ^= g;
+            }
+        }
+    }
+    chk
+}
+
+/// 描述符校验和 (BIP-380), 即 '#' 之后的 8 个字符
+pub fn descriptor_checksum(desc: &str) -> Result<String, anyhow::Error> {
+    let mut symbols = Vec::with_capacity(desc.len() * 2);
+    let mut groups = Vec::with_capacity(3);
+    for c in desc.chars() {
+        let value = INPUT_CHARSET
+            .find(c)
+            .ok_or_else(|| anyhow!("invalid descriptor character '{}'", c))? as u64;
+        symbols.push(value & 31);
+        groups.push(value >> 5);
+        if groups.len() == 3 {
+            symbols.push(groups[0] * 9 + groups[1] * 3 + groups[2]);
+            groups.clear();
+        }
+    }
+    match groups.as_slice() {
+        [a] => symbols.push(*a),
+        [a, b] => symbols.push(a * 3 + b),
+        _ => {}
+    }
+    symbols.extend([0; 8]);
+
+    let chk = checksum_polymod(&symbols) ^ 1;
+    Ok((0..8)
+        .map(|i| CHECKSUM_CHARSET[((chk >> (5 * (7 - i))) & 31) as usize] as char)
+        .collect())
+}
+
+fn tagged_hash(tag: &str, data: &[u8]) -> [u8; 32] {
+    let tag = Sha256::digest(tag.as_bytes());
+    Sha256::new()
+        .chain_update(tag)
+        .chain_update(tag)
+        .chain_update(data)
+        .finalize()
+        .into()
+}
+
+/// BIP-86 taproot 输出公钥: 内部公钥按偶数 y 提升后加上 H_TapTweak(P) * G, 无脚本树
+pub fn taproot_output_key(internal_key: &[u8; 32]) -> Result<[u8; 32], anyhow::Error> {
+    let mut key = vec![0x02];
+    key.extend(internal_key);
+    let tweaked = tweak_add(&key, &tagged_hash("TapTweak", internal_key))?;
+    Ok(tweaked[1..].try_into()?)
+}
+
+// 公钥所在的脚本上下文, 决定允许的公钥格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum KeyContext {
+    /// pkh / sh(multi), 允许非压缩公钥
+    Legacy,
+    /// 隔离见证 v0, 只允许压缩公钥
+    Segwit,
+    /// tr, 固定公钥为 32 字节 x-only
+    Taproot,
+}
+
+/// 描述符中的公钥表达式
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DescriptorKey {
+    /// 固定公钥, tr 中为 32 字节 x-only
+    Single { origin: Option<KeySource>, key: Vec<u8> },
+    /// xpub/tpub 加非硬化路径, wildcard 时末尾为 /*
+    Extended {
+        origin: Option<KeySource>,
+        xpub: ExtendedPubKey,
+        path: Vec<u32>,
+        wildcard: bool,
+        /// xpub 沿 path 派生后的密钥, 派生地址时只需再派生一级
+        base: ExtendedPubKey,
+    },
+}
+
+impl DescriptorKey {
+    fn parse(s: &str, ctx: KeyContext) -> Result<Self, anyhow::Error> {
+        let (origin, key) = match s.strip_prefix('[') {
+            Some(rest) => {
+                let (origin, key) = rest
+                    .split_once(']')
+                    .ok_or_else(|| anyhow!("unclosed key origin in {}", s))?;
+                (Some(parse_origin(origin)?), key)
+            }
+            None => (None, s),
+        };
+
+        let mut steps = key.split('/');
+        let key = steps.next().unwrap_or_default();
+        let steps = steps.collect::<Vec<_>>();
+
+        let is_hex = key.len() % 2 == 0 && key.bytes().all(|b| b.is_ascii_hexdigit());
+        if is_hex && key.len() <= 130 {
+            if !steps.is_empty() {
+                bail!("derivation path after non-extended key {}", key);
+            }
+            let key = hex::decode(key)?;
+            let valid = match (ctx, key.len()) {
+                (KeyContext::Taproot, len) => len == 32,
+                (_, 33) => key[0] == 0x02 || key[0] == 0x03,
+                (KeyContext::Legacy, 65) => key[0] == 0x04,
+                (KeyContext::Segwit, 65) => bail!("uncompressed key not allowed in segwit: {}", hex::encode(&key)),
+                _ => false,
+            };
+            if !valid {
+                bail!("invalid public key {}", hex::encode(&key));
+            }
+            return Ok(DescriptorKey::Single { origin, key });
+        }
+
+        if key.starts_with("xprv") || key.starts_with("tprv") {
+            bail!("private keys are not supported");
+        }
+        let xpub: ExtendedPubKey = key.parse()?;
+        if xpub.to_standard() != xpub {
+            bail!("SLIP-132 keys are not allowed in descriptors, use xpub/tpub");
+        }
+
+        let (steps, wildcard) = match steps.split_last() {
+            Some((&"*", steps)) => (steps, true),
+            Some((last, _)) if last.starts_with('*') => bail!("hardened wildcard requires private key"),
+            _ => (steps.as_slice(), false),
+        };
+        if steps.iter().any(|step| step.starts_with('<')) {
+            bail!("multipath key expressions are not supported");
+        }
+        let path = parse_path(&steps.join("/"))?;
+        let base = xpub.derive_path(&path)?;
+
+        Ok(DescriptorKey::Extended {
+            origin,
+            xpub,
+            path,
+            wildcard,
+            base,
+        })
+    }
+
+    pub fn is_range(&self) -> bool {
+        matches!(self, DescriptorKey::Extended { wildcard: true, .. })
+    }
+
+    /// 派生公钥, 非范围公钥忽略 index
+    pub fn derive(&self, index: u32) -> Result<Vec<u8>, anyhow::Error> {
+        match self {
+            DescriptorKey::Single { key, .. } => Ok(key.clone()),
+            DescriptorKey::Extended {
+                base, wildcard: true, ..
+            } => Ok(base.derive_child(index)?.public_key.to_vec()),
+            DescriptorKey::Extended { base, .. } => Ok(base.public_key.to_vec()),
+        }
+    }
+}
+
+fn parse_origin(origin: &str) -> Result<KeySource, anyhow::Error> {
+    let (fingerprint, path) = origin.split_once('/').unwrap_or((origin, ""));
+    let fingerprint = hex::decode(fingerprint)?
+        .try_into()
+        .map_err(|_| anyhow!("key origin fingerprint must be 4 bytes"))?;
+
+    Ok(KeySource {
+        fingerprint,
+        path: parse_path(path)?,
+    })
+}
+
+impl fmt::Display for DescriptorKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let origin = match self {
+            DescriptorKey::Single { origin, .. } | DescriptorKey::Extended { origin, .. } => origin,
+        };
+        if let Some(origin) = origin {
+            write!(f, "[{}", hex::encode(origin.fingerprint))?;
+            if !origin.path.is_empty() {
+                write!(f, "/{}", format_path(&origin.path))?;
+            }
+            write!(f, "]")?;
+        }
+
+        match self {
+            DescriptorKey::Single { key, .. } => write!(f, "{}", hex::encode(key)),
+            DescriptorKey::Extended {
+                xpub, path, wildcard, ..
+            } => {
+                write!(f, "{}", xpub)?;
+                if !path.is_empty() {
+                    write!(f, "/{}", format_path(path))?;
+                }
+                if *wildcard {
+                    write!(f, "/*")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// multi / sortedmulti
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Multisig {
+    pub threshold: usize,
+    pub keys: Vec<DescriptorKey>,
+    /// sortedmulti 按派生后的公钥字典序排列
+    pub sorted: bool,
+}
+
+impl Multisig {
+    // 超过 16 个公钥需要数字 push 编码, 暂不支持; P2SH 受 520 字节脚本限制最多 15 个
+    fn parse(name: &str, args: &str, ctx: KeyContext, max_keys: usize) -> Result<Self, anyhow::Error> {
+        let sorted = match name {
+            "multi" => false,
+            "sortedmulti" => true,
+            _ => bail!("expected multi or sortedmulti, got {}", name),
+        };
+
+        let args = split_args(args);
+        let (threshold, keys) = args.split_first().ok_or_else(|| anyhow!("empty {}", name))?;
+        let threshold: usize = threshold
+            .parse()
+            .map_err(|_| anyhow!("invalid threshold {}", threshold))?;
+        let keys = keys
+            .iter()
+            .map(|key| DescriptorKey::parse(key, ctx))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if keys.is_empty() || keys.len() > max_keys {
+            bail!("{} requires 1 to {} keys, got {}", name, max_keys, keys.len());
+        }
+        if threshold == 0 || threshold > keys.len() {
+            bail!("invalid threshold {} of {}", threshold, keys.len());
+        }
+
+        Ok(Multisig {
+            threshold,
+            keys,
+            sorted,
+        })
+    }
+
+    fn script(&self, index: u32) -> Result<Vec<u8>, anyhow::Error> {
+        let mut pubkeys = self
+            .keys
+            .iter()
+            .map(|key| key.derive(index))
+            .collect::<Result<Vec<_>, _>>()?;
+        if self.sorted {
+            pubkeys.sort();
+        }
+
+        ScriptType::Multisig {
+            required: u8::try_from(self.threshold)?,
+            pubkeys,
+        }
+        .script_pubkey()
+        .ok_or_else(|| anyhow!("invalid multisig {} of {}", self.threshold, self.keys.len()))
+    }
+}
+
+impl fmt::Display for Multisig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = if self.sorted { "sortedmulti" } else { "multi" };
+        write!(f, "{}({}", name, self.threshold)?;
+        for key in &self.keys {
+            write!(f, ",{}", key)?;
+        }
+        write!(f, ")")
+    }
+}
+
+/// 输出描述符, 支持 pkh, wpkh, sh(wpkh), sh(multi), wsh(multi), sh(wsh(multi)) 和 tr (仅密钥路径).
+/// 只包含公钥, 可离线派生地址, 与 importdescriptors 导入的描述符得到相同地址
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Descriptor {
+    Pkh(DescriptorKey),
+    Wpkh(DescriptorKey),
+    ShWpkh(DescriptorKey),
+    Sh(Multisig),
+    Wsh(Multisig),
+    ShWsh(Multisig),
+    Tr(DescriptorKey),
+}
+
+// name(args) 拆分
+fn split_call(s: &str) -> Result<(&str, &str), anyhow::Error> {
+    let (name, rest) = s.split_once('(').ok_or_else(|| anyhow!("expected function in {}", s))?;
+    let args = rest
+        .strip_suffix(')')
+        .ok_or_else(|| anyhow!("unbalanced parentheses in {}", s))?;
+    Ok((name, args))
+}
+
+// 按顶层逗号拆分参数
+fn split_args(s: &str) -> Vec<&str> {
+    let mut res = Vec::new();
+    let mut depth = 0i32;
+    let mut start = 0;
+    for (i, c) in s.char_indices() {
+        match c {
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => depth -= 1,
+            ',' if depth == 0 => {
+                res.push(&s[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    res.push(&s[start..]);
+    res
+}
+
+impl Descriptor {
+    pub fn parse(s: &str) -> Result<Self, anyhow::Error> {
+        s.parse()
+    }
+
+    /// 是否包含 /* 通配符, 非范围描述符只对应一个地址
+    pub fn is_range(&self) -> bool {
+        match self {
+            Descriptor::Pkh(key) | Descriptor::Wpkh(key) | Descriptor::ShWpkh(key) | Descriptor::Tr(key) => {
+                key.is_range()
+            }
+            Descriptor::Sh(multi) | Descriptor::Wsh(multi) | Descriptor::ShWsh(multi) => {
+                multi.keys.iter().any(DescriptorKey::is_range)
+            }
+        }
+    }
+
+    /// 第 index 个输出的脚本类型
+    pub fn script_type(&self, index: u32) -> Result<ScriptType, anyhow::Error> {
+        let script_type = match self {
+            Descriptor::Pkh(key) => ScriptType::P2pkh {
+                hash: hash160(&key.derive(index)?),
+            },
+            Descriptor::Wpkh(key) => ScriptType::P2wpkh {
+                program: hash160(&key.derive(index)?),
+            },
+            Descriptor::ShWpkh(key) => {
+                let redeem = ScriptType::P2wpkh {
+                    program: hash160(&key.derive(index)?),
+                };
+                ScriptType::P2sh {
+                    hash: hash160(&redeem.script_pubkey().ok_or_else(|| anyhow!("invalid redeem script"))?),
+                }
+            }
+            Descriptor::Sh(multi) => ScriptType::P2sh {
+                hash: hash160(&multi.script(index)?),
+            },
+            Descriptor::Wsh(multi) => ScriptType::P2wsh {
+                program: Sha256::digest(multi.script(index)?).into(),
+            },
+            Descriptor::ShWsh(multi) => {
+                let redeem = ScriptType::P2wsh {
+                    program: Sha256::digest(multi.script(index)?).into(),
+                };
+                ScriptType::P2sh {
+                    hash: hash160(&redeem.script_pubkey().ok_or_else(|| anyhow!("invalid redeem script"))?),
+                }
+            }
+            Descriptor::Tr(key) => {
+                let key = key.derive(index)?;
+                // xpub 派生得到压缩公钥, 取 x 坐标
+                let internal_key = key[key.len() - 32..].try_into()?;
+                ScriptType::P2tr {
+                    output_key: taproot_output_key(&internal_key)?,
+                }
+            }
+        };
+        Ok(script_type)
+    }
+
+    pub fn script_pubkey(&self, index: u32) -> Result<Vec<u8>, anyhow::Error> {
+        self.script_type(index)?
+            .script_pubkey()
+            .ok_or_else(|| anyhow!("invalid script type"))
+    }
+
+    pub fn address(&self, index: u32, params: &NetworkParams) -> Result<String, anyhow::Error> {
+        self.script_type(index)?
+            .address(params)
+            .ok_or_else(|| anyhow!("descriptor has no address"))
+    }
+
+    /// 批量派生地址, 用于预生成充值地址集合. 非范围描述符只返回一个地址
+    pub fn addresses(&self, range: Range<u32>, params: &NetworkParams) -> Result<Vec<String>, anyhow::Error> {
+        if !self.is_range() {
+            return Ok(vec![self.address(0, params)?]);
+        }
+        range.map(|index| self.address(index, params)).collect()
+    }
+
+    /// 带校验和的描述符字符串, 可直接用于 importdescriptors
+    pub fn to_string_with_checksum(&self) -> String {
+        let desc = self.to_string();
+        // Display 只输出校验字符集内的字符
+        let checksum = descriptor_checksum(&desc).unwrap_or_default();
+        format!("{}#{}", desc, checksum)
+    }
+}
+
+impl FromStr for Descriptor {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let desc = match s.split_once('#') {
+            Some((desc, checksum)) => {
+                if descriptor_checksum(desc)? != checksum {
+                    bail!("descriptor checksum mismatch: {}", checksum);
+                }
+                desc
+            }
+            None => {
+                descriptor_checksum(s)?;
+                s
+            }
+        };
+
+        let (name, args) = split_call(desc)?;
+        let descriptor = match name {
+            "pkh" => Descriptor::Pkh(DescriptorKey::parse(args, KeyContext::Legacy)?),
+            "wpkh" => Descriptor::Wpkh(DescriptorKey::parse(args, KeyContext::Segwit)?),
+            "tr" => {
+                if split_args(args).len() > 1 {
+                    bail!("taproot script trees are not supported");
+                }
+                Descriptor::Tr(DescriptorKey::parse(args, KeyContext::Taproot)?)
+            }
+            "wsh" => {
+                let (inner, inner_args) = split_call(args)?;
+                Descriptor::Wsh(Multisig::parse(inner, inner_args, KeyContext::Segwit, 16)?)
+            }
+            "sh" => {
+                let (inner, inner_args) = split_call(args)?;
+                match inner {
+                    "wpkh" => Descriptor::ShWpkh(DescriptorKey::parse(inner_args, KeyContext::Segwit)?),
+                    "wsh" => {
+                        let (multi, multi_args) = split_call(inner_args)?;
+                        Descriptor::ShWsh(Multisig::parse(multi, multi_args, KeyContext::Segwit, 16)?)
+                    }
+                    _ => Descriptor::Sh(Multisig::parse(inner, inner_args, KeyContext::Legacy, 15)?),
+                }
+            }
+            _ => bail!("unsupported descriptor {}", name),
+        };
+
+        Ok(descriptor)
+    }
+}
+
+impl fmt::Display for Descriptor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Descriptor::Pkh(key) => write!(f, "pkh({})", key),
+            Descriptor::Wpkh(key) => write!(f, "wpkh({})", key),
+            Descriptor::ShWpkh(key) => write!(f, "sh(wpkh({}))", key),
+            Descriptor::Sh(multi) => write!(f, "sh({})", multi),
+            Descriptor::Wsh(multi) => write!(f, "wsh({})", multi),
+            Descriptor::ShWsh(multi) => write!(f, "sh(wsh({}))", multi),
+            Descriptor::Tr(key) => write!(f, "tr({})", key),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::btc_client::network::Network;
+
+    use super::*;
+
+    // "abandon ... about" 助记词的 BIP-44/49/84/86 账户 xpub
+    const XPUB_44: &str = "xpub6BosfCnifzxcFwrSzQiqu2DBVTshkCXacvNsWGYJVVhhawA7d4R5WSWGFNbi8Aw6ZRc1brxMyWMzG3DSSSSoekkudhUd9yLb6qx39T9nMdj";
+    const XPUB_49: &str = "xpub6C6nQwHaWbSrzs5tZ1q7m5R9cPK9eYpNMFesiXsYrgc1P8bvLLAet9JfHjYXKjToD8cBRswJXXbbFpXgwsswVPAZzKMa1jUp2kVkGVUaJa7";
+    const XPUB_84: &str = "xpub6CatWdiZiodmUeTDp8LT5or8nmbKNcuyvz7WyksVFkKB4RHwCD3XyuvPEbvqAQY3rAPshWcMLoP2fMFMKHPJ4ZeZXYVUhLv1VMrjPC7PW6V";
+    const XPUB_86: &str = "xpub6BgBgsespWvERF3LHQu6CnqdvfEvtMcQjYrcRzx53QJjSxarj2afYWcLteoGVky7D3UKDP9QyrLprQ3VCECoY49yfdDEHGCtMMj92pReUsQ";
+
+    #[test]
+    fn test_checksum() {
+        assert_eq!(descriptor_checksum("raw(deadbeef)").unwrap(), "89f8spxm");
+        assert_eq!(
+            descriptor_checksum("pkh(02c6047f9441ed7d6d3045406e95c07cd85c778e4b8cef3ca7abac09b95c709ee5)").unwrap(),
+            "8fhd9pwu"
+        );
+
+        let desc = "wpkh([d34db33f/84h/0h/0h]xpub6DJ2dNUysrn5Vt36jH2KLBT2i1auw1tTSSomg8PhqNiUtx8QX2SvC9nrHu81fT41fvDUnhMjEzQgXnQjKEu3oaqMSzhSrHMxyyoEAmUHQbY/0/*)";
+        let parsed = Descriptor::parse(&format!("{}#cjjspncu", desc)).unwrap();
+        assert_eq!(parsed.to_string_with_checksum(), format!("{}#cjjspncu", desc));
+        assert!(Descriptor::parse(&format!("{}#cjjspncv", desc)).is_err());
+    }
+
+    #[test]
+    fn test_addresses() {
+        let main = Network::Bitcoin.params();
+        let cases = [
+            (
+                format!("pkh([73c5da0a/44'/0'/0']{}/0/*)", XPUB_44),
+                "1LqBGSKuX5yYUonjxT5qGfpUsXKYYWeabA",
+            ),
+            (
+                format!("sh(wpkh({}/0/*))", XPUB_49),
+                "37VucYSaXLCAsxYyAPfbSi9eh4iEcbShgf",
+            ),
+            (
+                format!("wpkh({}/0/*)", XPUB_84),
+                "bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu",
+            ),
+            (
+                format!("tr({}/0/*)", XPUB_86),
+                "bc1p5cyxnuxmeuwuvkwfem96lqzszd02n6xdcjrs20cac6yqjjwudpxqkedrcr",
+            ),
+            (
+                "tr(cc8a4bc64d897bddc5fbc2f670f7a8ba0b386779106cf1223c6fc5d7cd6fc115)".to_string(),
+                "bc1p5cyxnuxmeuwuvkwfem96lqzszd02n6xdcjrs20cac6yqjjwudpxqkedrcr",
+            ),
+        ];
+        for (desc, address) in cases {
+            let parsed = Descriptor::parse(&desc).unwrap();
+            assert_eq!(parsed.address(0, &main).unwrap(), address, "{}", desc);
+            assert_eq!(Descriptor::parse(&parsed.to_string()).unwrap(), parsed);
+        }
+
+        let desc = Descriptor::parse(&format!("wpkh({}/0/*)", XPUB_84)).unwrap();
+        let addresses = desc.addresses(0..3, &main).unwrap();
+        assert_eq!(addresses.len(), 3);
+        assert_eq!(addresses[0], "bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu");
+        assert_eq!(addresses[1], desc.address(1, &main).unwrap());
+        assert_ne!(addresses[1], addresses[2]);
+
+        let desc = Descriptor::parse(&format!("wpkh({}/0/0)", XPUB_84)).unwrap();
+        assert!(!desc.is_range());
+        assert_eq!(
+            desc.addresses(0..10, &main).unwrap(),
+            vec!["bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu"]
+        );
+    }
+
+    #[test]
+    fn test_multisig() {
+        let keys = format!("{}/0/*,{}/0/*,{}/0/*", XPUB_84, XPUB_44, XPUB_49);
+        let sorted = Descriptor::parse(&format!("wsh(sortedmulti(2,{}))", keys)).unwrap();
+        assert_eq!(
+            hex::encode(sorted.script_pubkey(0).unwrap()),
+            "0020b26ffacb557f242fb7eba0424f902a49ef1ac4c9f145405cb64a1ca33f32fb38"
+        );
+        let unsorted = Descriptor::parse(&format!("wsh(multi(2,{}))", keys)).unwrap();
+        assert_ne!(unsorted.script_pubkey(0).unwrap(), sorted.script_pubkey(0).unwrap());
+
+        let nested = Descriptor::parse(&format!("sh(wsh(sortedmulti(2,{})))", keys)).unwrap();
+        assert!(matches!(nested.script_type(5).unwrap(), ScriptType::P2sh { .. }));
+        assert!(Descriptor::parse(&format!("sh(multi(1,{}))", keys)).is_ok());
+    }
+
+    #[test]
+    fn test_invalid() {
+        let invalid = [
+            format!("wpkh({}/0h/*)", XPUB_84),                // xpub 之后的硬化派生
+            format!("wpkh({}/0/*')", XPUB_84),                // 硬化通配符
+            format!("wpkh({}/<0;1>/*)", XPUB_84),             // 多路径
+            format!("wsh(multi(3,{}/0/*,{}/0/*))", XPUB_84, XPUB_44),
+            format!("tr({},{{pk({})}})", XPUB_86, XPUB_84),  // 脚本树
+            format!("combo({})", XPUB_84),
+            format!("wpkh(04{})", "11".repeat(64)),           // 隔离见证中的非压缩公钥
+            format!("tr(02{})", "11".repeat(32)),             // tr 固定公钥需为 x-only
+            format!("wpkh({})", XPUB_84.replace('W', "w")),
+            "wpkh(zpub6rFR7y4Q2AijBEqTUquhVz398htDFrtymD9xYYfG1m4wAcvPhXNfE3EfH1r1ADqtfSdVCToUG868RvUUkgDKf31mGDtKsAYz2oz2AGutZYs/0/*)".to_string(),
+            "wpkh(xprv9s21ZrQH143K3QTDL4LXw2F7HEK3wJUD2nW2nRk4stbPy6cq3jPPqjiChkVvvNKmPGJxWUtg6LnF5kejMRNNU3TGtRBeJgk33yuGBxrMPHi)".to_string(),
+        ];
+        for desc in invalid {
+            assert!(Descriptor::parse(&desc).is_err(), "{}", desc);
+        }
+    }
+}
@@ -0,0 +1,201 @@
+use std::{
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use reqwest::header::HeaderValue;
+
+use super::error::BtcRpcError;
+
+/// 节点 RPC 认证方式
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BtcAuth {
+    /// 不认证, 如经由已认证的代理访问
+    None,
+    UserPass {
+        username: String,
+        password: String,
+    },
+    /// 节点未配置 rpcuser/rpcpassword 时在数据目录生成的 .cookie 文件.
+    /// 节点每次启动都会重新生成, 认证失败 (401) 时重新读取
+    CookieFile(PathBuf),
+}
+
+impl BtcAuth {
+    pub fn user_pass(username: &str, password: &str) -> Self {
+        BtcAuth::UserPass {
+            username: username.to_owned(),
+            password: password.to_owned(),
+        }
+    }
+
+    pub fn cookie_file(path: impl Into<PathBuf>) -> Self {
+        BtcAuth::CookieFile(path.into())
+    }
+
+    /// 生成 Basic 认证头, 不认证时为 None. cookie 文件内容即为 `__cookie__:<密码>`
+    pub fn header(&self) -> Result<Option<HeaderValue>, BtcRpcError> {
+        let credentials = match self {
+            BtcAuth::None => return Ok(None),
+            BtcAuth::UserPass { username, password } => format!("{}:{}", username, password),
+            BtcAuth::CookieFile(path) => std::fs::read_to_string(path)
+                .map_err(|err| BtcRpcError::Auth(format!("read cookie {}: {}", path.display(), err)))?
+                .trim()
+                .to_string(),
+        };
+
+        let mut header = HeaderValue::from_str(&format!("Basic {}", STANDARD.encode(credentials)))
+            .map_err(|err| BtcRpcError::Auth(err.to_string()))?;
+        header.set_sensitive(true);
+        Ok(Some(header))
+    }
+}
+
+/// 节点地址及其认证方式
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BtcEndpoint {
+    /// 如 http://127.0.0.1:8332
+    pub url: String,
+    pub auth: BtcAuth,
+}
+
+impl BtcEndpoint {
+    pub fn new(url: &str, auth: BtcAuth) -> Self {
+        BtcEndpoint {
+            url: url.to_owned(),
+            auth,
+        }
+    }
+}
+
+/// 节点健康状态
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EndpointStatus {
+    pub url: String,
+    /// 不在失败冷却期内
+    pub healthy: bool,
+    /// 连续失败次数
+    pub failures: u32,
+}
+
+/// 节点运行时状态, 由 BtcClient 的各个克隆 (含 wallet() 得到的客户端) 共享
+#[derive(Debug)]
+pub(crate) struct EndpointState {
+    pub(crate) endpoint: BtcEndpoint,
+    // 缓存的认证头, cookie 轮换后重新读取
+    header: Mutex<Option<HeaderValue>>,
+    failures: AtomicU32,
+    down_until: Mutex<Option<Instant>>,
+}
+
+impl EndpointState {
+    pub(crate) fn new(endpoint: BtcEndpoint) -> Self {
+        EndpointState {
+            endpoint,
+            header: Mutex::new(None),
+            failures: AtomicU32::new(0),
+            down_until: Mutex::new(None),
+        }
+    }
+
+    /// 认证头, reload 为 true 时重新生成 (重新读取 cookie 文件)
+    pub(crate) fn auth_header(&self, reload: bool) -> Result<Option<HeaderValue>, BtcRpcError> {
+        let mut header = self.header.lock().unwrap();
+        if reload || header.is_none() {
+            *header = self.endpoint.auth.header()?;
+        }
+        Ok(header.clone())
+    }
+
+    pub(crate) fn is_cookie(&self) -> bool {
+        matches!(self.endpoint.auth, BtcAuth::CookieFile(_))
+    }
+
+    pub(crate) fn is_healthy(&self) -> bool {
+        match *self.down_until.lock().unwrap() {
+            Some(until) => Instant::now() >= until,
+            None => true,
+        }
+    }
+
+    /// 记一次失败, cooldown 内不再优先选择该节点
+    pub(crate) fn mark_failed(&self, cooldown: Duration) {
+        self.failures.fetch_add(1, Ordering::Relaxed);
+        *self.down_until.lock().unwrap() = Some(Instant::now() + cooldown);
+    }
+
+    pub(crate) fn mark_ok(&self) {
+        self.failures.store(0, Ordering::Relaxed);
+        *self.down_until.lock().unwrap() = None;
+    }
+
+    pub(crate) fn status(&self) -> EndpointStatus {
+        EndpointStatus {
+            url: self.endpoint.url.clone(),
+            healthy: self.is_healthy(),
+            failures: self.failures.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use serde_json::json;
+
+    use super::*;
+    use crate::{
+        btc_client::BtcClient,
+        utils::test_node::{down_node, fake_http},
+    };
+
+    #[async_std::test]
+    async fn test_cookie_failover() {
+        let down = down_node().await;
+        // Authorization 与 expected 一致时返回区块高度, 否则 401
+        let expected = Arc::new(Mutex::new("__cookie__:old".to_string()));
+        let auth = expected.clone();
+        let (up, _) = fake_http(move |request| {
+            let auth = format!("authorization: basic {}", STANDARD.encode(&*auth.lock().unwrap()));
+            if request.headers.contains(&auth.to_lowercase()) {
+                (200, json!({"result": 100, "error": null, "id": 1}).to_string())
+            } else {
+                (401, String::new())
+            }
+        })
+        .await;
+
+        let cookie = std::env::temp_dir().join(format!("btc_cookie_{}", std::process::id()));
+        std::fs::write(&cookie, "__cookie__:old\n").unwrap();
+        let endpoints = vec![
+            BtcEndpoint::new(&down, BtcAuth::user_pass("user", "pass")),
+            BtcEndpoint::new(&up, BtcAuth::cookie_file(&cookie)),
+        ];
+        let client = BtcClient::with_endpoints(endpoints, Duration::from_secs(2)).unwrap();
+
+        // 第一个节点不可用, 切换到第二个
+        assert_eq!(client.get_block_count_result().await.unwrap(), 100);
+        assert_eq!(client.url(), up);
+        let status = client.endpoint_status();
+        assert!(!status[0].healthy && status[0].failures == 1);
+        assert!(status[1].healthy);
+
+        // 节点重启, cookie 轮换
+        *expected.lock().unwrap() = "__cookie__:new".to_string();
+        std::fs::write(&cookie, "__cookie__:new\n").unwrap();
+        assert_eq!(client.wallet("w").get_block_count_result().await.unwrap(), 100);
+
+        std::fs::remove_file(&cookie).unwrap();
+        assert!(matches!(
+            BtcAuth::cookie_file(&cookie).header(),
+            Err(BtcRpcError::Auth(_))
+        ));
+        assert!(BtcClient::with_endpoints(vec![], Duration::from_secs(1)).is_err());
+    }
+}
@@ -0,0 +1,399 @@
+use std::fmt;
+
+use reqwest::StatusCode;
+use serde::de::DeserializeOwned;
+
+use crate::utils::rpc_batch::BatchError;
+
+use super::response_type::{JsonError, JsonResponse};
+
+/// Bitcoin Core 常用的 RPC 错误码, 见 src/rpc/protocol.h
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RpcErrorCode {
+    // 标准 JSON-RPC 2.0
+    InvalidRequest,
+    MethodNotFound,
+    InvalidParams,
+    InternalError,
+    ParseError,
+    // 通用
+    MiscError,
+    TypeError,
+    InvalidAddressOrKey,
+    OutOfMemory,
+    InvalidParameter,
+    DatabaseError,
+    DeserializationError,
+    VerifyError,
+    VerifyRejected,
+    VerifyAlreadyInChain,
+    InWarmup,
+    MethodDeprecated,
+    // P2P
+    ClientNotConnected,
+    ClientInInitialDownload,
+    // 钱包
+    WalletError,
+    WalletInsufficientFunds,
+    WalletNotFound,
+    WalletNotSpecified,
+    Other(i32),
+}
+
+impl RpcErrorCode {
+    pub fn code(&self) -> i32 {
+        match self {
+            RpcErrorCode::InvalidRequest => -32600,
+            RpcErrorCode::MethodNotFound => -32601,
+            RpcErrorCode::InvalidParams => -32602,
+            RpcErrorCode::InternalError => -32603,
+            RpcErrorCode::ParseError => -32700,
+            RpcErrorCode::MiscError => -1,
+            RpcErrorCode::TypeError => -3,
+            RpcErrorCode::InvalidAddressOrKey => -5,
+            RpcErrorCode::OutOfMemory => -7,
+            RpcErrorCode::InvalidParameter => -8,
+            RpcErrorCode::DatabaseError => -20,
+            RpcErrorCode::DeserializationError => -22,
+            RpcErrorCode::VerifyError => -25,
+            RpcErrorCode::VerifyRejected => -26,
+            RpcErrorCode::VerifyAlreadyInChain => -27,
+            RpcErrorCode::InWarmup => -28,
+            RpcErrorCode::MethodDeprecated => -32,
+            RpcErrorCode::ClientNotConnected => -9,
+            RpcErrorCode::ClientInInitialDownload => -10,
+            RpcErrorCode::WalletError => -4,
+            RpcErrorCode::WalletInsufficientFunds => -6,
+            RpcErrorCode::WalletNotFound => -18,
+            RpcErrorCode::WalletNotSpecified => -19,
+            RpcErrorCode::Other(code) => *code,
+        }
+    }
+}
+
+impl From<i32> for RpcErrorCode {
+    fn from(code: i32) -> Self {
+        match code {
+            -32600 => RpcErrorCode::InvalidRequest,
+            -32601 => RpcErrorCode::MethodNotFound,
+            -32602 => RpcErrorCode::InvalidParams,
+            -32603 => RpcErrorCode::InternalError,
+            -32700 => RpcErrorCode::ParseError,
+            -1 => RpcErrorCode::MiscError,
+            -3 => RpcErrorCode::TypeError,
+            -5 => RpcErrorCode::InvalidAddressOrKey,
+            -7 => RpcErrorCode::OutOfMemory,
+            -8 => RpcErrorCode::InvalidParameter,
+            -20 => RpcErrorCode::DatabaseError,
+            -22 => RpcErrorCode::DeserializationError,
+            -25 => RpcErrorCode::VerifyError,
+            -26 => RpcErrorCode::VerifyRejected,
+            -27 => RpcErrorCode::VerifyAlreadyInChain,
+            -28 => RpcErrorCode::InWarmup,
+            -32 => RpcErrorCode::MethodDeprecated,
+            -9 => RpcErrorCode::ClientNotConnected,
+            -10 => RpcErrorCode::ClientInInitialDownload,
+            -4 => RpcErrorCode::WalletError,
+            -6 => RpcErrorCode::WalletInsufficientFunds,
+            -18 => RpcErrorCode::WalletNotFound,
+            -19 => RpcErrorCode::WalletNotSpecified,
+            code => RpcErrorCode::Other(code),
+        }
+    }
+}
+
+/// BtcClient 的错误类型, 区分网络错误、HTTP 状态错误、解析错误和节点返回的 RPC 错误
+#[derive(Debug)]
+pub enum BtcRpcError {
+    /// 连接失败、超时等
+    Transport(reqwest::Error),
+    /// 非 2xx 且响应体不是 JSON-RPC 响应 (如 401 认证失败), 保留原始响应体
+    HttpStatus { status: u16, body: String },
+    /// 响应体无法解析为目标类型
+    Decode { source: serde_json::Error, body: String },
+    /// 节点返回的 error
+    Rpc { code: RpcErrorCode, message: String },
+    /// error 与 result 均为空
+    EmptyResult,
+    /// 无法生成认证信息, 如 cookie 文件不存在 (节点未启动)
+    Auth(String),
+    /// 批量请求中单个请求失败 (节点返回的错误转为 Rpc)
+    Batch(BatchError),
+    /// 节点所属的链不支持该请求, 如 DOGE 的 getblock verbosity=3
+    Unsupported(String),
+}
+
+impl BtcRpcError {
+    /// 节点返回的错误码, 非 RPC 错误为 None
+    pub fn rpc_code(&self) -> Option<RpcErrorCode> {
+        match self {
+            BtcRpcError::Rpc { code, .. } => Some(*code),
+            _ => None,
+        }
+    }
+
+    /// 交易/区块不存在 (-5 RPC_INVALID_ADDRESS_OR_KEY, REST 接口为 HTTP 404)
+    pub fn is_not_found(&self) -> bool {
+        matches!(self, BtcRpcError::HttpStatus { status: 404, .. })
+            || self.rpc_code() == Some(RpcErrorCode::InvalidAddressOrKey)
+    }
+
+    /// 节点启动中 (-28 RPC_IN_WARMUP)
+    pub fn is_warmup(&self) -> bool {
+        self.rpc_code() == Some(RpcErrorCode::InWarmup)
+    }
+
+    /// 连接失败、认证失败或非 JSON-RPC 的 HTTP 错误, 可切换到其他节点重试
+    pub fn is_endpoint_failure(&self) -> bool {
+        matches!(self, BtcRpcError::Transport(_) | BtcRpcError::HttpStatus { .. } | BtcRpcError::Auth(_))
+    }
+
+    /// 请求确定没有被节点执行: 连接失败、认证失败 (401/403) 或无法生成认证信息.
+    /// 读超时、连接中断等错误时节点可能已执行了请求
+    pub fn is_unsent(&self) -> bool {
+        match self {
+            BtcRpcError::Transport(err) => err.is_connect(),
+            BtcRpcError::HttpStatus { status, .. } => matches!(status, 401 | 403),
+            BtcRpcError::Auth(_) => true,
+            _ => false,
+        }
+    }
+
+    /// sendrawtransaction 被拒绝的原因 (-25/-26/-27)
+    pub fn reject_reason(&self) -> Option<RejectReason> {
+        match self {
+            BtcRpcError::Rpc {
+                code: RpcErrorCode::VerifyError | RpcErrorCode::VerifyRejected | RpcErrorCode::VerifyAlreadyInChain,
+                message,
+            } => Some(RejectReason::parse(message)),
+            _ => None,
+        }
+    }
+}
+
+/// 交易被交易池拒绝的原因, 见 src/validation.cpp 与 src/policy/policy.cpp 中的 reject reason
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RejectReason {
+    /// 已在链上
+    AlreadyInChain,
+    AlreadyInMempool,
+    /// 同 txid 不同见证的交易已在交易池
+    AlreadyKnown,
+    /// 与交易池中交易冲突且不满足替换规则
+    MempoolConflict,
+    /// 输入不存在或已花费
+    MissingInputs,
+    /// 手续费不足以替换 (RBF)
+    InsufficientFee,
+    MinRelayFeeNotMet,
+    MempoolMinFeeNotMet,
+    /// 超过 maxfeerate
+    MaxFeeExceeded,
+    /// 超过 maxburnamount
+    MaxBurnExceeded,
+    Dust,
+    NonFinal,
+    NonBip68Final,
+    TooLongMempoolChain,
+    /// 签名/脚本验证失败, 附带详细原因
+    ScriptVerifyFailed(String),
+    /// 非标准交易 (tx-size, scriptpubkey, multi-op-return 等)
+    NonStandard(String),
+    Other(String),
+}
+
+impl RejectReason {
+    /// 由 reject-reason 字段或 RPC 错误信息解析, 信息可能带有 ", 详情" 后缀
+    pub fn parse(reason: &str) -> Self {
+        let key = reason.split([',', ' ']).next().unwrap_or_default();
+        match key {
+            "txn-already-in-mempool" => RejectReason::AlreadyInMempool,
+            "txn-already-known" => RejectReason::AlreadyKnown,
+            "txn-mempool-conflict" | "bip125-replacement-disallowed" => RejectReason::MempoolConflict,
+            "missing-inputs" | "bad-txns-inputs-missingorspent" => RejectReason::MissingInputs,
+            "max-fee-exceeded" => RejectReason::MaxFeeExceeded,
+            "dust" => RejectReason::Dust,
+            "non-final" => RejectReason::NonFinal,
+            "non-BIP68-final" => RejectReason::NonBip68Final,
+            "too-long-mempool-chain" => RejectReason::TooLongMempoolChain,
+            "tx-size" | "tx-size-small" | "scriptpubkey" | "scriptsig-size" | "scriptsig-not-pushonly"
+            | "bare-multisig" | "multi-op-return" | "version" | "bad-txns-nonstandard-inputs" | "bad-witness-nonstandard" => {
+                RejectReason::NonStandard(reason.to_string())
+            }
+            _ if key.starts_with("mandatory-script-verify-flag-failed")
+                || key.starts_with("non-mandatory-script-verify-flag")
+                || key.starts_with("mempool-script-verify-flag-failed") =>
+            {
+                RejectReason::ScriptVerifyFailed(reason.to_string())
+            }
+            _ if reason.starts_with("insufficient fee") => RejectReason::InsufficientFee,
+            _ if reason.starts_with("min relay fee not met") => RejectReason::MinRelayFeeNotMet,
+            _ if reason.starts_with("mempool min fee not met") => RejectReason::MempoolMinFeeNotMet,
+            _ if reason.starts_with("Fee exceeds maximum") => RejectReason::MaxFeeExceeded,
+            _ if reason.starts_with("Unspendable output exceeds maximum") => RejectReason::MaxBurnExceeded,
+            _ if reason.starts_with("Transaction already in block chain")
+                || reason.starts_with("Transaction outputs already in utxo set") =>
+            {
+                RejectReason::AlreadyInChain
+            }
+            _ => RejectReason::Other(reason.to_string()),
+        }
+    }
+
+    /// 稍后重试或提高手续费可能成功
+    pub fn is_fee_related(&self) -> bool {
+        matches!(
+            self,
+            RejectReason::InsufficientFee | RejectReason::MinRelayFeeNotMet | RejectReason::MempoolMinFeeNotMet
+        )
+    }
+
+    /// 交易已在交易池或链上, 广播可视为成功
+    pub fn is_already_known(&self) -> bool {
+        matches!(
+            self,
+            RejectReason::AlreadyInChain | RejectReason::AlreadyInMempool | RejectReason::AlreadyKnown
+        )
+    }
+}
+
+impl fmt::Display for BtcRpcError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BtcRpcError::Transport(err) => write!(f, "transport error: {}", err),
+            BtcRpcError::HttpStatus { status, body } => write!(f, "http status {}: {}", status, body),
+            BtcRpcError::Decode { source, body } => write!(f, "decode error: {} body: {}", source, body),
+            BtcRpcError::Rpc { code, message } => write!(f, "rpc error {}: {}", code.code(), message),
+            BtcRpcError::EmptyResult => write!(f, "rpc response has neither result nor error"),
+            BtcRpcError::Auth(err) => write!(f, "auth error: {}", err),
+            BtcRpcError::Batch(err) => write!(f, "{}", err),
+            BtcRpcError::Unsupported(err) => write!(f, "unsupported: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for BtcRpcError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            BtcRpcError::Transport(err) => Some(err),
+            BtcRpcError::Decode { source, .. } => Some(source),
+            BtcRpcError::Batch(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for BtcRpcError {
+    fn from(err: reqwest::Error) -> Self {
+        BtcRpcError::Transport(err)
+    }
+}
+
+impl From<BatchError> for BtcRpcError {
+    fn from(err: BatchError) -> Self {
+        match err {
+            BatchError::Rpc { code, message } => BtcRpcError::Rpc {
+                code: RpcErrorCode::from(code as i32),
+                message,
+            },
+            err => BtcRpcError::Batch(err),
+        }
+    }
+}
+
+impl From<JsonError> for BtcRpcError {
+    fn from(err: JsonError) -> Self {
+        BtcRpcError::Rpc {
+            code: RpcErrorCode::from(err.code),
+            message: err.message,
+        }
+    }
+}
+
+impl<T> JsonResponse<T> {
+    /// 取出 result, error 存在时返回 BtcRpcError::Rpc
+    pub fn into_result(self) -> Result<T, BtcRpcError> {
+        self.into_option()?.ok_or(BtcRpcError::EmptyResult)
+    }
+
+    /// 同 into_result, 但 result 为 null 时返回 None (如 gettxout)
+    pub fn into_option(self) -> Result<Option<T>, BtcRpcError> {
+        match self.error {
+            Some(err) => Err(err.into()),
+            None => Ok(self.result),
+        }
+    }
+}
+
+/// 解析响应体. Core 对 RPC 错误会返回 500 + JSON 响应体, 因此先尝试按 JSON 解析, 失败时再按状态码报错
+pub(crate) fn decode_body<U: DeserializeOwned>(status: StatusCode, body: String) -> Result<U, BtcRpcError> {
+    match serde_json::from_str::<U>(&body) {
+        Ok(data) => Ok(data),
+        Err(_) if !status.is_success() => Err(BtcRpcError::HttpStatus {
+            status: status.as_u16(),
+            body,
+        }),
+        Err(source) => Err(BtcRpcError::Decode { source, body }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reject_reason() {
+        assert_eq!(RejectReason::parse("min relay fee not met, 100 < 141"), RejectReason::MinRelayFeeNotMet);
+        assert_eq!(RejectReason::parse("insufficient fee, rejecting replacement abc"), RejectReason::InsufficientFee);
+        assert_eq!(RejectReason::parse("missing-inputs"), RejectReason::MissingInputs);
+        assert_eq!(RejectReason::parse("txn-mempool-conflict"), RejectReason::MempoolConflict);
+        assert!(matches!(
+            RejectReason::parse("mandatory-script-verify-flag-failed (Signature must be zero for failed CHECK(MULTI)SIG operation)"),
+            RejectReason::ScriptVerifyFailed(_)
+        ));
+        assert_eq!(RejectReason::parse("something-new"), RejectReason::Other("something-new".to_string()));
+
+        let err = BtcRpcError::Rpc {
+            code: RpcErrorCode::VerifyAlreadyInChain,
+            message: "Transaction outputs already in utxo set".to_string(),
+        };
+        assert!(err.reject_reason().unwrap().is_already_known());
+        let err = BtcRpcError::Rpc {
+            code: RpcErrorCode::VerifyError,
+            message: "Fee exceeds maximum configured by user (e.g. -maxtxfee, maxfeerate)".to_string(),
+        };
+        assert_eq!(err.reject_reason(), Some(RejectReason::MaxFeeExceeded));
+        assert_eq!(BtcRpcError::EmptyResult.reject_reason(), None);
+    }
+
+    #[test]
+    fn test_error_code() {
+        assert_eq!(RpcErrorCode::from(-5), RpcErrorCode::InvalidAddressOrKey);
+        assert_eq!(RpcErrorCode::from(-28), RpcErrorCode::InWarmup);
+        assert_eq!(RpcErrorCode::from(-12345), RpcErrorCode::Other(-12345));
+        assert_eq!(RpcErrorCode::from(-26).code(), -26);
+    }
+
+    #[test]
+    fn test_decode_body() {
+        // 401 响应体为空
+        let res = decode_body::<JsonResponse<i32>>(StatusCode::UNAUTHORIZED, "".to_string());
+        assert!(matches!(res, Err(BtcRpcError::HttpStatus { status: 401, .. })));
+
+        // 500 + JSON 错误
+        let body = r#"{"result":null,"error":{"code":-5,"message":"No such mempool or blockchain transaction"},"id":1}"#;
+        let res = decode_body::<JsonResponse<String>>(StatusCode::INTERNAL_SERVER_ERROR, body.to_string())
+            .unwrap()
+            .into_result();
+        assert!(res.as_ref().is_err_and(|err| err.is_not_found()));
+
+        // 200 但类型不符
+        let body = r#"{"result":"abc","error":null,"id":1}"#;
+        let res = decode_body::<JsonResponse<i32>>(StatusCode::OK, body.to_string());
+        assert!(matches!(res, Err(BtcRpcError::Decode { .. })));
+
+        let body = r#"{"result":null,"error":null,"id":1}"#;
+        let res = decode_body::<JsonResponse<i32>>(StatusCode::OK, body.to_string()).unwrap();
+        assert!(matches!(res.into_result(), Err(BtcRpcError::EmptyResult)));
+    }
+}
@@ -0,0 +1,294 @@
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    sync::Mutex,
+};
+
+use anyhow::anyhow;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::utils::rpc_batch::RpcRequest;
+
+use super::{amount::Amount, response_type::Transaction, BtcClient};
+
+/// 交易手续费
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TxFee {
+    pub txid: String,
+    pub coinbase: bool,
+    /// 出块奖励交易为 0
+    pub input_value: Amount,
+    pub output_value: Amount,
+    pub fee: Amount,
+    pub vsize: usize,
+    /// sat/vB
+    pub feerate: f64,
+}
+
+/// 交易输出金额缓存 (txid -> 各输出金额), 超出容量时淘汰最早写入的
+pub struct PrevoutCache {
+    capacity: usize,
+    inner: Mutex<CacheInner>,
+}
+
+#[derive(Default)]
+struct CacheInner {
+    values: HashMap<String, Vec<Amount>>,
+    order: VecDeque<String>,
+}
+
+impl PrevoutCache {
+    pub fn new(capacity: usize) -> Self {
+        PrevoutCache {
+            capacity,
+            inner: Mutex::new(CacheInner::default()),
+        }
+    }
+
+    pub fn get(&self, txid: &str, vout: u32) -> Option<Amount> {
+        let inner = self.inner.lock().unwrap();
+        inner.values.get(txid).and_then(|values| values.get(vout as usize).copied())
+    }
+
+    /// 交易全部输出的金额
+    pub fn outputs(&self, txid: &str) -> Option<Vec<Amount>> {
+        self.inner.lock().unwrap().values.get(txid).cloned()
+    }
+
+    pub fn contains(&self, txid: &str) -> bool {
+        self.inner.lock().unwrap().values.contains_key(txid)
+    }
+
+    pub fn insert(&self, tx: &Transaction) {
+        let values = output_values(tx);
+
+        let mut inner = self.inner.lock().unwrap();
+        if inner.values.insert(tx.txid.clone(), values).is_none() {
+            inner.order.push_back(tx.txid.clone());
+        }
+        while inner.values.len() > self.capacity.max(1) {
+            match inner.order.pop_front() {
+                Some(txid) => inner.values.remove(&txid),
+                None => break,
+            };
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner.lock().unwrap().values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl Default for PrevoutCache {
+    fn default() -> Self {
+        PrevoutCache::new(100_000)
+    }
+}
+
+impl BtcClient {
+    /// 计算交易手续费. 输入金额优先取 verbosity=3 的 prevout, 其次由 fee 字段反推,
+    /// 否则批量 getrawtransaction 查询 (结果写入 cache)
+    pub async fn tx_fees(&self, txs: &[Transaction], cache: &PrevoutCache) -> Result<Vec<TxFee>, anyhow::Error> {
+        // 本次计算用到的输出金额. cache 只用于减少查询, 批次大于 cache 容量时条目可能已被淘汰
+        let mut outputs = HashMap::new();
+        // 同一批交易之间的花费 (如同一区块内) 无需查询
+        for tx in txs {
+            outputs.insert(tx.txid.clone(), output_values(tx));
+        }
+
+        let needed = txs
+            .iter()
+            .filter(|tx| tx.fee.is_none())
+            .flat_map(|tx| tx.vin.iter())
+            .filter(|input| input.prevout.is_none())
+            .filter_map(|input| input.txid.clone())
+            .filter(|txid| !outputs.contains_key(txid))
+            .collect::<HashSet<_>>();
+
+        let mut missing = Vec::new();
+        for txid in needed {
+            match cache.outputs(&txid) {
+                Some(values) => {
+                    outputs.insert(txid, values);
+                }
+                None => missing.push(txid),
+            }
+        }
+        for tx in txs {
+            cache.insert(tx);
+        }
+
+        if !missing.is_empty() {
+            log::debug!("[tx_fees] fetch {} prevout transactions", missing.len());
+
+            let requests = missing
+                .iter()
+                .map(|txid| RpcRequest::new("getrawtransaction", json!([txid, true])))
+                .collect::<Vec<_>>();
+            for (txid, res) in missing.into_iter().zip(self.batch_call::<Transaction>(requests).await) {
+                match res {
+                    Ok(tx) => {
+                        cache.insert(&tx);
+                        outputs.insert(txid, output_values(&tx));
+                    }
+                    Err(err) => return Err(anyhow!("getrawtransaction {} failed: {}", txid, err)),
+                }
+            }
+        }
+
+        txs.iter().map(|tx| compute_fee(tx, &outputs)).collect()
+    }
+
+    pub async fn tx_fee(&self, tx: &Transaction, cache: &PrevoutCache) -> Result<TxFee, anyhow::Error> {
+        let mut fees = self.tx_fees(std::slice::from_ref(tx), cache).await?;
+        Ok(fees.remove(0))
+    }
+
+    pub async fn tx_fee_by_txid(&self, txid: &str, cache: &PrevoutCache) -> Result<TxFee, anyhow::Error> {
+        let tx = self.get_raw_transaction_result(txid).await?;
+        self.tx_fee(&tx, cache).await
+    }
+}
+
+fn output_values(tx: &Transaction) -> Vec<Amount> {
+    tx.vout.iter().map(|output| output.value).collect()
+}
+
+fn compute_fee(tx: &Transaction, outputs: &HashMap<String, Vec<Amount>>) -> Result<TxFee, anyhow::Error> {
+    let coinbase = tx.vin.iter().any(|input| input.coinbase.is_some());
+    let output_value = Amount::checked_sum(tx.vout.iter().map(|output| output.value))
+        .ok_or_else(|| anyhow!("output value overflow for {}", tx.txid))?;
+
+    let input_value = if coinbase {
+        output_value
+    } else if let (Some(fee), true) = (tx.fee, tx.vin.iter().any(|input| input.prevout.is_none())) {
+        output_value
+            .checked_add(fee)
+            .ok_or_else(|| anyhow!("input value overflow for {}", tx.txid))?
+    } else {
+        let mut total = Amount::ZERO;
+        for input in &tx.vin {
+            let value = match (&input.prevout, &input.txid, input.vout) {
+                (Some(prevout), _, _) => Some(prevout.value),
+                (None, Some(txid), Some(vout)) => outputs.get(txid).and_then(|values| values.get(vout as usize).copied()),
+                _ => None,
+            };
+            match value {
+                Some(value) => {
+                    total = total
+                        .checked_add(value)
+                        .ok_or_else(|| anyhow!("input value overflow for {}", tx.txid))?
+                }
+                None => return Err(anyhow!("cannot resolve input {:?}:{:?} of {}", input.txid, input.vout, tx.txid)),
+            }
+        }
+        total
+    };
+
+    let fee = input_value - output_value;
+    if fee.is_negative() {
+        return Err(anyhow!("outputs exceed inputs for {}", tx.txid));
+    }
+
+    Ok(TxFee {
+        txid: tx.txid.clone(),
+        coinbase,
+        input_value: if coinbase { Amount::ZERO } else { input_value },
+        output_value,
+        fee,
+        vsize: tx.virtual_size(),
+        feerate: if tx.virtual_size() > 0 { fee.to_sat() as f64 / tx.virtual_size() as f64 } else { 0.0 },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use serde_json::Value;
+
+    use super::*;
+
+    fn tx(txid: &str, vin: Vec<Value>, outputs: &[f64], fee: Option<f64>) -> Transaction {
+        let vout = outputs
+            .iter()
+            .enumerate()
+            .map(|(n, value)| json!({"value": value, "n": n, "scriptPubKey": {"asm": "", "desc": "", "hex": "51"}}))
+            .collect::<Vec<_>>();
+
+        serde_json::from_value(json!({
+            "txid": txid, "hash": txid, "version": 2, "size": 200, "vsize": 200, "weight": 800,
+            "locktime": 0, "vin": vin, "vout": vout, "hex": "", "fee": fee
+        }))
+        .unwrap()
+    }
+
+    #[async_std::test]
+    async fn test_tx_fees() {
+        // 不会发出请求
+        let btc_client = BtcClient::new("http://127.0.0.1:1", "", "", Duration::from_secs(1)).unwrap();
+        let cache = PrevoutCache::new(10);
+
+        let txs = vec![
+            tx("c", vec![json!({"coinbase": "00", "sequence": 0})], &[6.25], None),
+            // 花费同批次的 c
+            tx("a", vec![json!({"txid": "c", "vout": 0, "sequence": 0})], &[6.2499], None),
+            // verbosity=3 prevout
+            tx(
+                "b",
+                vec![json!({"txid": "x", "vout": 1, "sequence": 0, "prevout": {
+                    "generated": false, "height": 1, "value": 0.001,
+                    "scriptPubKey": {"asm": "", "desc": "", "hex": "51"}
+                }})],
+                &[0.0009],
+                Some(0.0001),
+            ),
+            // 仅有 fee 字段
+            tx("d", vec![json!({"txid": "y", "vout": 0, "sequence": 0})], &[0.5], Some(0.00002)),
+        ];
+
+        let fees = btc_client.tx_fees(&txs, &cache).await.unwrap();
+        assert!(fees[0].coinbase);
+        assert_eq!(fees[0].fee, Amount::ZERO);
+        assert_eq!(fees[1].fee, Amount::from_sat(10_000));
+        assert_eq!(fees[1].feerate, 50.0);
+        assert_eq!(fees[2].fee, Amount::from_sat(10_000));
+        assert_eq!(fees[2].input_value, Amount::from_sat(100_000));
+        assert_eq!(fees[3].fee, Amount::from_sat(2_000));
+    }
+
+    #[async_std::test]
+    async fn test_tx_fees_small_cache() {
+        // 不会发出请求
+        let btc_client = BtcClient::new("http://127.0.0.1:1", "", "", Duration::from_secs(1)).unwrap();
+        // 容量小于批次大小, 批次内的交易写入后 p 和 c 均被淘汰
+        let cache = PrevoutCache::new(1);
+        cache.insert(&tx("p", vec![], &[1.0], None));
+
+        let txs = vec![
+            tx("c", vec![json!({"coinbase": "00", "sequence": 0})], &[6.25], None),
+            tx("a", vec![json!({"txid": "c", "vout": 0, "sequence": 0})], &[6.2499], None),
+            tx("e", vec![json!({"txid": "a", "vout": 0, "sequence": 0})], &[6.2498], None),
+            tx("f", vec![json!({"txid": "p", "vout": 0, "sequence": 0})], &[0.9999], None),
+        ];
+
+        let fees = btc_client.tx_fees(&txs, &cache).await.unwrap();
+        assert!(fees[1..].iter().all(|fee| fee.fee == Amount::from_sat(10_000)));
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[test]
+    fn test_cache_capacity() {
+        let cache = PrevoutCache::new(2);
+        for txid in ["a", "b", "c"] {
+            cache.insert(&tx(txid, vec![], &[1.0], None));
+        }
+        assert_eq!(cache.len(), 2);
+        assert!(!cache.contains("a"));
+        assert_eq!(cache.get("c", 0), Some(Amount::ONE_BTC));
+    }
+}
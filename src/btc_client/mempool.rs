@@ -0,0 +1,571 @@
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    time::Duration,
+};
+
+use anyhow::bail;
+use async_trait::async_trait;
+use futures::Stream;
+
+use crate::utils::rpc_batch::BatchError;
+
+use super::{
+    response_type::{BlockWithTxs, MempoolEntry, Transaction},
+    scanner::BlockSource,
+    zmq::{SequenceEvent, ZmqEvent},
+    BtcClient,
+};
+
+/// 交易池的数据来源, BtcClient 已实现, 测试时可替换为脚本化的假节点
+#[async_trait]
+pub trait MempoolSource: Send + Sync {
+    /// getrawmempool true
+    async fn mempool(&self) -> Result<HashMap<String, MempoolEntry>, anyhow::Error>;
+    /// 交易已离开交易池时为 None
+    async fn mempool_entry(&self, txid: &str) -> Result<Option<MempoolEntry>, anyhow::Error>;
+    /// 批量获取交易, 与 txids 一一对应, 交易已不存在时为 None
+    async fn transactions(&self, txids: &[String]) -> Result<Vec<Option<Transaction>>, anyhow::Error>;
+}
+
+#[async_trait]
+impl MempoolSource for BtcClient {
+    async fn mempool(&self) -> Result<HashMap<String, MempoolEntry>, anyhow::Error> {
+        Ok(self.get_raw_mempool_verbose_result().await?)
+    }
+
+    async fn mempool_entry(&self, txid: &str) -> Result<Option<MempoolEntry>, anyhow::Error> {
+        match self.get_mempool_entry_result(txid).await {
+            Ok(entry) => Ok(Some(entry)),
+            Err(err) if err.is_not_found() => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    async fn transactions(&self, txids: &[String]) -> Result<Vec<Option<Transaction>>, anyhow::Error> {
+        if txids.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let mut txs = Vec::with_capacity(txids.len());
+        for (txid, res) in txids.iter().zip(self.get_txs_raw_result(txids.to_vec()).await) {
+            match res {
+                Ok(tx) => txs.push(Some(tx)),
+                // -5 RPC_INVALID_ADDRESS_OR_KEY: 获取前已离开交易池
+                Err(BatchError::Rpc { code: -5, .. }) => txs.push(None),
+                Err(err) => bail!("getrawtransaction {} failed: {:?}", txid, err),
+            }
+        }
+        Ok(txs)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum MempoolEvent {
+    /// 交易进入交易池
+    Added {
+        tx: Box<Transaction>,
+        entry: Box<MempoolEntry>,
+    },
+    /// 交易被替换 (RBF), replaced_by 为花费了相同输入的交易, 可能已被打包
+    Replaced { txid: String, replaced_by: String },
+    /// 交易被打包
+    Confirmed {
+        txid: String,
+        block_hash: String,
+        height: u64,
+    },
+    /// 因过期、交易池已满等原因离开交易池, 且未发现冲突交易
+    Evicted { txid: String },
+}
+
+impl MempoolEvent {
+    pub fn txid(&self) -> &str {
+        match self {
+            MempoolEvent::Added { tx, .. } => &tx.txid,
+            MempoolEvent::Replaced { txid, .. }
+            | MempoolEvent::Confirmed { txid, .. }
+            | MempoolEvent::Evicted { txid } => txid,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MempoolMonitorConfig {
+    /// 轮询交易池快照的间隔
+    pub poll_interval: Duration,
+    /// 两次同步间最多补取的区块数, 超出时不再比对区块, 离开交易池的交易记为 Evicted
+    pub max_block_catchup: u64,
+}
+
+impl Default for MempoolMonitorConfig {
+    fn default() -> Self {
+        MempoolMonitorConfig {
+            poll_interval: Duration::from_secs(5),
+            max_block_catchup: 6,
+        }
+    }
+}
+
+/// 被花费的输出 (txid, vout)
+type OutPoint = (String, u32);
+
+/// 交易池监控: 比对 getrawmempool 快照 (或消费 ZMQ sequence 消息) 得到新增、替换、打包和驱逐事件.
+/// 记录每笔交易花费的输出, 新交易或区块交易与已知交易花费相同输出即判定为替换.
+pub struct MempoolMonitor<C: MempoolSource + BlockSource = BtcClient> {
+    source: C,
+    config: MempoolMonitorConfig,
+    txs: HashMap<String, Vec<OutPoint>>,
+    spends: HashMap<OutPoint, String>,
+    /// ZMQ R 消息移出的交易, 等下一条消息确定是被替换还是被驱逐
+    removed: Vec<(String, Vec<OutPoint>)>,
+    height: Option<u64>,
+    pending: VecDeque<MempoolEvent>,
+}
+
+impl<C: MempoolSource + BlockSource> MempoolMonitor<C> {
+    pub fn new(source: C, config: MempoolMonitorConfig) -> Self {
+        MempoolMonitor {
+            source,
+            config,
+            txs: HashMap::new(),
+            spends: HashMap::new(),
+            removed: Vec::new(),
+            height: None,
+            pending: VecDeque::new(),
+        }
+    }
+
+    /// 交易是否在交易池中 (以最近一次同步为准)
+    pub fn contains(&self, txid: &str) -> bool {
+        self.txs.contains_key(txid)
+    }
+
+    pub fn len(&self) -> usize {
+        self.txs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.txs.is_empty()
+    }
+
+    /// 同步一次交易池快照, 返回期间产生的事件. 首次同步时交易池中的交易均为 Added
+    pub async fn sync(&mut self) -> Result<Vec<MempoolEvent>, anyhow::Error> {
+        self.refresh().await?;
+        Ok(self.pending.drain(..).collect())
+    }
+
+    /// 处理 ZMQ 事件, 返回产生的交易池事件. 需订阅 sequence 主题;
+    /// 连接建立、消息丢失或断线轮询到新区块时重新同步快照
+    pub async fn handle_zmq(&mut self, event: &ZmqEvent) -> Result<Vec<MempoolEvent>, anyhow::Error> {
+        match event {
+            ZmqEvent::Sequence { event, .. } => self.apply_sequence(event).await?,
+            ZmqEvent::Connected | ZmqEvent::Gap { .. } | ZmqEvent::PolledBlock { .. } => self.refresh().await?,
+            _ => {}
+        }
+        Ok(self.pending.drain(..).collect())
+    }
+
+    /// 等待下一个事件, 无事件时按 poll_interval 轮询快照
+    pub async fn next(&mut self) -> Result<MempoolEvent, anyhow::Error> {
+        loop {
+            if let Some(event) = self.pending.pop_front() {
+                return Ok(event);
+            }
+            self.refresh().await?;
+            if self.pending.is_empty() {
+                async_std::task::sleep(self.config.poll_interval).await;
+            }
+        }
+    }
+
+    /// 转为事件流, 出错后流继续
+    pub fn into_stream(self) -> impl Stream<Item = Result<MempoolEvent, anyhow::Error>> {
+        futures::stream::unfold(self, |mut monitor| async move {
+            let event = monitor.next().await;
+            Some((event, monitor))
+        })
+    }
+
+    // 先取快照再取高度, 快照之后出块时区块中的交易按打包处理, 不会被误判为新增
+    async fn refresh(&mut self) -> Result<(), anyhow::Error> {
+        let snapshot = self.source.mempool().await?;
+        let tip = self.source.tip_height().await?;
+        self.flush_removed();
+
+        let mut confirmed = HashSet::new();
+        match self.height {
+            Some(height) if tip > height && tip - height <= self.config.max_block_catchup => {
+                for height in height + 1..=tip {
+                    let hash = self.source.block_hash(height).await?;
+                    let block = self.source.block(&hash).await?;
+                    confirmed.extend(block.tx.iter().map(|tx| tx.txid.clone()));
+                    self.connect_block(&block);
+                }
+            }
+            Some(height) if tip > height => {
+                log::warn!("[MempoolMonitor] skip blocks {}..={}", height + 1, tip);
+                self.height = Some(tip);
+            }
+            _ => self.height = Some(tip),
+        }
+
+        let gone = self
+            .txs
+            .keys()
+            .filter(|txid| !snapshot.contains_key(*txid))
+            .cloned()
+            .collect::<Vec<_>>();
+        let added = snapshot
+            .keys()
+            .filter(|txid| !self.txs.contains_key(*txid) && !confirmed.contains(*txid))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        // 先处理新增交易, 被其替换的交易不再记为驱逐
+        let txs = self.source.transactions(&added).await?;
+        for (txid, tx) in added.iter().zip(txs) {
+            match (tx, snapshot.get(txid)) {
+                (Some(tx), Some(entry)) => self.add_tx(tx, entry.clone()),
+                _ => log::debug!("[MempoolMonitor] {} left mempool before fetch", txid),
+            }
+        }
+
+        for txid in gone {
+            if self.untrack(&txid).is_some() {
+                self.pending.push_back(MempoolEvent::Evicted { txid });
+            }
+        }
+        Ok(())
+    }
+
+    async fn apply_sequence(&mut self, event: &SequenceEvent) -> Result<(), anyhow::Error> {
+        match event {
+            // 替换时节点先发送被替换交易的 R, 再发送新交易的 A
+            SequenceEvent::TxAdded { txid, .. } => {
+                if !self.txs.contains_key(txid) {
+                    let tx = self
+                        .source
+                        .transactions(std::slice::from_ref(txid))
+                        .await?
+                        .pop()
+                        .flatten();
+                    let entry = self.source.mempool_entry(txid).await?;
+                    if let (Some(tx), Some(entry)) = (tx, entry) {
+                        self.add_tx(tx, entry);
+                    }
+                }
+                self.flush_removed();
+            }
+            SequenceEvent::TxRemoved { txid, .. } => {
+                if let Some(inputs) = self.untrack(txid) {
+                    self.removed.push((txid.clone(), inputs));
+                }
+            }
+            // 与区块交易冲突的交易在 C 之前以 R 移出
+            SequenceEvent::BlockConnected { hash } => {
+                let block = self.source.block(hash).await?;
+                self.connect_block(&block);
+                self.flush_removed();
+            }
+            // 回滚区块中的交易会以 A 重新进入交易池
+            SequenceEvent::BlockDisconnected { .. } => {
+                self.flush_removed();
+                self.height = self.height.map(|height| height.saturating_sub(1));
+            }
+        }
+        Ok(())
+    }
+
+    fn add_tx(&mut self, tx: Transaction, entry: MempoolEntry) {
+        let inputs = spent_outputs(&tx);
+        let replaced = self.take_conflicts(&tx.txid, &inputs);
+        let txid = tx.txid.clone();
+
+        for outpoint in &inputs {
+            self.spends.insert(outpoint.clone(), txid.clone());
+        }
+        self.txs.insert(txid.clone(), inputs);
+
+        self.pending.push_back(MempoolEvent::Added {
+            tx: Box::new(tx),
+            entry: Box::new(entry),
+        });
+        for old in replaced {
+            log::info!("[MempoolMonitor] {} replaced by {}", old, txid);
+            self.pending.push_back(MempoolEvent::Replaced {
+                txid: old,
+                replaced_by: txid.clone(),
+            });
+        }
+    }
+
+    fn connect_block(&mut self, block: &BlockWithTxs) {
+        let height = block.height as u64;
+        for tx in &block.tx {
+            if self.untrack(&tx.txid).is_some() {
+                self.pending.push_back(MempoolEvent::Confirmed {
+                    txid: tx.txid.clone(),
+                    block_hash: block.hash.clone(),
+                    height,
+                });
+            }
+
+            for old in self.take_conflicts(&tx.txid, &spent_outputs(tx)) {
+                self.pending.push_back(MempoolEvent::Replaced {
+                    txid: old,
+                    replaced_by: tx.txid.clone(),
+                });
+            }
+        }
+        self.height = Some(height);
+    }
+
+    // 取出与 txid 花费相同输出的已知交易 (含 R 移出待定的交易)
+    fn take_conflicts(&mut self, txid: &str, inputs: &[OutPoint]) -> Vec<String> {
+        let mut conflicts = inputs
+            .iter()
+            .filter_map(|outpoint| self.spends.get(outpoint))
+            .filter(|spender| spender.as_str() != txid)
+            .cloned()
+            .collect::<Vec<_>>();
+        conflicts.sort();
+        conflicts.dedup();
+        for conflict in &conflicts {
+            self.untrack(conflict);
+        }
+
+        let (removed, remaining) = std::mem::take(&mut self.removed)
+            .into_iter()
+            .partition::<Vec<_>, _>(|(_, spent)| spent.iter().any(|outpoint| inputs.contains(outpoint)));
+        self.removed = remaining;
+        conflicts.extend(removed.into_iter().map(|(txid, _)| txid));
+        conflicts
+    }
+
+    fn untrack(&mut self, txid: &str) -> Option<Vec<OutPoint>> {
+        let inputs = self.txs.remove(txid)?;
+        for outpoint in &inputs {
+            if self.spends.get(outpoint).map(String::as_str) == Some(txid) {
+                self.spends.remove(outpoint);
+            }
+        }
+        Some(inputs)
+    }
+
+    fn flush_removed(&mut self) {
+        for (txid, _) in self.removed.drain(..) {
+            self.pending.push_back(MempoolEvent::Evicted { txid });
+        }
+    }
+}
+
+fn spent_outputs(tx: &Transaction) -> Vec<OutPoint> {
+    tx.vin
+        .iter()
+        .filter_map(|input| Some((input.txid.clone()?, input.vout?)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use serde_json::json;
+
+    use super::*;
+
+    // 脚本化的假节点: mempool 为当前交易池, txs 为可查询的交易, chain 为主链各高度的区块
+    #[derive(Default)]
+    struct FakeNode {
+        mempool: Mutex<Vec<String>>,
+        txs: Mutex<HashMap<String, Transaction>>,
+        chain: Mutex<Vec<BlockWithTxs>>,
+    }
+
+    impl FakeNode {
+        // 交易 txid 花费 (prev, 0)
+        fn add_tx(&self, txid: &str, prev: &str) {
+            let tx = serde_json::from_value(json!({
+                "txid": txid, "hash": txid, "version": 2, "size": 100, "locktime": 0,
+                "vin": [{"txid": prev, "vout": 0, "sequence": 4294967293u32}],
+                "vout": [], "hex": "00"
+            }))
+            .unwrap();
+            self.txs.lock().unwrap().insert(txid.to_string(), tx);
+            self.mempool.lock().unwrap().push(txid.to_string());
+        }
+
+        fn remove_tx(&self, txid: &str) {
+            self.mempool.lock().unwrap().retain(|id| id != txid);
+        }
+
+        // 打包交易并移出交易池
+        fn mine(&self, txids: &[&str]) -> String {
+            let mut chain = self.chain.lock().unwrap();
+            let height = chain.len();
+            let txs = self.txs.lock().unwrap();
+            let block = serde_json::from_value::<BlockWithTxs>(json!({
+                "hash": format!("b{}", height), "confirmations": 1, "height": height, "version": 1,
+                "merkleroot": "", "time": 0, "nonce": 0, "bits": "", "difficulty": 1.0, "chainwork": "",
+                "size": 0, "tx": txids.iter().map(|txid| &txs[*txid]).collect::<Vec<_>>()
+            }))
+            .unwrap();
+            self.mempool.lock().unwrap().retain(|id| !txids.contains(&id.as_str()));
+            chain.push(block);
+            format!("b{}", height)
+        }
+    }
+
+    fn entry(txid: &str) -> MempoolEntry {
+        serde_json::from_value(json!({
+            "vsize": 100, "time": 0, "height": 0, "descendantcount": 1, "descendantsize": 100,
+            "ancestorcount": 1, "ancestorsize": 100, "wtxid": txid,
+            "fees": {"base": 0.0001, "modified": 0.0001, "ancestor": 0.0001, "descendant": 0.0001},
+            "depends": [], "spentby": [], "bip125-replaceable": true
+        }))
+        .unwrap()
+    }
+
+    #[async_trait]
+    impl MempoolSource for &FakeNode {
+        async fn mempool(&self) -> Result<HashMap<String, MempoolEntry>, anyhow::Error> {
+            Ok(self
+                .mempool
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|txid| (txid.clone(), entry(txid)))
+                .collect())
+        }
+
+        async fn mempool_entry(&self, txid: &str) -> Result<Option<MempoolEntry>, anyhow::Error> {
+            Ok(self
+                .mempool
+                .lock()
+                .unwrap()
+                .contains(&txid.to_string())
+                .then(|| entry(txid)))
+        }
+
+        async fn transactions(&self, txids: &[String]) -> Result<Vec<Option<Transaction>>, anyhow::Error> {
+            let txs = self.txs.lock().unwrap();
+            Ok(txids.iter().map(|txid| txs.get(txid).cloned()).collect())
+        }
+    }
+
+    #[async_trait]
+    impl BlockSource for &FakeNode {
+        async fn tip_height(&self) -> Result<u64, anyhow::Error> {
+            Ok(self.chain.lock().unwrap().len() as u64 - 1)
+        }
+
+        async fn block_hash(&self, height: u64) -> Result<String, anyhow::Error> {
+            Ok(self.chain.lock().unwrap()[height as usize].hash.clone())
+        }
+
+        async fn block(&self, hash: &str) -> Result<BlockWithTxs, anyhow::Error> {
+            match self.chain.lock().unwrap().iter().find(|block| block.hash == hash) {
+                Some(block) => Ok(block.clone()),
+                None => bail!("block {} not found", hash),
+            }
+        }
+    }
+
+    fn summary(events: &[MempoolEvent]) -> Vec<String> {
+        events
+            .iter()
+            .map(|event| match event {
+                MempoolEvent::Added { tx, .. } => format!("added {}", tx.txid),
+                MempoolEvent::Replaced { txid, replaced_by } => format!("replaced {} by {}", txid, replaced_by),
+                MempoolEvent::Confirmed { txid, height, .. } => format!("confirmed {} at {}", txid, height),
+                MempoolEvent::Evicted { txid } => format!("evicted {}", txid),
+            })
+            .collect()
+    }
+
+    #[async_std::test]
+    async fn test_snapshot() {
+        let node = FakeNode::default();
+        node.mine(&[]);
+        node.add_tx("a", "p0");
+        node.add_tx("b", "p1");
+        node.add_tx("c", "p2");
+
+        let mut monitor = MempoolMonitor::new(&node, MempoolMonitorConfig::default());
+        let mut events = summary(&monitor.sync().await.unwrap());
+        events.sort();
+        assert_eq!(events, vec!["added a", "added b", "added c"]);
+
+        node.add_tx("d", "p3");
+        assert_eq!(summary(&monitor.sync().await.unwrap()), vec!["added d"]);
+
+        // b 被 b2 替换, a 被打包, c 被驱逐; 区块中的 x 与 d 冲突
+        node.remove_tx("b");
+        node.add_tx("b2", "p1");
+        node.remove_tx("c");
+        node.add_tx("x", "p3");
+        node.remove_tx("d");
+        node.mine(&["a", "x"]);
+
+        let events = summary(&monitor.sync().await.unwrap());
+        assert_eq!(
+            events,
+            vec![
+                "confirmed a at 1",
+                "replaced d by x",
+                "added b2",
+                "replaced b by b2",
+                "evicted c"
+            ]
+        );
+        assert!(monitor.contains("b2") && !monitor.contains("b"));
+        assert_eq!(monitor.len(), 1);
+
+        assert!(monitor.sync().await.unwrap().is_empty());
+    }
+
+    #[async_std::test]
+    async fn test_zmq_sequence() {
+        let node = FakeNode::default();
+        node.mine(&[]);
+        node.add_tx("a", "p0");
+        node.add_tx("b", "p1");
+
+        let mut monitor = MempoolMonitor::new(&node, MempoolMonitorConfig::default());
+        assert_eq!(monitor.handle_zmq(&ZmqEvent::Connected).await.unwrap().len(), 2);
+
+        let sequence = |event: SequenceEvent| ZmqEvent::Sequence { event, sequence: 0 };
+        let removed = |txid: &str| {
+            sequence(SequenceEvent::TxRemoved {
+                txid: txid.to_string(),
+                mempool_sequence: 0,
+            })
+        };
+        let added = |txid: &str| {
+            sequence(SequenceEvent::TxAdded {
+                txid: txid.to_string(),
+                mempool_sequence: 0,
+            })
+        };
+
+        // R 后紧跟花费相同输出的 A, 为替换
+        node.remove_tx("a");
+        node.add_tx("a2", "p0");
+        assert!(monitor.handle_zmq(&removed("a")).await.unwrap().is_empty());
+        assert_eq!(
+            summary(&monitor.handle_zmq(&added("a2")).await.unwrap()),
+            vec!["added a2", "replaced a by a2"]
+        );
+
+        // R 后出块且无冲突, 为驱逐
+        node.remove_tx("b");
+        assert!(monitor.handle_zmq(&removed("b")).await.unwrap().is_empty());
+        let hash = node.mine(&["a2"]);
+        let events = monitor
+            .handle_zmq(&sequence(SequenceEvent::BlockConnected { hash }))
+            .await
+            .unwrap();
+        assert_eq!(summary(&events), vec!["confirmed a2 at 1", "evicted b"]);
+        assert!(monitor.is_empty());
+    }
+}
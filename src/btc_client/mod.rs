@@ -1,21 +1,75 @@
-use anyhow::Result;
-use reqwest::{
-    header::{HeaderMap, HeaderValue, AUTHORIZATION},
-    Client,
-};
+use anyhow::{bail, Result};
+use reqwest::{header::AUTHORIZATION, Client, StatusCode};
 use serde::{de::DeserializeOwned, Serialize};
 use serde_json::{json, Value};
-use std::time::Duration;
+use std::{
+    collections::HashMap,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use crate::utils::rpc_batch::{batch_call, BatchConfig, BatchError, RpcRequest};
 
-use self::response_type::*;
+use self::{
+    amount::Amount,
+    consensus::*,
+    endpoint::*,
+    error::*,
+    network::{Coin, Network, NetworkParams},
+    response_type::*,
+};
 
+pub mod amount;
+pub mod consensus;
+pub mod descriptor;
+pub mod endpoint;
+pub mod error;
+pub mod fee;
+pub mod mempool;
+pub mod network;
+pub mod psbt;
 pub mod response_type;
+pub mod rest;
+pub mod scanner;
+pub mod script;
+pub mod utxo;
+pub mod wallet;
+pub mod zmq;
+
+// 重复执行会改变节点状态的方法 (生成新地址、锁定 UTXO、发送资金等), 超时后不能换节点重发
+const NON_IDEMPOTENT_METHODS: &[&str] = &[
+    "createwallet",
+    "loadwallet",
+    "unloadwallet",
+    "getnewaddress",
+    "getrawchangeaddress",
+    "walletcreatefundedpsbt",
+    "lockunspent",
+    "sendtoaddress",
+    "sendmany",
+    "send",
+    "sendall",
+    "bumpfee",
+    "psbtbumpfee",
+];
 
 #[derive(Debug, Clone)]
 pub struct BtcClient {
     client: Client,
-    url: String,
-    headers: HeaderMap,
+    endpoints: Arc<Vec<EndpointState>>,
+    // 上次请求成功的节点
+    active: Arc<AtomicUsize>,
+    // wallet() 设置的 /wallet/<name>, 拼接到各节点地址之后
+    wallet_path: String,
+    pub batch_config: BatchConfig,
+    /// 节点所属的链, 默认为 BTC. 连接 LTC/DOGE/BCH 节点时需设置
+    pub coin: Coin,
+    /// 节点失败后的冷却时间, 期间优先使用其他节点
+    pub failover_cooldown: Duration,
 }
 
 impl BtcClient {
@@ -26,41 +80,217 @@ impl BtcClient {
         password: &str,
         timeout: Duration,
     ) -> Result<Self, anyhow::Error> {
-        // 构造基本认证头
-        let mut headers: HeaderMap = HeaderMap::new();
-        // headers.insert(CONTENT_TYPE, HeaderValue::from_str("application/json")?);
-        let auth = format!(
-            "Basic {}",
-            base64::encode(format!("{}:{}", username, password))
-        );
-        headers.insert(AUTHORIZATION, HeaderValue::from_str(&auth)?);
+        BtcClient::with_endpoints(vec![BtcEndpoint::new(url, BtcAuth::user_pass(username, password))], timeout)
+    }
+
+    /// 使用节点数据目录下的 .cookie 文件认证, 节点重启轮换 cookie 后自动重新读取
+    pub fn with_cookie(url: &str, cookie_path: impl Into<PathBuf>, timeout: Duration) -> Result<Self, anyhow::Error> {
+        BtcClient::with_endpoints(vec![BtcEndpoint::new(url, BtcAuth::cookie_file(cookie_path))], timeout)
+    }
+
+    /// 多节点, 各自认证. 请求失败 (连接失败、认证失败等) 时按顺序切换到下一个节点.
+    /// 请求可能已被节点执行时 (如读超时), 钱包客户端和非幂等请求不切换, 直接返回错误
+    pub fn with_endpoints(endpoints: Vec<BtcEndpoint>, timeout: Duration) -> Result<Self, anyhow::Error> {
+        if endpoints.is_empty() {
+            bail!("no btc endpoint");
+        }
+        let endpoints = endpoints.into_iter().map(EndpointState::new).collect::<Vec<_>>();
+        // cookie 文件可能在节点启动后才生成, 首次请求时再读取
+        for endpoint in endpoints.iter().filter(|endpoint| !endpoint.is_cookie()) {
+            endpoint.auth_header(false)?;
+        }
 
         let client = reqwest::Client::builder().timeout(timeout).build()?;
 
         Ok(BtcClient {
             client,
-            url: url.to_owned(),
-            headers,
+            endpoints: Arc::new(endpoints),
+            active: Arc::new(AtomicUsize::new(0)),
+            wallet_path: String::new(),
+            batch_config: BatchConfig::default(),
+            coin: Coin::default(),
+            failover_cooldown: Duration::from_secs(30),
         })
     }
+
+    /// 当前使用的节点地址 (含钱包路径)
+    pub fn url(&self) -> String {
+        self.endpoint_url(self.active.load(Ordering::Relaxed))
+    }
+
+    pub fn endpoint_status(&self) -> Vec<EndpointStatus> {
+        self.endpoints.iter().map(EndpointState::status).collect()
+    }
+
+    /// 逐个节点请求 getblockcount 并更新健康状态, 不做切换
+    pub async fn check_endpoints(&self) -> Vec<EndpointStatus> {
+        let post_json = json!({"jsonrpc": "2.0", "id": 1, "method": "getblockcount", "params": []});
+        for (index, endpoint) in self.endpoints.iter().enumerate() {
+            match self.post_endpoint::<Value, JsonResponse<u64>>(index, &post_json).await {
+                Ok(_) => endpoint.mark_ok(),
+                Err(err) => {
+                    log::warn!("[BtcClient] health check {} failed: {}", endpoint.endpoint.url, err);
+                    endpoint.mark_failed(self.failover_cooldown);
+                }
+            }
+        }
+        self.endpoint_status()
+    }
     // 通用post请求
     pub async fn http_post<T, U>(&self, post_json: T) -> Result<U, anyhow::Error>
     where
         T: Serialize,
         U: DeserializeOwned,
     {
-        let res = self
-            .client
-            .post(&self.url)
-            .headers(self.headers.clone())
-            .json(&post_json)
-            .send()
-            .await?
-            .json::<U>()
-            .await?;
+        let res = self.post_json::<T, U>(&post_json).await?;
+
+        Ok(res)
+    }
+    // post请求, 保留HTTP状态码和响应体. 从上次成功的节点开始, 冷却中的节点排在最后.
+    // 请求可能已被执行时只有幂等请求切换节点重发, 避免重复创建地址、锁定 UTXO 等
+    async fn post_json<T, U>(&self, post_json: &T) -> Result<U, BtcRpcError>
+    where
+        T: Serialize,
+        U: DeserializeOwned,
+    {
+        let active = self.active.load(Ordering::Relaxed);
+        let mut order = (0..self.endpoints.len())
+            .map(|offset| (active + offset) % self.endpoints.len())
+            .collect::<Vec<_>>();
+        order.sort_by_key(|&index| !self.endpoints[index].is_healthy());
+
+        let mut last_err = None;
+        for index in order {
+            let endpoint = &self.endpoints[index];
+            match self.post_endpoint(index, post_json).await {
+                Ok(res) => {
+                    endpoint.mark_ok();
+                    if index != active {
+                        log::warn!("[BtcClient] switch to {}", endpoint.endpoint.url);
+                        self.active.store(index, Ordering::Relaxed);
+                    }
+                    return Ok(res);
+                }
+                Err(err) if err.is_endpoint_failure() => {
+                    log::warn!("[BtcClient] {} failed: {}", endpoint.endpoint.url, err);
+                    endpoint.mark_failed(self.failover_cooldown);
+                    if !err.is_unsent() && !self.is_idempotent(post_json) {
+                        return Err(err);
+                    }
+                    last_err = Some(err);
+                }
+                Err(err) => return Err(err),
+            }
+        }
+        Err(last_err.expect("BtcClient has at least one endpoint"))
+    }
+
+    // 请求指定节点. cookie 认证返回 401 时重新读取 cookie 重试一次
+    async fn post_endpoint<T, U>(&self, index: usize, post_json: &T) -> Result<U, BtcRpcError>
+    where
+        T: Serialize,
+        U: DeserializeOwned,
+    {
+        let endpoint = &self.endpoints[index];
+        let url = self.endpoint_url(index);
+
+        let mut reload = false;
+        loop {
+            let mut request = self.client.post(&url).json(post_json);
+            if let Some(header) = endpoint.auth_header(reload)? {
+                request = request.header(AUTHORIZATION, header);
+            }
+            let res = request.send().await?;
+
+            let status = res.status();
+            if status == StatusCode::UNAUTHORIZED && endpoint.is_cookie() && !reload {
+                log::info!("[BtcClient] {} unauthorized, reload cookie", endpoint.endpoint.url);
+                reload = true;
+                continue;
+            }
+            let body = res.text().await?;
+
+            return decode_body(status, body);
+        }
+    }
+
+    // 钱包状态只存在于单个节点, 钱包客户端的请求一律视为非幂等. 批量请求中有任一非幂等方法即为非幂等
+    fn is_idempotent<T: Serialize>(&self, post_json: &T) -> bool {
+        if !self.wallet_path.is_empty() {
+            return false;
+        }
+        let Ok(value) = serde_json::to_value(post_json) else {
+            return false;
+        };
+
+        let requests = match &value {
+            Value::Array(requests) => requests.iter().collect(),
+            request => vec![request],
+        };
+        requests.iter().all(|request| {
+            let method = request["method"].as_str().unwrap_or_default();
+            !NON_IDEMPOTENT_METHODS.contains(&method)
+        })
+    }
+
+    fn endpoint_url(&self, index: usize) -> String {
+        let url = &self.endpoints[index].endpoint.url;
+        if self.wallet_path.is_empty() {
+            return url.clone();
+        }
+
+        let base = match url.find("/wallet/") {
+            Some(index) => &url[..index],
+            None => url.trim_end_matches('/'),
+        };
+        format!("{}{}", base, self.wallet_path)
+    }
+    // 通用rpc请求, params 为 json 数组
+    pub async fn call<T>(&self, method: &str, params: Value) -> Result<JsonResponse<T>, anyhow::Error>
+    where
+        T: DeserializeOwned,
+    {
+        let res = self.call_response::<T>(method, params).await?;
 
         Ok(res)
     }
+    // 通用rpc请求, 直接返回 result
+    pub async fn call_result<T>(&self, method: &str, params: Value) -> Result<T, BtcRpcError>
+    where
+        T: DeserializeOwned,
+    {
+        self.call_response::<T>(method, params).await?.into_result()
+    }
+
+    /// 节点所属链在指定网络下的地址参数
+    pub fn network_params(&self, network: Network) -> NetworkParams {
+        self.coin.params(network)
+    }
+
+    // getblock 参数, DOGE 的 verbosity 0/1 需传 bool; DOGE 不支持更高的 verbosity, 由调用方改用 getrawtransaction
+    fn block_params(&self, hash: &str, verbosity: u8) -> Value {
+        if self.coin.block_verbose_is_bool() && verbosity <= 1 {
+            json!([hash, verbosity == 1])
+        } else {
+            json!([hash, verbosity])
+        }
+    }
+
+    async fn call_response<T>(&self, method: &str, params: Value) -> Result<JsonResponse<T>, BtcRpcError>
+    where
+        T: DeserializeOwned,
+    {
+        let post_json = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": method,
+            "params": params
+        });
+
+        log::debug!("[{}] {:?}", method, post_json);
+
+        self.post_json::<Value, JsonResponse<T>>(&post_json).await
+    }
 
     pub async fn get_block_count(&self) -> Result<JsonResponse<i32>, anyhow::Error> {
         let post_json = json!({
@@ -107,6 +337,35 @@ impl BtcClient {
         Ok(res)
     }
 
+    /// getblock verbosity=2, tx 为完整交易, 一次请求即可取得区块内全部交易.
+    /// DOGE 不支持 verbosity=2, 改为 getblock + 批量 getrawtransaction (需节点开启 -txindex)
+    pub async fn get_block_with_txs(&self, hash: &str) -> Result<JsonResponse<BlockWithTxs>, anyhow::Error> {
+        if self.coin.block_verbose_is_bool() {
+            return json_response(self.get_block_with_txs_result(hash).await);
+        }
+        self.call("getblock", self.block_params(hash, 2)).await
+    }
+
+    /// getblock verbosity=3, 在 verbosity=2 的基础上每个输入附带 prevout (需 v25+). DOGE 不支持
+    pub async fn get_block_with_prevouts(&self, hash: &str) -> Result<JsonResponse<BlockWithTxs>, anyhow::Error> {
+        if self.coin.block_verbose_is_bool() {
+            return json_response(self.get_block_with_prevouts_result(hash).await);
+        }
+        self.call("getblock", self.block_params(hash, 3)).await
+    }
+
+    /// getblock verbosity=0, 返回原始区块 hex
+    pub async fn get_block_hex(&self, hash: &str) -> Result<JsonResponse<String>, anyhow::Error> {
+        self.call("getblock", self.block_params(hash, 0)).await
+    }
+
+    /// 获取原始区块并在本地解析, 交易的 txid/wtxid/vsize/weight 均由本地计算
+    pub async fn get_raw_block(&self, hash: &str) -> Result<RawBlock, anyhow::Error> {
+        let block_hex = self.get_block_hex_result(hash).await?;
+
+        RawBlock::from_hex(&block_hex)
+    }
+
     pub async fn get_raw_transaction(&self, hash: &str) -> Result<JsonResponse<Transaction>, anyhow::Error> {
         let post_json = json!({
             "jsonrpc": "2.0",
@@ -122,27 +381,478 @@ impl BtcClient {
         Ok(res)
     }
 
+    /// 批量 getrawtransaction, 按 batch_config 分批并发, 返回值与 hashs 顺序一致, id 为下标.
+    /// 节点对单个交易的错误保留在对应的 error 中, 重试后仍有请求失败时返回 Err
     pub async fn get_txs_raw(&self, hashs: Vec<String>) -> Result<Vec<JsonResponse<Transaction>>, anyhow::Error> {
-        let post_json = hashs.into_iter().enumerate().map(|(index, hash)|{
-            json!({
-                "jsonrpc": "2.0",
-                "id": index,
-                "method": "getrawtransaction",
-                "params": [hash, true]
+        log::debug!("[get_txs_raw] {} txs", hashs.len());
+
+        self.get_txs_raw_result(hashs)
+            .await
+            .into_iter()
+            .enumerate()
+            .map(|(id, res)| match res {
+                Ok(tx) => Ok(JsonResponse {
+                    error: None,
+                    id,
+                    result: Some(tx),
+                }),
+                Err(BatchError::Rpc { code, message }) => Ok(JsonResponse {
+                    error: Some(JsonError {
+                        code: code as i32,
+                        message,
+                    }),
+                    id,
+                    result: None,
+                }),
+                Err(err) => Err(err.into()),
             })
-        }).collect::<Vec<_>>();
+            .collect()
+    }
+
+    /// 批量rpc请求, 按 batch_config 分批并发, 返回值与 requests 一一对应
+    pub async fn batch_call<T>(&self, requests: Vec<RpcRequest>) -> Vec<Result<T, BatchError>>
+    where
+        T: DeserializeOwned,
+    {
+        batch_call(requests, &self.batch_config, |post_json| self.http_post::<Vec<Value>, Vec<Value>>(post_json)).await
+    }
+
+    pub async fn get_block_header(&self, hash: &str) -> Result<JsonResponse<BlockHeader>, anyhow::Error> {
+        self.call("getblockheader", json!([hash, true])).await
+    }
 
-        log::debug!("[get_txs_raw] {:?}", post_json);
+    pub async fn get_blockchain_info(&self) -> Result<JsonResponse<BlockchainInfo>, anyhow::Error> {
+        self.call("getblockchaininfo", json!([])).await
+    }
 
-        let res = self.http_post::<Vec<Value>, Vec<JsonResponse<Transaction>>>(post_json).await?;
+    pub async fn get_mempool_info(&self) -> Result<JsonResponse<MempoolInfo>, anyhow::Error> {
+        self.call("getmempoolinfo", json!([])).await
+    }
 
-        Ok(res)
+    /// 内存池中全部交易的txid
+    pub async fn get_raw_mempool(&self) -> Result<JsonResponse<Vec<String>>, anyhow::Error> {
+        self.call("getrawmempool", json!([false])).await
+    }
+
+    /// 内存池中全部交易的详情, key 为txid
+    pub async fn get_raw_mempool_verbose(&self) -> Result<JsonResponse<HashMap<String, MempoolEntry>>, anyhow::Error> {
+        self.call("getrawmempool", json!([true])).await
+    }
+
+    pub async fn get_mempool_entry(&self, txid: &str) -> Result<JsonResponse<MempoolEntry>, anyhow::Error> {
+        self.call("getmempoolentry", json!([txid])).await
+    }
+
+    /// 输出已花费或不存在时 result 为 None
+    pub async fn get_tx_out(
+        &self,
+        txid: &str,
+        vout: u32,
+        include_mempool: bool,
+    ) -> Result<JsonResponse<TxOut>, anyhow::Error> {
+        self.call("gettxout", json!([txid, vout, include_mempool])).await
+    }
+
+    pub async fn estimate_smart_fee(
+        &self,
+        conf_target: u32,
+        estimate_mode: Option<EstimateMode>,
+    ) -> Result<JsonResponse<SmartFee>, anyhow::Error> {
+        let params = match estimate_mode {
+            Some(mode) => json!([conf_target, mode]),
+            None => json!([conf_target]),
+        };
+
+        self.call("estimatesmartfee", params).await
+    }
+
+    pub async fn get_network_info(&self) -> Result<JsonResponse<NetworkInfo>, anyhow::Error> {
+        self.call("getnetworkinfo", json!([])).await
+    }
+
+    pub async fn get_chain_tips(&self) -> Result<JsonResponse<Vec<ChainTip>>, anyhow::Error> {
+        self.call("getchaintips", json!([])).await
+    }
+
+    // 广播交易, 返回 txid. max_feerate 单位 BTC/kvB, None 时使用节点默认值 0.10, 0 表示不限制
+    pub async fn send_raw_transaction(
+        &self,
+        hex: &str,
+        max_feerate: Option<Amount>,
+    ) -> Result<JsonResponse<String>, anyhow::Error> {
+        self.call("sendrawtransaction", with_max_feerate(json!(hex), max_feerate)).await
+    }
+
+    // 检查交易能否进入交易池 (不广播). 多笔交易时按包检查, 需按依赖顺序排列
+    pub async fn test_mempool_accept(
+        &self,
+        rawtxs: &[String],
+        max_feerate: Option<Amount>,
+    ) -> Result<JsonResponse<Vec<MempoolAcceptResult>>, anyhow::Error> {
+        self.call("testmempoolaccept", with_max_feerate(json!(rawtxs), max_feerate)).await
+    }
+
+    // 提交交易包 (子交易在最后), v26+; max_feerate v28+
+    pub async fn submit_package(
+        &self,
+        rawtxs: &[String],
+        max_feerate: Option<Amount>,
+    ) -> Result<JsonResponse<SubmitPackageResult>, anyhow::Error> {
+        self.call("submitpackage", with_max_feerate(json!(rawtxs), max_feerate)).await
+    }
+}
+
+fn with_max_feerate(first: Value, max_feerate: Option<Amount>) -> Value {
+    match max_feerate {
+        Some(max_feerate) => json!([first, max_feerate]),
+        None => json!([first]),
+    }
+}
+
+// *_result: 直接返回 result, 节点错误与网络错误统一为 BtcRpcError
+impl BtcClient {
+    pub async fn get_block_count_result(&self) -> Result<i32, BtcRpcError> {
+        self.call_result("getblockcount", json!([])).await
+    }
+
+    pub async fn get_block_hash_result(&self, height: u64) -> Result<String, BtcRpcError> {
+        self.call_result("getblockhash", json!([height])).await
+    }
+
+    pub async fn get_block_result(&self, hash: &str) -> Result<Block, BtcRpcError> {
+        self.call_result("getblock", json!([hash])).await
+    }
+
+    pub async fn get_block_with_txs_result(&self, hash: &str) -> Result<BlockWithTxs, BtcRpcError> {
+        if self.coin.block_verbose_is_bool() {
+            return self.block_with_raw_txs(hash).await;
+        }
+        self.call_result("getblock", self.block_params(hash, 2)).await
+    }
+
+    pub async fn get_block_with_prevouts_result(&self, hash: &str) -> Result<BlockWithTxs, BtcRpcError> {
+        if self.coin.block_verbose_is_bool() {
+            return Err(BtcRpcError::Unsupported(format!("getblock verbosity=3 on {}", self.coin.as_str())));
+        }
+        self.call_result("getblock", self.block_params(hash, 3)).await
+    }
+
+    // DOGE 的 getblock 只返回 txid 列表, 再批量 getrawtransaction 取得完整交易, 顺序与区块一致
+    async fn block_with_raw_txs(&self, hash: &str) -> Result<BlockWithTxs, BtcRpcError> {
+        let block: Block = self.call_result("getblock", self.block_params(hash, 1)).await?;
+        let requests = block
+            .tx
+            .iter()
+            .map(|txid| RpcRequest::new("getrawtransaction", json!([txid, 1])))
+            .collect::<Vec<_>>();
+        let txs = self
+            .batch_call::<Transaction>(requests)
+            .await
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(block.with_txs(txs))
+    }
+
+    pub async fn get_block_hex_result(&self, hash: &str) -> Result<String, BtcRpcError> {
+        self.call_result("getblock", self.block_params(hash, 0)).await
+    }
+
+    pub async fn get_raw_transaction_result(&self, hash: &str) -> Result<Transaction, BtcRpcError> {
+        self.call_result("getrawtransaction", json!([hash, true])).await
+    }
+
+    /// 批量 getrawtransaction, 返回值与 hashs 顺序一致
+    pub async fn get_txs_raw_result(&self, hashs: Vec<String>) -> Vec<Result<Transaction, BatchError>> {
+        let requests = hashs
+            .into_iter()
+            .map(|hash| RpcRequest::new("getrawtransaction", json!([hash, true])))
+            .collect::<Vec<_>>();
+
+        self.batch_call(requests).await
+    }
+
+    pub async fn get_block_header_result(&self, hash: &str) -> Result<BlockHeader, BtcRpcError> {
+        self.call_result("getblockheader", json!([hash, true])).await
+    }
+
+    pub async fn get_blockchain_info_result(&self) -> Result<BlockchainInfo, BtcRpcError> {
+        self.call_result("getblockchaininfo", json!([])).await
+    }
+
+    pub async fn get_mempool_info_result(&self) -> Result<MempoolInfo, BtcRpcError> {
+        self.call_result("getmempoolinfo", json!([])).await
+    }
+
+    pub async fn get_raw_mempool_result(&self) -> Result<Vec<String>, BtcRpcError> {
+        self.call_result("getrawmempool", json!([false])).await
+    }
+
+    pub async fn get_raw_mempool_verbose_result(&self) -> Result<HashMap<String, MempoolEntry>, BtcRpcError> {
+        self.call_result("getrawmempool", json!([true])).await
+    }
+
+    pub async fn get_mempool_entry_result(&self, txid: &str) -> Result<MempoolEntry, BtcRpcError> {
+        self.call_result("getmempoolentry", json!([txid])).await
+    }
+
+    /// 输出已花费或不存在时返回 None
+    pub async fn get_tx_out_result(
+        &self,
+        txid: &str,
+        vout: u32,
+        include_mempool: bool,
+    ) -> Result<Option<TxOut>, BtcRpcError> {
+        self.call_response::<TxOut>("gettxout", json!([txid, vout, include_mempool]))
+            .await?
+            .into_option()
+    }
+
+    pub async fn estimate_smart_fee_result(
+        &self,
+        conf_target: u32,
+        estimate_mode: Option<EstimateMode>,
+    ) -> Result<SmartFee, BtcRpcError> {
+        let params = match estimate_mode {
+            Some(mode) => json!([conf_target, mode]),
+            None => json!([conf_target]),
+        };
+
+        self.call_result("estimatesmartfee", params).await
+    }
+
+    pub async fn get_network_info_result(&self) -> Result<NetworkInfo, BtcRpcError> {
+        self.call_result("getnetworkinfo", json!([])).await
+    }
+
+    pub async fn get_chain_tips_result(&self) -> Result<Vec<ChainTip>, BtcRpcError> {
+        self.call_result("getchaintips", json!([])).await
+    }
+
+    // 被拒绝时可通过 BtcRpcError::reject_reason 判断原因
+    pub async fn send_raw_transaction_result(&self, hex: &str, max_feerate: Option<Amount>) -> Result<String, BtcRpcError> {
+        self.call_result("sendrawtransaction", with_max_feerate(json!(hex), max_feerate)).await
+    }
+
+    pub async fn test_mempool_accept_result(
+        &self,
+        rawtxs: &[String],
+        max_feerate: Option<Amount>,
+    ) -> Result<Vec<MempoolAcceptResult>, BtcRpcError> {
+        self.call_result("testmempoolaccept", with_max_feerate(json!(rawtxs), max_feerate)).await
+    }
+
+    pub async fn submit_package_result(
+        &self,
+        rawtxs: &[String],
+        max_feerate: Option<Amount>,
+    ) -> Result<SubmitPackageResult, BtcRpcError> {
+        self.call_result("submitpackage", with_max_feerate(json!(rawtxs), max_feerate)).await
+    }
+}
+
+// *_result 的结果转为 JsonResponse, 节点错误放入 error, 其余错误返回 Err
+fn json_response<T>(res: Result<T, BtcRpcError>) -> Result<JsonResponse<T>, anyhow::Error> {
+    match res {
+        Ok(result) => Ok(JsonResponse {
+            error: None,
+            id: 1,
+            result: Some(result),
+        }),
+        Err(BtcRpcError::Rpc { code, message }) => Ok(JsonResponse {
+            error: Some(JsonError {
+                code: code.code(),
+                message,
+            }),
+            id: 1,
+            result: None,
+        }),
+        Err(err) => Err(err.into()),
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::utils::test_node::{fake_node, silent_node};
+
+    #[async_std::test]
+    async fn test_failover_idempotent() {
+        let (url, requests) = fake_node(|method, _| match method {
+            "getblockcount" => Ok(json!(100)),
+            "getnewaddress" => Ok(json!("bc1qaddr")),
+            _ => Err((-32601, "Method not found")),
+        })
+        .await;
+        let silent = silent_node().await;
+
+        let endpoints = vec![
+            BtcEndpoint::new(&silent, BtcAuth::None),
+            BtcEndpoint::new(&url, BtcAuth::None),
+        ];
+        let mut client = BtcClient::with_endpoints(endpoints, Duration::from_millis(300)).unwrap();
+        client.failover_cooldown = Duration::ZERO;
+
+        // 读超时时节点可能已执行, 钱包客户端和非幂等方法不切换节点
+        let err = client.wallet("w").call_result::<Value>("getbalances", json!([])).await.unwrap_err();
+        assert!(err.is_endpoint_failure() && !err.is_unsent());
+        assert!(client.call_result::<String>("getnewaddress", json!([])).await.is_err());
+        assert!(requests.lock().unwrap().is_empty());
+
+        // 幂等方法切换到第二个节点
+        assert_eq!(client.call_result::<u64>("getblockcount", json!([])).await.unwrap(), 100);
+        assert_eq!(client.url(), url);
+
+        // 无法生成认证信息时请求未发出, 非幂等方法也可以切换
+        let cookie = std::env::temp_dir().join(format!("btc_cookie_missing_{}", std::process::id()));
+        let endpoints = vec![
+            BtcEndpoint::new(&silent, BtcAuth::cookie_file(cookie)),
+            BtcEndpoint::new(&url, BtcAuth::None),
+        ];
+        let client = BtcClient::with_endpoints(endpoints, Duration::from_millis(300)).unwrap();
+        assert_eq!(client.call_result::<String>("getnewaddress", json!([])).await.unwrap(), "bc1qaddr");
+        let methods = requests.lock().unwrap().iter().map(|req| req["method"].clone()).collect::<Vec<_>>();
+        assert_eq!(methods, vec![json!("getblockcount"), json!("getnewaddress")]);
+    }
+
+    #[async_std::test]
+    async fn test_chain_rpcs() {
+        let (url, requests) = fake_node(|method, _| match method {
+            "getblockchaininfo" => Ok(json!({
+                "chain": "main", "blocks": 850000, "headers": 850001, "bestblockhash": "00aa",
+                "difficulty": 1.0, "mediantime": 1700000000, "verificationprogress": 0.9999,
+                "initialblockdownload": false, "chainwork": "00ff", "size_on_disk": 1, "pruned": false,
+                "warnings": ""
+            })),
+            "getrawmempool" => Ok(json!(["t1", "t2"])),
+            "estimatesmartfee" => Ok(json!({"feerate": 0.00012, "blocks": 6})),
+            _ => Err((-32601, "Method not found")),
+        })
+        .await;
+        let btc_client = BtcClient::new(&url, "user", "pass", Duration::from_secs(2)).unwrap();
+
+        let info = btc_client.get_blockchain_info_result().await.unwrap();
+        assert_eq!((info.blocks, info.initialblockdownload), (850000, Some(false)));
+        assert_eq!(btc_client.get_raw_mempool_result().await.unwrap(), vec!["t1", "t2"]);
+
+        let fee = btc_client.estimate_smart_fee_result(6, Some(EstimateMode::Economical)).await.unwrap();
+        assert_eq!(fee.feerate, Some(Amount::from_sat(12_000)));
+        assert_eq!(requests.lock().unwrap()[2]["params"], json!([6, "economical"]));
+
+        let err = btc_client.get_network_info_result().await.unwrap_err();
+        assert_eq!(err.rpc_code(), Some(RpcErrorCode::MethodNotFound));
+    }
+
+    #[async_std::test]
+    async fn test_block_with_txs() {
+        let (url, requests) = fake_node(|method, params| match method {
+            "getblock" => {
+                let mut vin = json!({"txid": "c0", "vout": 0, "scriptSig": {"asm": "", "hex": ""}, "sequence": 4294967293u32});
+                if params[1] == 3 {
+                    vin["prevout"] = json!({"generated": true, "height": 0, "value": 1.5,
+                        "scriptPubKey": {"asm": "", "hex": "51", "type": "nonstandard"}});
+                }
+                Ok(json!({
+                    "hash": params[0], "confirmations": 1, "height": 1, "version": 1, "merkleroot": "aa",
+                    "time": 1, "nonce": 0, "bits": "1d00ffff", "difficulty": 1.0, "chainwork": "00",
+                    "nTx": 1, "size": 200, "weight": 800,
+                    "tx": [{
+                        "txid": "t1", "hash": "t1", "version": 2, "size": 200, "vsize": 200, "weight": 800,
+                        "locktime": 0, "vin": [vin], "fee": 0.0001, "hex": "00",
+                        "vout": [{"value": 1.4999, "n": 0, "scriptPubKey": {"asm": "", "hex": "51", "type": "nonstandard"}}]
+                    }]
+                }))
+            }
+            _ => Err((-32601, "Method not found")),
+        })
+        .await;
+        let btc_client = BtcClient::new(&url, "user", "pass", Duration::from_secs(2)).unwrap();
+
+        let block = btc_client.get_block_with_txs_result("b1").await.unwrap();
+        assert_eq!(block.tx[0].fee, Some(Amount::from_sat(10_000)));
+        assert!(block.tx[0].vin[0].prevout.is_none());
+
+        let block = btc_client.get_block_with_prevouts_result("b1").await.unwrap();
+        let prevout = block.tx[0].vin[0].prevout.as_ref().unwrap();
+        assert_eq!(prevout.value, Amount::from_sat(150_000_000));
+
+        let params = requests.lock().unwrap().iter().map(|req| req["params"].clone()).collect::<Vec<_>>();
+        assert_eq!(params, vec![json!(["b1", 2]), json!(["b1", 3])]);
+    }
+
+    #[async_std::test]
+    async fn test_dogecoin_block_with_txs() {
+        let (url, requests) = fake_node(|method, params| match method {
+            "getblock" => Ok(json!({
+                "hash": params[0], "confirmations": 1, "height": 1, "version": 1, "merkleroot": "aa",
+                "time": 1, "nonce": 0, "bits": "1d00ffff", "difficulty": 1.0, "chainwork": "00",
+                "size": 200, "tx": ["t1", "t2"]
+            })),
+            "getrawtransaction" => Ok(json!({
+                "txid": params[0], "hash": params[0], "version": 1, "size": 100, "locktime": 0, "hex": "00",
+                "vin": [], "vout": [{"value": 1.0, "n": 0, "scriptPubKey": {"asm": "", "hex": "51", "type": "nonstandard"}}]
+            })),
+            _ => Err((-32601, "Method not found")),
+        })
+        .await;
+        let mut btc_client = BtcClient::new(&url, "user", "pass", Duration::from_secs(2)).unwrap();
+        btc_client.coin = Coin::Dogecoin;
+
+        let block = btc_client.get_block_with_txs_result("b1").await.unwrap();
+        let txids = block.tx.iter().map(|tx| tx.txid.as_str()).collect::<Vec<_>>();
+        assert_eq!(txids, vec!["t1", "t2"]);
+        assert_eq!(block.tx_count(), 2);
+
+        let err = btc_client.get_block_with_prevouts_result("b1").await.unwrap_err();
+        assert!(matches!(err, BtcRpcError::Unsupported(_)));
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0]["params"], json!(["b1", true]));
+        let params = requests[1]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|req| (req["method"].clone(), req["params"].clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            params,
+            vec![
+                (json!("getrawtransaction"), json!(["t1", 1])),
+                (json!("getrawtransaction"), json!(["t2", 1]))
+            ]
+        );
+    }
+
+    #[async_std::test]
+    async fn test_get_txs_raw_chunked() {
+        let (url, requests) = fake_node(|_, params| match params[0].as_str().unwrap() {
+            "t3" => Err((-5, "No such mempool or blockchain transaction")),
+            txid => Ok(json!({
+                "txid": txid, "hash": txid, "version": 2, "size": 100, "vsize": 100, "weight": 400, "locktime": 0,
+                "vin": [], "vout": [], "hex": "00"
+            })),
+        })
+        .await;
+        let mut btc_client = BtcClient::new(&url, "user", "pass", Duration::from_secs(2)).unwrap();
+        btc_client.batch_config.chunk_size = 2;
+
+        let txids = (0..5).map(|i| format!("t{}", i)).collect::<Vec<_>>();
+        let res = btc_client.get_txs_raw(txids.clone()).await.unwrap();
+        assert_eq!(res.len(), 5);
+        for (i, data) in res.iter().enumerate() {
+            assert_eq!(data.id, i);
+            match &data.result {
+                Some(tx) => assert_eq!(tx.txid, txids[i]),
+                None => assert_eq!((i, data.error.as_ref().unwrap().code), (3, -5)),
+            }
+        }
+
+        // 5 笔交易分为 3 个批次
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 3);
+        assert!(requests.iter().all(|batch| batch.as_array().unwrap().len() <= 2));
+    }
 
     async fn test_get_latest_block() -> Result<(), anyhow::Error> {
         let url = "http://192.168.195.233:8030";
@@ -0,0 +1,122 @@
+use serde::{Deserialize, Serialize};
+
+/// 地址编码相关的网络参数
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NetworkParams {
+    /// P2PKH Base58Check 版本字节
+    pub p2pkh_prefix: u8,
+    /// P2SH Base58Check 版本字节
+    pub p2sh_prefix: u8,
+    /// 隔离见证地址的 bech32 hrp, 未激活隔离见证的链 (DOGE/BCH) 为空
+    pub bech32_hrp: Option<&'static str>,
+    /// BCH CashAddr 前缀, 非空时 P2PKH/P2SH 地址以 CashAddr 编码
+    pub cashaddr_prefix: Option<&'static str>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Network {
+    Bitcoin,
+    Testnet,
+    Signet,
+    Regtest,
+}
+
+impl Network {
+    pub fn params(&self) -> NetworkParams {
+        Coin::Bitcoin.params(*self)
+    }
+
+    /// 由 getblockchaininfo 的 chain 字段转换
+    pub fn from_chain(chain: &str) -> Option<Network> {
+        match chain {
+            "main" => Some(Network::Bitcoin),
+            // testnet4: BTC v28+; chipnet: BCH 升级测试网
+            "test" | "testnet4" | "test4" | "chipnet" => Some(Network::Testnet),
+            "signet" => Some(Network::Signet),
+            "regtest" => Some(Network::Regtest),
+            _ => None,
+        }
+    }
+}
+
+/// 与 Bitcoin Core RPC 兼容的链. 各链 RPC 接口相同, 但返回字段和地址参数不同
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Coin {
+    #[default]
+    Bitcoin,
+    Litecoin,
+    Dogecoin,
+    BitcoinCash,
+}
+
+impl Coin {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Coin::Bitcoin => "btc",
+            Coin::Litecoin => "ltc",
+            Coin::Dogecoin => "doge",
+            Coin::BitcoinCash => "bch",
+        }
+    }
+
+    /// 该链在指定网络下的地址参数. 没有 signet 的链按测试网处理
+    pub fn params(&self, network: Network) -> NetworkParams {
+        let (p2pkh_prefix, p2sh_prefix, bech32_hrp, cashaddr_prefix) = match (self, network) {
+            (Coin::Bitcoin, Network::Bitcoin) => (0x00, 0x05, Some("bc"), None),
+            (Coin::Bitcoin, Network::Testnet | Network::Signet) => (0x6f, 0xc4, Some("tb"), None),
+            (Coin::Bitcoin, Network::Regtest) => (0x6f, 0xc4, Some("bcrt"), None),
+            (Coin::Litecoin, Network::Bitcoin) => (0x30, 0x32, Some("ltc"), None),
+            (Coin::Litecoin, Network::Testnet | Network::Signet) => (0x6f, 0x3a, Some("tltc"), None),
+            (Coin::Litecoin, Network::Regtest) => (0x6f, 0x3a, Some("rltc"), None),
+            (Coin::Dogecoin, Network::Bitcoin) => (0x1e, 0x16, None, None),
+            (Coin::Dogecoin, Network::Testnet | Network::Signet) => (0x71, 0xc4, None, None),
+            (Coin::Dogecoin, Network::Regtest) => (0x6f, 0xc4, None, None),
+            (Coin::BitcoinCash, Network::Bitcoin) => (0x00, 0x05, None, Some("bitcoincash")),
+            (Coin::BitcoinCash, Network::Testnet | Network::Signet) => (0x6f, 0xc4, None, Some("bchtest")),
+            (Coin::BitcoinCash, Network::Regtest) => (0x6f, 0xc4, None, Some("bchreg")),
+        };
+
+        NetworkParams {
+            p2pkh_prefix,
+            p2sh_prefix,
+            bech32_hrp,
+            cashaddr_prefix,
+        }
+    }
+
+    /// 是否支持隔离见证, 不支持时交易没有 witness/vsize/weight
+    pub fn supports_segwit(&self) -> bool {
+        matches!(self, Coin::Bitcoin | Coin::Litecoin)
+    }
+
+    /// getblock 第二个参数是否为 bool. DOGE 基于 v0.14, 不支持 verbosity=2/3
+    pub fn block_verbose_is_bool(&self) -> bool {
+        *self == Coin::Dogecoin
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_params() {
+        assert_eq!(Network::Testnet.params(), Coin::Bitcoin.params(Network::Testnet));
+        assert_eq!(Network::Regtest.params().bech32_hrp, Some("bcrt"));
+
+        let ltc = Coin::Litecoin.params(Network::Bitcoin);
+        assert_eq!(
+            (ltc.p2pkh_prefix, ltc.p2sh_prefix, ltc.bech32_hrp),
+            (0x30, 0x32, Some("ltc"))
+        );
+        assert_eq!(Coin::Dogecoin.params(Network::Bitcoin).bech32_hrp, None);
+        assert_eq!(
+            Coin::BitcoinCash.params(Network::Testnet).cashaddr_prefix,
+            Some("bchtest")
+        );
+        assert!(!Coin::BitcoinCash.supports_segwit());
+        assert_eq!(Network::from_chain("chipnet"), Some(Network::Testnet));
+    }
+}
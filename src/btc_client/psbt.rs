@@ -0,0 +1,1351 @@
+use std::collections::BTreeMap;
+
+use anyhow::{anyhow, bail};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+
+use crate::utils::address_convert::hash160;
+
+use super::{
+    amount::Amount,
+    consensus::{
+        hash_from_hex, hash_to_hex, write_var_bytes, write_varint, Decoder, RawTransaction, RawTxIn, RawTxOut,
+    },
+    error::BtcRpcError,
+    response_type::*,
+    script::{push_data, ScriptType},
+    BtcClient,
+};
+
+const PSBT_MAGIC: &[u8] = b"psbt\xff";
+
+// 全局字段
+const PSBT_GLOBAL_UNSIGNED_TX: u64 = 0x00;
+const PSBT_GLOBAL_TX_VERSION: u64 = 0x02;
+const PSBT_GLOBAL_FALLBACK_LOCKTIME: u64 = 0x03;
+const PSBT_GLOBAL_INPUT_COUNT: u64 = 0x04;
+const PSBT_GLOBAL_OUTPUT_COUNT: u64 = 0x05;
+const PSBT_GLOBAL_TX_MODIFIABLE: u64 = 0x06;
+const PSBT_GLOBAL_VERSION: u64 = 0xfb;
+
+// 输入字段
+const PSBT_IN_NON_WITNESS_UTXO: u64 = 0x00;
+const PSBT_IN_WITNESS_UTXO: u64 = 0x01;
+const PSBT_IN_PARTIAL_SIG: u64 = 0x02;
+const PSBT_IN_SIGHASH_TYPE: u64 = 0x03;
+const PSBT_IN_REDEEM_SCRIPT: u64 = 0x04;
+const PSBT_IN_WITNESS_SCRIPT: u64 = 0x05;
+const PSBT_IN_BIP32_DERIVATION: u64 = 0x06;
+const PSBT_IN_FINAL_SCRIPTSIG: u64 = 0x07;
+const PSBT_IN_FINAL_SCRIPTWITNESS: u64 = 0x08;
+const PSBT_IN_PREVIOUS_TXID: u64 = 0x0e;
+const PSBT_IN_OUTPUT_INDEX: u64 = 0x0f;
+const PSBT_IN_SEQUENCE: u64 = 0x10;
+const PSBT_IN_REQUIRED_TIME_LOCKTIME: u64 = 0x11;
+const PSBT_IN_REQUIRED_HEIGHT_LOCKTIME: u64 = 0x12;
+const PSBT_IN_TAP_KEY_SIG: u64 = 0x13;
+const PSBT_IN_TAP_SCRIPT_SIG: u64 = 0x14;
+const PSBT_IN_TAP_MERKLE_ROOT: u64 = 0x18;
+const PSBT_IN_TAP_INTERNAL_KEY: u64 = 0x17;
+
+// 输出字段
+const PSBT_OUT_REDEEM_SCRIPT: u64 = 0x00;
+const PSBT_OUT_WITNESS_SCRIPT: u64 = 0x01;
+const PSBT_OUT_BIP32_DERIVATION: u64 = 0x02;
+const PSBT_OUT_AMOUNT: u64 = 0x03;
+const PSBT_OUT_SCRIPT: u64 = 0x04;
+
+/// BIP-32 密钥来源: 主密钥指纹 + 派生路径 (硬化索引含 0x80000000)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeySource {
+    pub fingerprint: [u8; 4],
+    pub path: Vec<u32>,
+}
+
+impl KeySource {
+    fn decode(value: &[u8]) -> Result<Self, anyhow::Error> {
+        if value.len() < 4 || !value.len().is_multiple_of(4) {
+            bail!("invalid key source length {}", value.len());
+        }
+        Ok(KeySource {
+            fingerprint: value[..4].try_into()?,
+            path: value[4..]
+                .chunks_exact(4)
+                .map(|chunk| u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+                .collect(),
+        })
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        let mut buf = self.fingerprint.to_vec();
+        for index in &self.path {
+            buf.extend(index.to_le_bytes());
+        }
+        buf
+    }
+}
+
+/// PSBT 输入. 未解析的字段 (含 taproot 脚本路径相关字段) 按完整 key 保存在 unknown 中
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PsbtInput {
+    pub non_witness_utxo: Option<RawTransaction>,
+    pub witness_utxo: Option<RawTxOut>,
+    /// 公钥 -> 签名 (DER + sighash 字节)
+    pub partial_sigs: BTreeMap<Vec<u8>, Vec<u8>>,
+    pub sighash_type: Option<u32>,
+    pub redeem_script: Option<Vec<u8>>,
+    pub witness_script: Option<Vec<u8>>,
+    pub bip32_derivation: BTreeMap<Vec<u8>, KeySource>,
+    pub final_script_sig: Option<Vec<u8>>,
+    pub final_script_witness: Option<Vec<Vec<u8>>>,
+    // v2
+    pub previous_txid: Option<[u8; 32]>,
+    pub output_index: Option<u32>,
+    pub sequence: Option<u32>,
+    pub required_time_locktime: Option<u32>,
+    pub required_height_locktime: Option<u32>,
+    // taproot 密钥路径
+    pub tap_key_sig: Option<Vec<u8>>,
+    pub tap_internal_key: Option<Vec<u8>>,
+    pub unknown: BTreeMap<Vec<u8>, Vec<u8>>,
+}
+
+impl PsbtInput {
+    pub fn is_finalized(&self) -> bool {
+        self.final_script_sig.is_some() || self.final_script_witness.is_some()
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PsbtOutput {
+    pub redeem_script: Option<Vec<u8>>,
+    pub witness_script: Option<Vec<u8>>,
+    pub bip32_derivation: BTreeMap<Vec<u8>, KeySource>,
+    // v2
+    pub amount: Option<Amount>,
+    pub script: Option<Vec<u8>>,
+    pub unknown: BTreeMap<Vec<u8>, Vec<u8>>,
+}
+
+/// 创建 PSBT 时的输入
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PsbtTxIn {
+    pub txid: String,
+    pub vout: u32,
+    /// 0xfffffffd 表示允许 RBF
+    pub sequence: u32,
+}
+
+/// 输入的签名情况
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InputSigningStatus {
+    pub index: usize,
+    pub finalized: bool,
+    /// 实际需要签名的脚本类型 (P2SH/P2WSH 取内层脚本)
+    pub script_type: &'static str,
+    pub required: usize,
+    pub signed: usize,
+    /// 尚未签名的公钥 hex, 单签输入取自 bip32_derivation / tap_internal_key, 无法确定时为空
+    pub missing_pubkeys: Vec<String>,
+}
+
+impl InputSigningStatus {
+    pub fn is_complete(&self) -> bool {
+        self.finalized || self.signed >= self.required
+    }
+}
+
+/// 部分签名交易 (BIP-174 v0 / BIP-370 v2)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Psbt {
+    pub version: u32,
+    /// v0 必填, v2 为空
+    pub unsigned_tx: Option<RawTransaction>,
+    // v2
+    pub tx_version: Option<i32>,
+    pub fallback_locktime: Option<u32>,
+    pub tx_modifiable: Option<u8>,
+    /// 未解析的全局字段 (xpub、proprietary 等), 原样保留
+    pub unknown: BTreeMap<Vec<u8>, Vec<u8>>,
+    pub inputs: Vec<PsbtInput>,
+    pub outputs: Vec<PsbtOutput>,
+}
+
+fn sha256(data: &[u8]) -> [u8; 32] {
+    Sha256::digest(data).into()
+}
+
+type KeyValue = (u64, Vec<u8>, Vec<u8>);
+
+// 读取一个 map, 返回 (类型, keydata, value) 及完整 key
+fn read_map(decoder: &mut Decoder) -> Result<Vec<(Vec<u8>, KeyValue)>, anyhow::Error> {
+    let mut pairs: Vec<(Vec<u8>, KeyValue)> = Vec::new();
+    loop {
+        let key = decoder.read_var_bytes()?;
+        if key.is_empty() {
+            return Ok(pairs);
+        }
+        let value = decoder.read_var_bytes()?;
+        if pairs.iter().any(|(existing, _)| *existing == key) {
+            bail!("duplicate key {}", hex::encode(&key));
+        }
+
+        let mut key_decoder = Decoder::new(&key);
+        let key_type = key_decoder.read_varint()?;
+        let key_data = key_decoder.remaining().to_vec();
+        pairs.push((key.clone(), (key_type, key_data, value)));
+    }
+}
+
+fn write_pair(buf: &mut Vec<u8>, key_type: u64, key_data: &[u8], value: &[u8]) {
+    let mut key = vec![];
+    write_varint(&mut key, key_type);
+    key.extend(key_data);
+    write_var_bytes(buf, &key);
+    write_var_bytes(buf, value);
+}
+
+fn u32_value(value: &[u8]) -> Result<u32, anyhow::Error> {
+    Ok(u32::from_le_bytes(
+        value
+            .try_into()
+            .map_err(|_| anyhow!("expected 4 bytes, got {}", value.len()))?,
+    ))
+}
+
+fn varint_value(value: &[u8]) -> Result<usize, anyhow::Error> {
+    let mut decoder = Decoder::new(value);
+    let n = decoder.read_varint()?;
+    if !decoder.is_empty() {
+        bail!("trailing bytes after compact size");
+    }
+    Ok(n as usize)
+}
+
+fn expect_empty_key(key_type: u64, key_data: &[u8]) -> Result<(), anyhow::Error> {
+    if !key_data.is_empty() {
+        bail!("key type {:#x} must not have key data", key_type);
+    }
+    Ok(())
+}
+
+fn decode_txout(value: &[u8]) -> Result<RawTxOut, anyhow::Error> {
+    let mut decoder = Decoder::new(value);
+    let txout = RawTxOut::decode(&mut decoder)?;
+    if !decoder.is_empty() {
+        bail!("trailing bytes after txout");
+    }
+    Ok(txout)
+}
+
+fn encode_txout(txout: &RawTxOut) -> Vec<u8> {
+    let mut buf = txout.value.to_sat().to_le_bytes().to_vec();
+    write_var_bytes(&mut buf, &txout.script_pubkey);
+    buf
+}
+
+fn decode_witness(value: &[u8]) -> Result<Vec<Vec<u8>>, anyhow::Error> {
+    let mut decoder = Decoder::new(value);
+    let len = decoder.read_len()?;
+    let witness = (0..len)
+        .map(|_| decoder.read_var_bytes())
+        .collect::<Result<Vec<_>, _>>()?;
+    if !decoder.is_empty() {
+        bail!("trailing bytes after witness");
+    }
+    Ok(witness)
+}
+
+fn encode_witness(witness: &[Vec<u8>]) -> Vec<u8> {
+    let mut buf = vec![];
+    write_varint(&mut buf, witness.len() as u64);
+    for item in witness {
+        write_var_bytes(&mut buf, item);
+    }
+    buf
+}
+
+impl PsbtInput {
+    fn decode(pairs: Vec<(Vec<u8>, KeyValue)>) -> Result<Self, anyhow::Error> {
+        let mut input = PsbtInput::default();
+        for (key, (key_type, key_data, value)) in pairs {
+            match key_type {
+                PSBT_IN_PARTIAL_SIG => {
+                    input.partial_sigs.insert(key_data, value);
+                }
+                PSBT_IN_BIP32_DERIVATION => {
+                    input.bip32_derivation.insert(key_data, KeySource::decode(&value)?);
+                }
+                PSBT_IN_NON_WITNESS_UTXO
+                | PSBT_IN_WITNESS_UTXO
+                | PSBT_IN_SIGHASH_TYPE
+                | PSBT_IN_REDEEM_SCRIPT
+                | PSBT_IN_WITNESS_SCRIPT
+                | PSBT_IN_FINAL_SCRIPTSIG
+                | PSBT_IN_FINAL_SCRIPTWITNESS
+                | PSBT_IN_PREVIOUS_TXID
+                | PSBT_IN_OUTPUT_INDEX
+                | PSBT_IN_SEQUENCE
+                | PSBT_IN_REQUIRED_TIME_LOCKTIME
+                | PSBT_IN_REQUIRED_HEIGHT_LOCKTIME
+                | PSBT_IN_TAP_KEY_SIG
+                | PSBT_IN_TAP_INTERNAL_KEY => {
+                    expect_empty_key(key_type, &key_data)?;
+                    match key_type {
+                        PSBT_IN_NON_WITNESS_UTXO => input.non_witness_utxo = Some(RawTransaction::from_bytes(&value)?),
+                        PSBT_IN_WITNESS_UTXO => input.witness_utxo = Some(decode_txout(&value)?),
+                        PSBT_IN_SIGHASH_TYPE => input.sighash_type = Some(u32_value(&value)?),
+                        PSBT_IN_REDEEM_SCRIPT => input.redeem_script = Some(value),
+                        PSBT_IN_WITNESS_SCRIPT => input.witness_script = Some(value),
+                        PSBT_IN_FINAL_SCRIPTSIG => input.final_script_sig = Some(value),
+                        PSBT_IN_FINAL_SCRIPTWITNESS => input.final_script_witness = Some(decode_witness(&value)?),
+                        PSBT_IN_PREVIOUS_TXID => input.previous_txid = Some(value.as_slice().try_into()?),
+                        PSBT_IN_OUTPUT_INDEX => input.output_index = Some(u32_value(&value)?),
+                        PSBT_IN_SEQUENCE => input.sequence = Some(u32_value(&value)?),
+                        PSBT_IN_REQUIRED_TIME_LOCKTIME => input.required_time_locktime = Some(u32_value(&value)?),
+                        PSBT_IN_REQUIRED_HEIGHT_LOCKTIME => input.required_height_locktime = Some(u32_value(&value)?),
+                        PSBT_IN_TAP_KEY_SIG => input.tap_key_sig = Some(value),
+                        _ => input.tap_internal_key = Some(value),
+                    }
+                }
+                _ => {
+                    input.unknown.insert(key, value);
+                }
+            }
+        }
+        Ok(input)
+    }
+
+    fn encode(&self, buf: &mut Vec<u8>) {
+        if let Some(tx) = &self.non_witness_utxo {
+            write_pair(buf, PSBT_IN_NON_WITNESS_UTXO, &[], &tx.encode(true));
+        }
+        if let Some(txout) = &self.witness_utxo {
+            write_pair(buf, PSBT_IN_WITNESS_UTXO, &[], &encode_txout(txout));
+        }
+        for (pubkey, sig) in &self.partial_sigs {
+            write_pair(buf, PSBT_IN_PARTIAL_SIG, pubkey, sig);
+        }
+        if let Some(sighash_type) = self.sighash_type {
+            write_pair(buf, PSBT_IN_SIGHASH_TYPE, &[], &sighash_type.to_le_bytes());
+        }
+        if let Some(script) = &self.redeem_script {
+            write_pair(buf, PSBT_IN_REDEEM_SCRIPT, &[], script);
+        }
+        if let Some(script) = &self.witness_script {
+            write_pair(buf, PSBT_IN_WITNESS_SCRIPT, &[], script);
+        }
+        for (pubkey, source) in &self.bip32_derivation {
+            write_pair(buf, PSBT_IN_BIP32_DERIVATION, pubkey, &source.encode());
+        }
+        if let Some(script) = &self.final_script_sig {
+            write_pair(buf, PSBT_IN_FINAL_SCRIPTSIG, &[], script);
+        }
+        if let Some(witness) = &self.final_script_witness {
+            write_pair(buf, PSBT_IN_FINAL_SCRIPTWITNESS, &[], &encode_witness(witness));
+        }
+        if let Some(txid) = &self.previous_txid {
+            write_pair(buf, PSBT_IN_PREVIOUS_TXID, &[], txid);
+        }
+        let fields = [
+            (PSBT_IN_OUTPUT_INDEX, self.output_index),
+            (PSBT_IN_SEQUENCE, self.sequence),
+            (PSBT_IN_REQUIRED_TIME_LOCKTIME, self.required_time_locktime),
+            (PSBT_IN_REQUIRED_HEIGHT_LOCKTIME, self.required_height_locktime),
+        ];
+        for (key_type, value) in fields {
+            if let Some(value) = value {
+                write_pair(buf, key_type, &[], &value.to_le_bytes());
+            }
+        }
+        if let Some(sig) = &self.tap_key_sig {
+            write_pair(buf, PSBT_IN_TAP_KEY_SIG, &[], sig);
+        }
+        if let Some(key) = &self.tap_internal_key {
+            write_pair(buf, PSBT_IN_TAP_INTERNAL_KEY, &[], key);
+        }
+        for (key, value) in &self.unknown {
+            write_var_bytes(buf, key);
+            write_var_bytes(buf, value);
+        }
+        buf.push(0);
+    }
+
+    fn has_tap_script_sig(&self) -> bool {
+        self.unknown
+            .keys()
+            .any(|key| key.first() == Some(&(PSBT_IN_TAP_SCRIPT_SIG as u8)))
+    }
+
+    // 完成后只保留 utxo、最终脚本和未知字段, 见 BIP-174 Input Finalizer
+    fn clear_after_finalize(&mut self) {
+        self.partial_sigs.clear();
+        self.sighash_type = None;
+        self.redeem_script = None;
+        self.witness_script = None;
+        self.bip32_derivation.clear();
+        self.tap_key_sig = None;
+        self.tap_internal_key = None;
+        self.unknown
+            .retain(|key, _| !(PSBT_IN_TAP_SCRIPT_SIG..=PSBT_IN_TAP_MERKLE_ROOT).contains(&(key[0] as u64)));
+    }
+}
+
+impl PsbtOutput {
+    fn decode(pairs: Vec<(Vec<u8>, KeyValue)>) -> Result<Self, anyhow::Error> {
+        let mut output = PsbtOutput::default();
+        for (key, (key_type, key_data, value)) in pairs {
+            match key_type {
+                PSBT_OUT_BIP32_DERIVATION => {
+                    output.bip32_derivation.insert(key_data, KeySource::decode(&value)?);
+                }
+                PSBT_OUT_REDEEM_SCRIPT | PSBT_OUT_WITNESS_SCRIPT | PSBT_OUT_AMOUNT | PSBT_OUT_SCRIPT => {
+                    expect_empty_key(key_type, &key_data)?;
+                    match key_type {
+                        PSBT_OUT_REDEEM_SCRIPT => output.redeem_script = Some(value),
+                        PSBT_OUT_WITNESS_SCRIPT => output.witness_script = Some(value),
+                        PSBT_OUT_AMOUNT => {
+                            let sat = i64::from_le_bytes(value.as_slice().try_into()?);
+                            if sat < 0 {
+                                bail!("output amount {} out of range", sat);
+                            }
+                            output.amount = Some(Amount::from_sat(sat));
+                        }
+                        _ => output.script = Some(value),
+                    }
+                }
+                _ => {
+                    output.unknown.insert(key, value);
+                }
+            }
+        }
+        Ok(output)
+    }
+
+    fn encode(&self, buf: &mut Vec<u8>) {
+        if let Some(script) = &self.redeem_script {
+            write_pair(buf, PSBT_OUT_REDEEM_SCRIPT, &[], script);
+        }
+        if let Some(script) = &self.witness_script {
+            write_pair(buf, PSBT_OUT_WITNESS_SCRIPT, &[], script);
+        }
+        for (pubkey, source) in &self.bip32_derivation {
+            write_pair(buf, PSBT_OUT_BIP32_DERIVATION, pubkey, &source.encode());
+        }
+        if let Some(amount) = self.amount {
+            write_pair(buf, PSBT_OUT_AMOUNT, &[], &amount.to_sat().to_le_bytes());
+        }
+        if let Some(script) = &self.script {
+            write_pair(buf, PSBT_OUT_SCRIPT, &[], script);
+        }
+        for (key, value) in &self.unknown {
+            write_var_bytes(buf, key);
+            write_var_bytes(buf, value);
+        }
+        buf.push(0);
+    }
+}
+
+impl Psbt {
+    /// 由未签名交易创建 v0 PSBT, 各输入的 scriptSig 和见证必须为空
+    pub fn from_unsigned_tx(tx: RawTransaction) -> Result<Self, anyhow::Error> {
+        if tx
+            .inputs
+            .iter()
+            .any(|input| !input.script_sig.is_empty() || !input.witness.is_empty())
+        {
+            bail!("unsigned transaction must have empty scriptSigs and witnesses");
+        }
+
+        Ok(Psbt {
+            version: 0,
+            inputs: vec![PsbtInput::default(); tx.inputs.len()],
+            outputs: vec![PsbtOutput::default(); tx.outputs.len()],
+            unsigned_tx: Some(tx),
+            tx_version: None,
+            fallback_locktime: None,
+            tx_modifiable: None,
+            unknown: BTreeMap::new(),
+        })
+    }
+
+    /// 由输入和输出创建 v0 PSBT (交易版本 2), 之后需为各输入补充 witness_utxo / non_witness_utxo
+    pub fn create(inputs: &[PsbtTxIn], outputs: Vec<RawTxOut>, lock_time: u32) -> Result<Self, anyhow::Error> {
+        let inputs = inputs
+            .iter()
+            .map(|input| {
+                Ok(RawTxIn {
+                    prev_txid: hash_from_hex(&input.txid)?,
+                    prev_vout: input.vout,
+                    script_sig: vec![],
+                    sequence: input.sequence,
+                    witness: vec![],
+                })
+            })
+            .collect::<Result<Vec<_>, anyhow::Error>>()?;
+
+        Psbt::from_unsigned_tx(RawTransaction {
+            version: 2,
+            inputs,
+            outputs,
+            lock_time,
+        })
+    }
+
+    pub fn from_base64(s: &str) -> Result<Self, anyhow::Error> {
+        Self::from_bytes(&STANDARD.decode(s.trim())?)
+    }
+
+    pub fn to_base64(&self) -> String {
+        STANDARD.encode(self.serialize())
+    }
+
+    pub fn from_bytes(data: &[u8]) -> Result<Self, anyhow::Error> {
+        let Some(data) = data.strip_prefix(PSBT_MAGIC) else {
+            bail!("invalid PSBT magic");
+        };
+        let mut decoder = Decoder::new(data);
+
+        let mut psbt = Psbt {
+            version: 0,
+            unsigned_tx: None,
+            tx_version: None,
+            fallback_locktime: None,
+            tx_modifiable: None,
+            unknown: BTreeMap::new(),
+            inputs: vec![],
+            outputs: vec![],
+        };
+        let mut input_count = None;
+        let mut output_count = None;
+
+        for (key, (key_type, key_data, value)) in read_map(&mut decoder)? {
+            let known = matches!(
+                key_type,
+                PSBT_GLOBAL_UNSIGNED_TX
+                    | PSBT_GLOBAL_TX_VERSION
+                    | PSBT_GLOBAL_FALLBACK_LOCKTIME
+                    | PSBT_GLOBAL_INPUT_COUNT
+                    | PSBT_GLOBAL_OUTPUT_COUNT
+                    | PSBT_GLOBAL_TX_MODIFIABLE
+                    | PSBT_GLOBAL_VERSION
+            );
+            if !known {
+                psbt.unknown.insert(key, value);
+                continue;
+            }
+
+            expect_empty_key(key_type, &key_data)?;
+            match key_type {
+                PSBT_GLOBAL_UNSIGNED_TX => psbt.unsigned_tx = Some(RawTransaction::from_bytes(&value)?),
+                PSBT_GLOBAL_TX_VERSION => psbt.tx_version = Some(u32_value(&value)? as i32),
+                PSBT_GLOBAL_FALLBACK_LOCKTIME => psbt.fallback_locktime = Some(u32_value(&value)?),
+                PSBT_GLOBAL_INPUT_COUNT => input_count = Some(varint_value(&value)?),
+                PSBT_GLOBAL_OUTPUT_COUNT => output_count = Some(varint_value(&value)?),
+                PSBT_GLOBAL_TX_MODIFIABLE => {
+                    psbt.tx_modifiable = Some(*value.first().ok_or_else(|| anyhow!("empty tx modifiable"))?)
+                }
+                _ => psbt.version = u32_value(&value)?,
+            }
+        }
+
+        let (input_count, output_count) = match psbt.version {
+            0 => {
+                let Some(tx) = &psbt.unsigned_tx else {
+                    bail!("PSBTv0 requires unsigned tx");
+                };
+                if psbt.tx_version.is_some() || input_count.is_some() || output_count.is_some() {
+                    bail!("PSBTv0 must not contain v2 global fields");
+                }
+                if tx
+                    .inputs
+                    .iter()
+                    .any(|input| !input.script_sig.is_empty() || !input.witness.is_empty())
+                {
+                    bail!("unsigned tx has non-empty scriptSig or witness");
+                }
+                (tx.inputs.len(), tx.outputs.len())
+            }
+            2 => {
+                if psbt.unsigned_tx.is_some() {
+                    bail!("PSBTv2 must not contain unsigned tx");
+                }
+                match (psbt.tx_version, input_count, output_count) {
+                    (Some(_), Some(inputs), Some(outputs)) => (inputs, outputs),
+                    _ => bail!("PSBTv2 requires tx version, input count and output count"),
+                }
+            }
+            version => bail!("unsupported PSBT version {}", version),
+        };
+
+        for _ in 0..input_count {
+            psbt.inputs.push(PsbtInput::decode(read_map(&mut decoder)?)?);
+        }
+        for _ in 0..output_count {
+            psbt.outputs.push(PsbtOutput::decode(read_map(&mut decoder)?)?);
+        }
+        if !decoder.is_empty() {
+            bail!("trailing bytes after PSBT");
+        }
+
+        for (index, input) in psbt.inputs.iter().enumerate() {
+            if psbt.version == 2 && (input.previous_txid.is_none() || input.output_index.is_none()) {
+                bail!("input {} missing previous txid or output index", index);
+            }
+            if let Some(tx) = &input.non_witness_utxo {
+                let (txid, _) = psbt.prevout(index)?;
+                if tx.txid() != txid {
+                    bail!("input {} non-witness utxo does not match previous txid", index);
+                }
+            }
+        }
+        if psbt.version == 2
+            && psbt
+                .outputs
+                .iter()
+                .any(|output| output.amount.is_none() || output.script.is_none())
+        {
+            bail!("PSBTv2 output missing amount or script");
+        }
+
+        Ok(psbt)
+    }
+
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut buf = PSBT_MAGIC.to_vec();
+
+        if let Some(tx) = &self.unsigned_tx {
+            write_pair(&mut buf, PSBT_GLOBAL_UNSIGNED_TX, &[], &tx.encode(false));
+        }
+        for (key, value) in &self.unknown {
+            write_var_bytes(&mut buf, key);
+            write_var_bytes(&mut buf, value);
+        }
+        if self.version >= 2 {
+            let mut input_count = vec![];
+            write_varint(&mut input_count, self.inputs.len() as u64);
+            let mut output_count = vec![];
+            write_varint(&mut output_count, self.outputs.len() as u64);
+
+            write_pair(
+                &mut buf,
+                PSBT_GLOBAL_TX_VERSION,
+                &[],
+                &self.tx_version.unwrap_or(2).to_le_bytes(),
+            );
+            if let Some(lock_time) = self.fallback_locktime {
+                write_pair(&mut buf, PSBT_GLOBAL_FALLBACK_LOCKTIME, &[], &lock_time.to_le_bytes());
+            }
+            write_pair(&mut buf, PSBT_GLOBAL_INPUT_COUNT, &[], &input_count);
+            write_pair(&mut buf, PSBT_GLOBAL_OUTPUT_COUNT, &[], &output_count);
+            if let Some(modifiable) = self.tx_modifiable {
+                write_pair(&mut buf, PSBT_GLOBAL_TX_MODIFIABLE, &[], &[modifiable]);
+            }
+        }
+        if self.version > 0 {
+            write_pair(&mut buf, PSBT_GLOBAL_VERSION, &[], &self.version.to_le_bytes());
+        }
+        buf.push(0);
+
+        for input in &self.inputs {
+            input.encode(&mut buf);
+        }
+        for output in &self.outputs {
+            output.encode(&mut buf);
+        }
+        buf
+    }
+
+    /// 输入花费的输出 (显示字节序 txid, vout)
+    pub fn prevout(&self, index: usize) -> Result<(String, u32), anyhow::Error> {
+        let input = self
+            .inputs
+            .get(index)
+            .ok_or_else(|| anyhow!("input {} out of range", index))?;
+        match (&self.unsigned_tx, input.previous_txid, input.output_index) {
+            (Some(tx), _, _) => Ok((tx.inputs[index].prev_txid_hex(), tx.inputs[index].prev_vout)),
+            (None, Some(txid), Some(vout)) => Ok((hash_to_hex(&txid), vout)),
+            _ => bail!("input {} missing previous outpoint", index),
+        }
+    }
+
+    /// 未签名交易, v2 按 BIP-370 由各字段组装
+    pub fn unsigned_tx(&self) -> Result<RawTransaction, anyhow::Error> {
+        if let Some(tx) = &self.unsigned_tx {
+            return Ok(tx.clone());
+        }
+
+        let inputs = self
+            .inputs
+            .iter()
+            .enumerate()
+            .map(|(index, input)| match (input.previous_txid, input.output_index) {
+                (Some(prev_txid), Some(prev_vout)) => Ok(RawTxIn {
+                    prev_txid,
+                    prev_vout,
+                    script_sig: vec![],
+                    sequence: input.sequence.unwrap_or(u32::MAX),
+                    witness: vec![],
+                }),
+                _ => bail!("input {} missing previous outpoint", index),
+            })
+            .collect::<Result<Vec<_>, anyhow::Error>>()?;
+
+        let outputs = self
+            .outputs
+            .iter()
+            .map(|output| match (output.amount, &output.script) {
+                (Some(value), Some(script)) => Ok(RawTxOut {
+                    value,
+                    script_pubkey: script.clone(),
+                }),
+                _ => bail!("output missing amount or script"),
+            })
+            .collect::<Result<Vec<_>, anyhow::Error>>()?;
+
+        Ok(RawTransaction {
+            version: self.tx_version.unwrap_or(2),
+            inputs,
+            outputs,
+            lock_time: self.lock_time()?,
+        })
+    }
+
+    // BIP-370 锁定时间: 有要求的输入都支持高度时取最大高度, 否则都支持时间时取最大时间
+    fn lock_time(&self) -> Result<u32, anyhow::Error> {
+        let constrained = self
+            .inputs
+            .iter()
+            .filter(|input| input.required_height_locktime.is_some() || input.required_time_locktime.is_some())
+            .collect::<Vec<_>>();
+        if constrained.is_empty() {
+            return Ok(self.fallback_locktime.unwrap_or(0));
+        }
+
+        if let Some(heights) = constrained
+            .iter()
+            .map(|input| input.required_height_locktime)
+            .collect::<Option<Vec<_>>>()
+        {
+            return Ok(heights.into_iter().max().unwrap_or(0));
+        }
+        if let Some(times) = constrained
+            .iter()
+            .map(|input| input.required_time_locktime)
+            .collect::<Option<Vec<_>>>()
+        {
+            return Ok(times.into_iter().max().unwrap_or(0));
+        }
+        bail!("inputs have incompatible locktime requirements")
+    }
+
+    /// 输入花费的输出, 优先 witness_utxo
+    pub fn spent_output(&self, index: usize) -> Result<RawTxOut, anyhow::Error> {
+        let input = self
+            .inputs
+            .get(index)
+            .ok_or_else(|| anyhow!("input {} out of range", index))?;
+        if let Some(txout) = &input.witness_utxo {
+            return Ok(txout.clone());
+        }
+        if let Some(tx) = &input.non_witness_utxo {
+            let (_, vout) = self.prevout(index)?;
+            return tx
+                .outputs
+                .get(vout as usize)
+                .cloned()
+                .ok_or_else(|| anyhow!("input {} vout {} out of range", index, vout));
+        }
+        bail!("input {} has no utxo information", index)
+    }
+
+    /// 手续费 = 输入金额 - 输出金额, 需要全部输入的 utxo 信息
+    pub fn fee(&self) -> Result<Amount, anyhow::Error> {
+        let input_value = (0..self.inputs.len())
+            .map(|index| self.spent_output(index).map(|txout| txout.value))
+            .collect::<Result<Vec<_>, _>>()?;
+        let input_value = Amount::checked_sum(input_value).ok_or_else(|| anyhow!("input value overflow"))?;
+        let output_value = Amount::checked_sum(self.unsigned_tx()?.outputs.iter().map(|output| output.value))
+            .ok_or_else(|| anyhow!("output value overflow"))?;
+
+        let fee = input_value.checked_sub(output_value).ok_or_else(|| anyhow!("fee overflow"))?;
+        if fee.is_negative() {
+            bail!("outputs exceed inputs");
+        }
+        Ok(fee)
+    }
+
+    // 实际需要签名的脚本: P2SH 取 redeem_script, P2WSH 取 witness_script
+    fn signing_script(&self, index: usize) -> Result<ScriptType, anyhow::Error> {
+        let input = &self.inputs[index];
+        let mut script_type = ScriptType::classify(&self.spent_output(index)?.script_pubkey);
+
+        if let ScriptType::P2sh { hash } = script_type {
+            let redeem = input
+                .redeem_script
+                .as_ref()
+                .ok_or_else(|| anyhow!("input {} missing redeem script", index))?;
+            if hash160(redeem) != hash {
+                bail!("input {} redeem script does not match", index);
+            }
+            script_type = ScriptType::classify(redeem);
+        }
+        if let ScriptType::P2wsh { program } = script_type {
+            let witness = input
+                .witness_script
+                .as_ref()
+                .ok_or_else(|| anyhow!("input {} missing witness script", index))?;
+            if sha256(witness) != program {
+                bail!("input {} witness script does not match", index);
+            }
+            script_type = ScriptType::classify(witness);
+        }
+        Ok(script_type)
+    }
+
+    /// 各输入的签名情况
+    pub fn signing_status(&self) -> Result<Vec<InputSigningStatus>, anyhow::Error> {
+        let mut res = Vec::with_capacity(self.inputs.len());
+
+        for (index, input) in self.inputs.iter().enumerate() {
+            if input.is_finalized() {
+                res.push(InputSigningStatus {
+                    index,
+                    finalized: true,
+                    script_type: "finalized",
+                    required: 0,
+                    signed: 0,
+                    missing_pubkeys: vec![],
+                });
+                continue;
+            }
+
+            let script_type = self.signing_script(index)?;
+            let expected_signers = |signed: bool| -> Vec<String> {
+                if signed {
+                    return vec![];
+                }
+                input.bip32_derivation.keys().map(hex::encode).collect()
+            };
+
+            let (required, signed, missing_pubkeys) = match &script_type {
+                ScriptType::P2pkh { hash } | ScriptType::P2wpkh { program: hash } => {
+                    let signed = input.partial_sigs.keys().any(|pubkey| hash160(pubkey) == *hash);
+                    (1, signed as usize, expected_signers(signed))
+                }
+                ScriptType::P2pk { pubkey } => {
+                    let signed = input.partial_sigs.contains_key(pubkey);
+                    (
+                        1,
+                        signed as usize,
+                        if signed { vec![] } else { vec![hex::encode(pubkey)] },
+                    )
+                }
+                ScriptType::Multisig { required, pubkeys } => {
+                    let (signed, missing): (Vec<_>, Vec<_>) = pubkeys
+                        .iter()
+                        .partition(|pubkey| input.partial_sigs.contains_key(*pubkey));
+                    (
+                        *required as usize,
+                        signed.len(),
+                        missing.into_iter().map(hex::encode).collect(),
+                    )
+                }
+                ScriptType::P2tr { .. } => {
+                    let signed = input.tap_key_sig.is_some() || input.has_tap_script_sig();
+                    let missing = match (&input.tap_internal_key, signed) {
+                        (Some(key), false) => vec![hex::encode(key)],
+                        _ => vec![],
+                    };
+                    (1, signed as usize, missing)
+                }
+                _ => (1, 0, vec![]),
+            };
+
+            res.push(InputSigningStatus {
+                index,
+                finalized: false,
+                script_type: script_type.as_str(),
+                required,
+                signed,
+                missing_pubkeys,
+            });
+        }
+
+        Ok(res)
+    }
+
+    /// 仍缺少签名的输入
+    pub fn missing_signatures(&self) -> Result<Vec<InputSigningStatus>, anyhow::Error> {
+        Ok(self
+            .signing_status()?
+            .into_iter()
+            .filter(|status| !status.is_complete())
+            .collect())
+    }
+
+    pub fn is_fully_signed(&self) -> Result<bool, anyhow::Error> {
+        Ok(self.missing_signatures()?.is_empty())
+    }
+
+    // 单签: 取与哈希匹配的公钥及其签名
+    fn single_sig(&self, index: usize, hash: &[u8; 20]) -> Result<(Vec<u8>, Vec<u8>), anyhow::Error> {
+        self.inputs[index]
+            .partial_sigs
+            .iter()
+            .find(|(pubkey, _)| hash160(pubkey) == *hash)
+            .map(|(pubkey, sig)| (pubkey.clone(), sig.clone()))
+            .ok_or_else(|| anyhow!("input {} missing signature", index))
+    }
+
+    // 多签: 按脚本中的公钥顺序取前 required 个签名
+    fn multisig_sigs(&self, index: usize, script: &[u8]) -> Result<Vec<Vec<u8>>, anyhow::Error> {
+        let ScriptType::Multisig { required, pubkeys } = ScriptType::classify(script) else {
+            bail!("input {} unsupported script {}", index, hex::encode(script));
+        };
+        let sigs = pubkeys
+            .iter()
+            .filter_map(|pubkey| self.inputs[index].partial_sigs.get(pubkey).cloned())
+            .take(required as usize)
+            .collect::<Vec<_>>();
+        if sigs.len() < required as usize {
+            bail!("input {} has {} of {} signatures", index, sigs.len(), required);
+        }
+        Ok(sigs)
+    }
+
+    // 计算输入的最终 scriptSig 和见证
+    fn finalize_input(&self, index: usize) -> Result<(Vec<u8>, Vec<Vec<u8>>), anyhow::Error> {
+        let input = &self.inputs[index];
+        let script_pubkey = self.spent_output(index)?.script_pubkey;
+        // 校验 redeem/witness script 与输出匹配
+        self.signing_script(index)?;
+
+        let mut script_sig = vec![];
+        let witness = match ScriptType::classify(&script_pubkey) {
+            ScriptType::P2pkh { hash } => {
+                let (pubkey, sig) = self.single_sig(index, &hash)?;
+                push_data(&mut script_sig, &sig);
+                push_data(&mut script_sig, &pubkey);
+                vec![]
+            }
+            ScriptType::P2pk { pubkey } => {
+                let sig = input
+                    .partial_sigs
+                    .get(&pubkey)
+                    .ok_or_else(|| anyhow!("input {} missing signature", index))?;
+                push_data(&mut script_sig, sig);
+                vec![]
+            }
+            ScriptType::Multisig { .. } => {
+                script_sig.push(0);
+                for sig in self.multisig_sigs(index, &script_pubkey)? {
+                    push_data(&mut script_sig, &sig);
+                }
+                vec![]
+            }
+            ScriptType::P2wpkh { program } => {
+                let (pubkey, sig) = self.single_sig(index, &program)?;
+                vec![sig, pubkey]
+            }
+            ScriptType::P2wsh { .. } => self.wsh_witness(index)?,
+            ScriptType::P2tr { .. } => {
+                let sig = input
+                    .tap_key_sig
+                    .clone()
+                    .ok_or_else(|| anyhow!("input {} missing taproot key signature", index))?;
+                vec![sig]
+            }
+            ScriptType::P2sh { .. } => {
+                let redeem = input.redeem_script.clone().unwrap_or_default();
+                let witness = match ScriptType::classify(&redeem) {
+                    ScriptType::P2wpkh { program } => {
+                        let (pubkey, sig) = self.single_sig(index, &program)?;
+                        vec![sig, pubkey]
+                    }
+                    ScriptType::P2wsh { .. } => self.wsh_witness(index)?,
+                    ScriptType::Multisig { .. } => {
+                        script_sig.push(0);
+                        for sig in self.multisig_sigs(index, &redeem)? {
+                            push_data(&mut script_sig, &sig);
+                        }
+                        vec![]
+                    }
+                    other => bail!("input {} unsupported redeem script type {}", index, other.as_str()),
+                };
+                push_data(&mut script_sig, &redeem);
+                witness
+            }
+            other => bail!("input {} unsupported script type {}", index, other.as_str()),
+        };
+
+        Ok((script_sig, witness))
+    }
+
+    // P2WSH 多签见证: OP_CHECKMULTISIG 多弹出一个元素, 首项为空
+    fn wsh_witness(&self, index: usize) -> Result<Vec<Vec<u8>>, anyhow::Error> {
+        let witness_script = self.inputs[index].witness_script.clone().unwrap_or_default();
+        let mut witness = vec![vec![]];
+        witness.extend(self.multisig_sigs(index, &witness_script)?);
+        witness.push(witness_script);
+        Ok(witness)
+    }
+
+    /// 为全部未完成的输入生成最终脚本, 任一输入无法完成时不做修改并返回错误.
+    /// 支持 P2PK/P2PKH/P2WPKH/P2SH-P2WPKH/多签 (裸/P2SH/P2WSH/P2SH-P2WSH) 及 taproot 密钥路径
+    pub fn finalize(&mut self) -> Result<(), anyhow::Error> {
+        let mut finals = Vec::new();
+        let mut errors = Vec::new();
+        for index in 0..self.inputs.len() {
+            if self.inputs[index].is_finalized() {
+                continue;
+            }
+            match self.finalize_input(index) {
+                Ok(res) => finals.push((index, res)),
+                Err(err) => errors.push(err.to_string()),
+            }
+        }
+        if !errors.is_empty() {
+            bail!("cannot finalize: {}", errors.join("; "));
+        }
+
+        for (index, (script_sig, witness)) in finals {
+            let input = &mut self.inputs[index];
+            input.final_script_sig = (!script_sig.is_empty()).then_some(script_sig);
+            input.final_script_witness = (!witness.is_empty()).then_some(witness);
+            input.clear_after_finalize();
+        }
+        Ok(())
+    }
+
+    /// 提取已完成的交易
+    pub fn extract_tx(&self) -> Result<RawTransaction, anyhow::Error> {
+        let mut tx = self.unsigned_tx()?;
+        for (index, input) in self.inputs.iter().enumerate() {
+            if !input.is_finalized() {
+                bail!("input {} is not finalized", index);
+            }
+            tx.inputs[index].script_sig = input.final_script_sig.clone().unwrap_or_default();
+            tx.inputs[index].witness = input.final_script_witness.clone().unwrap_or_default();
+        }
+        Ok(tx)
+    }
+}
+
+// walletcreatefundedpsbt 选项, 均为可选
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FundPsbtOptions {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub add_inputs: Option<bool>,
+    #[serde(rename = "changeAddress", skip_serializing_if = "Option::is_none")]
+    pub change_address: Option<String>,
+    #[serde(rename = "changePosition", skip_serializing_if = "Option::is_none")]
+    pub change_position: Option<u32>,
+    #[serde(rename = "includeWatching", skip_serializing_if = "Option::is_none")]
+    pub include_watching: Option<bool>,
+    #[serde(rename = "lockUnspents", skip_serializing_if = "Option::is_none")]
+    pub lock_unspents: Option<bool>,
+    /// sat/vB
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fee_rate: Option<f64>,
+    #[serde(rename = "subtractFeeFromOutputs", skip_serializing_if = "Option::is_none")]
+    pub subtract_fee_from_outputs: Option<Vec<u32>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub replaceable: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub conf_target: Option<u32>,
+}
+
+// outputs 为 [{address: amount}], 保持顺序
+fn fund_psbt_params(
+    inputs: &[PsbtTxIn],
+    outputs: &[(String, Amount)],
+    lock_time: u32,
+    options: &FundPsbtOptions,
+) -> Value {
+    let outputs = outputs
+        .iter()
+        .map(|(address, amount)| json!({ address: amount }))
+        .collect::<Vec<_>>();
+    json!([inputs, outputs, lock_time, options, true])
+}
+
+impl BtcClient {
+    /// 由钱包选择输入并添加找零, 需通过 wallet(name) 调用
+    pub async fn wallet_create_funded_psbt(
+        &self,
+        inputs: &[PsbtTxIn],
+        outputs: &[(String, Amount)],
+        lock_time: u32,
+        options: &FundPsbtOptions,
+    ) -> Result<JsonResponse<FundedPsbt>, anyhow::Error> {
+        self.call(
+            "walletcreatefundedpsbt",
+            fund_psbt_params(inputs, outputs, lock_time, options),
+        )
+        .await
+    }
+
+    pub async fn decode_psbt(&self, psbt: &str) -> Result<JsonResponse<DecodedPsbt>, anyhow::Error> {
+        self.call("decodepsbt", json!([psbt])).await
+    }
+
+    pub async fn combine_psbt(&self, psbts: &[String]) -> Result<JsonResponse<String>, anyhow::Error> {
+        self.call("combinepsbt", json!([psbts])).await
+    }
+
+    pub async fn finalize_psbt(&self, psbt: &str, extract: bool) -> Result<JsonResponse<FinalizedPsbt>, anyhow::Error> {
+        self.call("finalizepsbt", json!([psbt, extract])).await
+    }
+}
+
+impl BtcClient {
+    pub async fn wallet_create_funded_psbt_result(
+        &self,
+        inputs: &[PsbtTxIn],
+        outputs: &[(String, Amount)],
+        lock_time: u32,
+        options: &FundPsbtOptions,
+    ) -> Result<FundedPsbt, BtcRpcError> {
+        self.call_result(
+            "walletcreatefundedpsbt",
+            fund_psbt_params(inputs, outputs, lock_time, options),
+        )
+        .await
+    }
+
+    pub async fn decode_psbt_result(&self, psbt: &str) -> Result<DecodedPsbt, BtcRpcError> {
+        self.call_result("decodepsbt", json!([psbt])).await
+    }
+
+    pub async fn combine_psbt_result(&self, psbts: &[String]) -> Result<String, BtcRpcError> {
+        self.call_result("combinepsbt", json!([psbts])).await
+    }
+
+    pub async fn finalize_psbt_result(&self, psbt: &str, extract: bool) -> Result<FinalizedPsbt, BtcRpcError> {
+        self.call_result("finalizepsbt", json!([psbt, extract])).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TXID: &str = "4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b";
+
+    fn pubkey(n: u8) -> Vec<u8> {
+        let mut key = vec![0x02];
+        key.extend([n; 32]);
+        key
+    }
+
+    // 假签名, 仅用于组装
+    fn sig(n: u8) -> Vec<u8> {
+        let mut sig = vec![0x30; 70];
+        sig[1] = n;
+        sig.push(0x01);
+        sig
+    }
+
+    fn p2wpkh(pubkey: &[u8]) -> Vec<u8> {
+        let mut script = vec![0x00, 0x14];
+        script.extend(hash160(pubkey));
+        script
+    }
+
+    fn multisig(required: u8, pubkeys: &[Vec<u8>]) -> Vec<u8> {
+        let mut script = vec![0x50 + required];
+        for pubkey in pubkeys {
+            push_data(&mut script, pubkey);
+        }
+        script.extend([0x50 + pubkeys.len() as u8, 0xae]);
+        script
+    }
+
+    fn create() -> Psbt {
+        let inputs = [
+            PsbtTxIn {
+                txid: TXID.to_string(),
+                vout: 0,
+                sequence: 0xffff_fffd,
+            },
+            PsbtTxIn {
+                txid: TXID.to_string(),
+                vout: 1,
+                sequence: 0xffff_fffd,
+            },
+        ];
+        let outputs = vec![RawTxOut {
+            value: Amount::from_sat(90_000),
+            script_pubkey: p2wpkh(&pubkey(9)),
+        }];
+        let mut psbt = Psbt::create(&inputs, outputs, 0).unwrap();
+
+        // 输入 0: P2WPKH, 输入 1: 2-of-3 P2WSH
+        psbt.inputs[0].witness_utxo = Some(RawTxOut {
+            value: Amount::from_sat(50_000),
+            script_pubkey: p2wpkh(&pubkey(1)),
+        });
+        psbt.inputs[0].bip32_derivation.insert(
+            pubkey(1),
+            KeySource {
+                fingerprint: [1, 2, 3, 4],
+                path: vec![0x8000_0054, 0, 7],
+            },
+        );
+
+        let witness_script = multisig(2, &[pubkey(2), pubkey(3), pubkey(4)]);
+        let mut script_pubkey = vec![0x00, 0x20];
+        script_pubkey.extend(sha256(&witness_script));
+        psbt.inputs[1].witness_utxo = Some(RawTxOut {
+            value: Amount::from_sat(50_000),
+            script_pubkey,
+        });
+        psbt.inputs[1].witness_script = Some(witness_script);
+        psbt
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let mut psbt = create();
+        psbt.inputs[1].unknown.insert(vec![0xfc, 0x01], vec![0xaa]);
+        psbt.unknown.insert(vec![0x01, 0xff], vec![0x01, 0x02, 0x03, 0x04]);
+
+        let encoded = psbt.to_base64();
+        assert!(encoded.starts_with("cHNidP8"));
+        assert_eq!(Psbt::from_base64(&encoded).unwrap(), psbt);
+        assert_eq!(psbt.fee().unwrap(), Amount::from_sat(10_000));
+    }
+
+    #[test]
+    fn test_sign_finalize_extract() {
+        let mut psbt = create();
+
+        let missing = psbt.missing_signatures().unwrap();
+        assert_eq!(missing.len(), 2);
+        assert_eq!(missing[0].script_type, "witness_v0_keyhash");
+        assert_eq!(missing[0].missing_pubkeys, vec![hex::encode(pubkey(1))]);
+        assert_eq!((missing[1].script_type, missing[1].required), ("multisig", 2));
+        assert_eq!(missing[1].missing_pubkeys.len(), 3);
+
+        psbt.inputs[0].partial_sigs.insert(pubkey(1), sig(1));
+        psbt.inputs[1].partial_sigs.insert(pubkey(4), sig(4));
+        let missing = psbt.missing_signatures().unwrap();
+        assert_eq!(missing.len(), 1);
+        assert_eq!(missing[0].signed, 1);
+        assert!(psbt.finalize().is_err());
+        assert!(!psbt.inputs[0].is_finalized());
+
+        psbt.inputs[1].partial_sigs.insert(pubkey(2), sig(2));
+        assert!(psbt.is_fully_signed().unwrap());
+        psbt.finalize().unwrap();
+        assert!(psbt.inputs[0].partial_sigs.is_empty() && psbt.inputs[0].bip32_derivation.is_empty());
+
+        let tx = psbt.extract_tx().unwrap();
+        assert_eq!(tx.inputs[0].witness, vec![sig(1), pubkey(1)]);
+        // 签名按脚本中的公钥顺序
+        assert_eq!(tx.inputs[1].witness[..3], [vec![], sig(2), sig(4)]);
+        assert!(tx.inputs.iter().all(|input| input.script_sig.is_empty()));
+
+        // 完成后仍可序列化并提取相同交易
+        assert_eq!(Psbt::from_base64(&psbt.to_base64()).unwrap().extract_tx().unwrap(), tx);
+    }
+
+    #[test]
+    fn test_p2sh_p2wpkh_finalize() {
+        let mut psbt = create();
+        psbt.inputs.truncate(1);
+        let tx = psbt.unsigned_tx.as_mut().unwrap();
+        tx.inputs.truncate(1);
+
+        let redeem = p2wpkh(&pubkey(1));
+        let mut script_pubkey = vec![0xa9, 0x14];
+        script_pubkey.extend(hash160(&redeem));
+        script_pubkey.push(0x87);
+        psbt.inputs[0].witness_utxo = Some(RawTxOut {
+            value: Amount::from_sat(100_000),
+            script_pubkey,
+        });
+        psbt.inputs[0].redeem_script = Some(redeem.clone());
+        psbt.inputs[0].partial_sigs.insert(pubkey(1), sig(1));
+
+        psbt.finalize().unwrap();
+        let mut expected = vec![];
+        push_data(&mut expected, &redeem);
+        assert_eq!(psbt.inputs[0].final_script_sig, Some(expected));
+        assert_eq!(psbt.inputs[0].final_script_witness, Some(vec![sig(1), pubkey(1)]));
+    }
+
+    #[test]
+    fn test_v2() {
+        let mut psbt = Psbt {
+            version: 2,
+            unsigned_tx: None,
+            tx_version: Some(2),
+            fallback_locktime: Some(100),
+            tx_modifiable: Some(0b11),
+            unknown: BTreeMap::new(),
+            inputs: vec![PsbtInput {
+                previous_txid: Some(hash_from_hex(TXID).unwrap()),
+                output_index: Some(3),
+                required_height_locktime: Some(800_000),
+                ..Default::default()
+            }],
+            outputs: vec![PsbtOutput {
+                amount: Some(Amount::from_sat(1000)),
+                script: Some(p2wpkh(&pubkey(1))),
+                ..Default::default()
+            }],
+        };
+
+        let parsed = Psbt::from_bytes(&psbt.serialize()).unwrap();
+        assert_eq!(parsed, psbt);
+        let tx = parsed.unsigned_tx().unwrap();
+        assert_eq!(tx.lock_time, 800_000);
+        assert_eq!(tx.inputs[0].sequence, u32::MAX);
+        assert_eq!(parsed.prevout(0).unwrap(), (TXID.to_string(), 3));
+
+        // 缺少输出金额
+        psbt.outputs[0].amount = None;
+        assert!(Psbt::from_bytes(&psbt.serialize()).is_err());
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert!(Psbt::from_bytes(b"psbt").is_err());
+        assert!(Psbt::from_base64("not base64!").is_err());
+
+        // 重复的 key
+        let mut data = PSBT_MAGIC.to_vec();
+        write_pair(&mut data, PSBT_GLOBAL_VERSION, &[], &0u32.to_le_bytes());
+        write_pair(&mut data, PSBT_GLOBAL_VERSION, &[], &0u32.to_le_bytes());
+        assert!(Psbt::from_bytes(&data).unwrap_err().to_string().contains("duplicate"));
+
+        // witness_utxo 金额 >= 2^63 时报错而不是得到负数
+        let mut psbt = create();
+        psbt.inputs[0].witness_utxo.as_mut().unwrap().value = Amount::from_sat(-1);
+        assert!(Psbt::from_bytes(&psbt.serialize()).unwrap_err().to_string().contains("out of range"));
+
+        // 金额溢出时 fee 返回错误而不是 panic
+        let mut psbt = create();
+        psbt.inputs[0].witness_utxo.as_mut().unwrap().value = Amount::from_sat(i64::MIN);
+        psbt.inputs[1].witness_utxo.as_mut().unwrap().value = Amount::ZERO;
+        assert!(psbt.fee().is_err());
+    }
+
+    #[test]
+    fn test_fund_params() {
+        let options = FundPsbtOptions {
+            fee_rate: Some(2.5),
+            include_watching: Some(true),
+            ..Default::default()
+        };
+        let params = fund_psbt_params(&[], &[("bc1qaddr".to_string(), Amount::from_sat(12_345))], 0, &options);
+        assert_eq!(
+            params,
+            json!([[], [{"bc1qaddr": 0.00012345}], 0, {"includeWatching": true, "fee_rate": 2.5}, true])
+        );
+    }
+}
@@ -1,4 +1,9 @@
+use std::collections::HashMap;
+
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::{amount::Amount, error::RejectReason};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct JsonResponse<T> {
@@ -13,9 +18,9 @@ pub struct JsonError {
     pub message: String,
 }
 
-// getblock
+// getblock. verbosity=1 时 tx 为txid列表, verbosity=2/3 时为完整交易(BlockWithTxs)
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Block {
+pub struct Block<T = String> {
     pub hash: String,
     pub confirmations: i32,
     pub height: usize,
@@ -30,13 +35,47 @@ pub struct Block {
     pub difficulty: f64,
     pub chainwork: String,
     #[serde(rename="nTx")]
-    pub n_tx: usize,
+    pub n_tx: Option<usize>, // v0.17+, DOGE 不返回
     pub previousblockhash: Option<String>,
     pub nextblockhash: Option<String>,
     pub strippedsize: Option<usize>,
     pub size: usize,
-    pub weight: usize,
-    pub tx: Vec<String>,
+    pub weight: Option<usize>, // BCH/DOGE 不返回
+    pub tx: Vec<T>,
+}
+
+pub type BlockWithTxs = Block<Transaction>;
+
+impl<T> Block<T> {
+    /// 交易数, 节点未返回 nTx 时取 tx 列表长度
+    pub fn tx_count(&self) -> usize {
+        self.n_tx.unwrap_or(self.tx.len())
+    }
+
+    /// 替换交易列表, 其余字段不变
+    pub fn with_txs<U>(self, tx: Vec<U>) -> Block<U> {
+        Block {
+            hash: self.hash,
+            confirmations: self.confirmations,
+            height: self.height,
+            version: self.version,
+            version_hex: self.version_hex,
+            merkleroot: self.merkleroot,
+            time: self.time,
+            mediantime: self.mediantime,
+            nonce: self.nonce,
+            bits: self.bits,
+            difficulty: self.difficulty,
+            chainwork: self.chainwork,
+            n_tx: self.n_tx,
+            previousblockhash: self.previousblockhash,
+            nextblockhash: self.nextblockhash,
+            strippedsize: self.strippedsize,
+            size: self.size,
+            weight: self.weight,
+            tx,
+        }
+    }
 }
 
 // getrawtransaction
@@ -47,18 +86,26 @@ pub struct Transaction {
     pub hash: String,
     pub version: u32,
     pub size: usize,
-    pub vsize: usize,
-    pub weight: usize, // new bitcoincore-rpc包多出来的字段
+    pub vsize: Option<usize>, // BCH 不返回
+    pub weight: Option<usize>, // v0.19+, BCH/DOGE 不返回
     pub locktime: u32,
     pub vin: Vec<Input>,
     pub vout: Vec<Output>,
     pub hex: String,
+    pub fee: Option<Amount>, // getblock verbosity=2/3 且节点有undo数据时返回, 出块奖励交易为空
     pub blockhash: Option<String>,
     pub confirmations: Option<u32>,
     pub time: Option<usize>,
     pub blocktime: Option<usize>,
 }
 
+impl Transaction {
+    /// 虚拟大小, 无隔离见证的链 vsize 即 size
+    pub fn virtual_size(&self) -> usize {
+        self.vsize.unwrap_or(self.size)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Input {
     pub txid: Option<String>, // 出块奖励交易为空
@@ -68,6 +115,17 @@ pub struct Input {
     pub txinwitness: Option<Vec<String>>, // 出块奖励交易为空
     pub sequence: u32,
     pub coinbase: Option<String>, // 非出块奖励交易为空
+    pub prevout: Option<Prevout>, // 仅 getblock verbosity=3 返回, 出块奖励交易为空
+}
+
+// 被花费的输出, getblock verbosity=3
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Prevout {
+    pub generated: bool,
+    pub height: u64,
+    pub value: Amount,
+    #[serde(rename = "scriptPubKey")]
+    pub script_pub_key: ScriptPubKey,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -78,7 +136,7 @@ pub struct ScriptSig {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Output {
-    pub value: f64,
+    pub value: Amount,
     pub n: u32,
     #[serde(rename = "scriptPubKey")]
     pub script_pub_key: ScriptPubKey,
@@ -87,10 +145,609 @@ pub struct Output {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ScriptPubKey {
     pub asm: String,
-    pub desc: String, // new bitcoincore-rpc包多出来的字段
+    pub desc: Option<String>, // v22+, 分叉链不返回
     pub hex: String,
     pub address: Option<String>,
     pub addresses: Option<Vec<String>>,
     pub r#type: Option<String>,  // `type` is a reserved keyword in Rust, hence the use of `r#`
     pub req_sigs: Option<usize>,
-}
\ No newline at end of file
+}
+// getblockheader
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlockHeader {
+    pub hash: String,
+    pub confirmations: i32,
+    pub height: usize,
+    pub version: i32,
+    #[serde(rename="versionHex")]
+    pub version_hex: Option<String>,
+    pub merkleroot: String,
+    pub time: usize,
+    pub mediantime: Option<usize>,
+    pub nonce: u32,
+    pub bits: String,
+    pub difficulty: f64,
+    pub chainwork: String,
+    #[serde(rename="nTx")]
+    pub n_tx: Option<usize>, // v0.17+, DOGE 不返回
+    pub previousblockhash: Option<String>,
+    pub nextblockhash: Option<String>,
+}
+
+// getblockchaininfo
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlockchainInfo {
+    pub chain: String,
+    pub blocks: u64,
+    pub headers: u64,
+    pub bestblockhash: String,
+    pub difficulty: f64,
+    pub time: Option<usize>, // v23+
+    pub mediantime: usize,
+    pub verificationprogress: f64,
+    pub initialblockdownload: Option<bool>, // v0.16+
+    pub chainwork: String,
+    pub size_on_disk: Option<u64>, // v0.16+
+    pub pruned: bool,
+    pub pruneheight: Option<u64>,
+    pub automatic_pruning: Option<bool>,
+    pub prune_target_size: Option<u64>,
+    pub warnings: Option<Warnings>,
+}
+
+/// `warnings` 在 v28 之前为字符串, 之后为字符串数组
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Warnings {
+    Single(String),
+    List(Vec<String>),
+}
+
+// getmempoolinfo
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MempoolInfo {
+    pub loaded: bool,
+    pub size: usize,
+    pub bytes: usize,
+    pub usage: usize,
+    pub total_fee: Option<Amount>, // v23+
+    pub maxmempool: usize,
+    pub mempoolminfee: Amount, // BTC/kvB
+    pub minrelaytxfee: Amount, // BTC/kvB
+    pub incrementalrelayfee: Option<Amount>, // BTC/kvB, v24+
+    pub unbroadcastcount: Option<usize>,
+    pub fullrbf: Option<bool>, // v24+
+}
+
+// getrawmempool true / getmempoolentry. DOGE (基于 v0.14) 返回 size/fee/modifiedfee,
+// BCHN 只返回 size/fees{base,modified}, 没有祖先/后代统计和 bip125-replaceable
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MempoolEntry {
+    #[serde(default)]
+    pub vsize: Option<usize>, // DOGE/BCH 不返回
+    #[serde(default)]
+    pub size: Option<usize>, // DOGE/BCH 返回, v0.19+ 已移除
+    pub weight: Option<usize>,
+    pub time: usize,
+    #[serde(default)]
+    pub height: Option<u64>, // BCHN 不返回
+    #[serde(default)]
+    pub descendantcount: Option<usize>,
+    #[serde(default)]
+    pub descendantsize: Option<usize>,
+    #[serde(default)]
+    pub ancestorcount: Option<usize>,
+    #[serde(default)]
+    pub ancestorsize: Option<usize>,
+    #[serde(default)]
+    pub wtxid: Option<String>, // DOGE/BCH 不返回
+    #[serde(default)]
+    pub fee: Option<Amount>, // DOGE 返回, v0.20+ 已移除
+    #[serde(default)]
+    pub modifiedfee: Option<Amount>,
+    #[serde(default)]
+    pub fees: Option<MempoolFees>, // DOGE 不返回
+    #[serde(default)]
+    pub depends: Vec<String>,
+    #[serde(default)]
+    pub spentby: Vec<String>, // DOGE 不返回
+    #[serde(rename = "bip125-replaceable", default)]
+    pub bip125_replaceable: Option<bool>, // DOGE/BCH 不返回
+    pub unbroadcast: Option<bool>,
+}
+
+impl MempoolEntry {
+    /// 虚拟大小, 没有 vsize 的链取 size
+    pub fn virtual_size(&self) -> usize {
+        self.vsize.or(self.size).unwrap_or_default()
+    }
+
+    /// 交易本身的手续费, 没有 fees 的链取 fee
+    pub fn base_fee(&self) -> Option<Amount> {
+        self.fees.as_ref().map(|fees| fees.base).or(self.fee)
+    }
+
+    /// 含 prioritisetransaction 调整的手续费, 没有 fees 的链取 modifiedfee
+    pub fn modified_fee(&self) -> Option<Amount> {
+        self.fees.as_ref().map(|fees| fees.modified).or(self.modifiedfee)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MempoolFees {
+    pub base: Amount,
+    pub modified: Amount,
+    #[serde(default)]
+    pub ancestor: Option<Amount>, // BCHN 不返回
+    #[serde(default)]
+    pub descendant: Option<Amount>,
+}
+
+// gettxout
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TxOut {
+    pub bestblock: String,
+    pub confirmations: u32,
+    pub value: Amount,
+    #[serde(rename = "scriptPubKey")]
+    pub script_pub_key: ScriptPubKey,
+    pub coinbase: bool,
+}
+
+// estimatesmartfee
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SmartFee {
+    pub feerate: Option<Amount>, // BTC/kvB
+    pub errors: Option<Vec<String>>,
+    pub blocks: u32,
+}
+
+/// estimatesmartfee 的 estimate_mode 参数
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EstimateMode {
+    Economical,
+    Conservative,
+}
+
+// getnetworkinfo
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NetworkInfo {
+    pub version: u64,
+    pub subversion: String,
+    pub protocolversion: u64,
+    pub localservices: String,
+    pub localservicesnames: Option<Vec<String>>,
+    pub localrelay: bool,
+    pub timeoffset: i64,
+    pub networkactive: bool,
+    pub connections: usize,
+    pub connections_in: Option<usize>,
+    pub connections_out: Option<usize>,
+    pub networks: Vec<NetworkReachability>,
+    pub relayfee: Amount, // BTC/kvB
+    pub incrementalfee: Amount, // BTC/kvB
+    pub localaddresses: Vec<LocalAddress>,
+    pub warnings: Option<Warnings>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NetworkReachability {
+    pub name: String,
+    pub limited: bool,
+    pub reachable: bool,
+    pub proxy: String,
+    pub proxy_randomize_credentials: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LocalAddress {
+    pub address: String,
+    pub port: u16,
+    pub score: i64,
+}
+
+// getchaintips
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChainTip {
+    pub height: u64,
+    pub hash: String,
+    pub branchlen: u64,
+    pub status: ChainTipStatus,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ChainTipStatus {
+    Invalid,
+    HeadersOnly,
+    ValidHeaders,
+    ValidFork,
+    Active,
+}
+
+// testmempoolaccept
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MempoolAcceptResult {
+    pub txid: String,
+    pub wtxid: String,
+    #[serde(rename = "package-error")]
+    pub package_error: Option<String>,
+    pub allowed: Option<bool>, // 包检查提前失败时为空
+    pub vsize: Option<usize>,
+    pub fees: Option<MempoolAcceptFees>,
+    #[serde(rename = "reject-reason")]
+    pub reject_reason: Option<String>,
+    #[serde(rename = "reject-details")]
+    pub reject_details: Option<String>, // v29+
+}
+
+impl MempoolAcceptResult {
+    pub fn is_allowed(&self) -> bool {
+        self.allowed == Some(true)
+    }
+
+    pub fn reject(&self) -> Option<RejectReason> {
+        self.reject_reason.as_deref().map(RejectReason::parse)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MempoolAcceptFees {
+    pub base: Amount,
+    #[serde(rename = "effective-feerate")]
+    pub effective_feerate: Option<Amount>, // BTC/kvB
+    #[serde(rename = "effective-includes")]
+    pub effective_includes: Option<Vec<String>>, // wtxid
+}
+
+// submitpackage
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubmitPackageResult {
+    pub package_msg: String, // 成功为 "success"
+    #[serde(rename = "tx-results")]
+    pub tx_results: HashMap<String, SubmitPackageTxResult>, // key 为 wtxid
+    #[serde(rename = "replaced-transactions")]
+    pub replaced_transactions: Option<Vec<String>>,
+}
+
+impl SubmitPackageResult {
+    pub fn is_success(&self) -> bool {
+        self.package_msg == "success"
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubmitPackageTxResult {
+    pub txid: String,
+    #[serde(rename = "other-wtxid")]
+    pub other_wtxid: Option<String>, // 同 txid 不同见证的交易已在交易池
+    pub vsize: Option<usize>,
+    pub fees: Option<MempoolAcceptFees>,
+    pub error: Option<String>,
+}
+
+impl SubmitPackageTxResult {
+    pub fn reject(&self) -> Option<RejectReason> {
+        self.error.as_deref().map(RejectReason::parse)
+    }
+}
+
+// createwallet / loadwallet
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoadWalletResult {
+    pub name: String,
+    pub warning: Option<String>,       // v25 之前
+    pub warnings: Option<Vec<String>>, // v25+
+}
+
+// importdescriptors, 与请求一一对应
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportDescriptorResult {
+    pub success: bool,
+    pub warnings: Option<Vec<String>>,
+    pub error: Option<JsonError>,
+}
+
+// listunspent
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WalletUtxo {
+    pub txid: String,
+    pub vout: u32,
+    pub address: Option<String>,
+    pub label: Option<String>,
+    #[serde(rename = "scriptPubKey")]
+    pub script_pub_key: String,
+    pub amount: Amount,
+    pub confirmations: u32,
+    #[serde(rename = "redeemScript")]
+    pub redeem_script: Option<String>,
+    #[serde(rename = "witnessScript")]
+    pub witness_script: Option<String>,
+    pub spendable: bool,
+    pub solvable: bool,
+    pub reused: Option<bool>,
+    pub desc: Option<String>,
+    pub parent_descs: Option<Vec<String>>,
+    pub safe: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TransactionCategory {
+    Send,
+    Receive,
+    Generate,
+    Immature,
+    Orphan,
+}
+
+// listtransactions / listsinceblock 的交易条目, 每个涉及钱包的输出一条
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WalletTransaction {
+    #[serde(rename = "involvesWatchonly")]
+    pub involves_watchonly: Option<bool>,
+    pub address: Option<String>,
+    pub category: TransactionCategory,
+    pub amount: Amount, // send 为负数
+    pub label: Option<String>,
+    pub vout: u32,
+    pub fee: Option<Amount>, // 仅 send, 负数
+    pub confirmations: i64, // 冲突交易为负数
+    pub generated: Option<bool>,
+    pub trusted: Option<bool>,
+    pub blockhash: Option<String>,
+    pub blockheight: Option<u64>,
+    pub blockindex: Option<usize>,
+    pub blocktime: Option<usize>,
+    pub txid: String,
+    pub wtxid: Option<String>, // v24+
+    pub walletconflicts: Vec<String>,
+    pub replaced_by_txid: Option<String>,
+    pub replaces_txid: Option<String>,
+    pub time: usize,
+    pub timereceived: usize,
+    #[serde(rename = "bip125-replaceable")]
+    pub bip125_replaceable: Option<String>, // yes/no/unknown
+    pub parent_descs: Option<Vec<String>>,
+    pub abandoned: Option<bool>,
+}
+
+// listsinceblock
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SinceBlock {
+    pub transactions: Vec<WalletTransaction>,
+    pub removed: Option<Vec<WalletTransaction>>, // 因分叉失效的交易
+    pub lastblock: String,
+}
+
+// getbalances
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WalletBalances {
+    pub mine: Balance,
+    pub watchonly: Option<Balance>, // 旧版钱包导入观察地址时返回
+    pub lastprocessedblock: Option<LastProcessedBlock>, // v26+
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Balance {
+    pub trusted: Amount,
+    pub untrusted_pending: Amount,
+    pub immature: Amount,
+    pub used: Option<Amount>, // 开启 avoid_reuse 时返回
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LastProcessedBlock {
+    pub hash: String,
+    pub height: u64,
+}
+
+// getdescriptorinfo
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DescriptorInfo {
+    pub descriptor: String, // 规范化并带校验和
+    pub checksum: String,
+    pub isrange: bool,
+    pub issolvable: bool,
+    pub hasprivatekeys: bool,
+}
+
+// walletcreatefundedpsbt
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FundedPsbt {
+    pub psbt: String,
+    pub fee: Amount,
+    pub changepos: i32, // 无找零为 -1
+}
+
+// decodepsbt, tx 及各输入输出字段较多, 保留为 JSON
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DecodedPsbt {
+    pub tx: Value,
+    pub global_xpubs: Option<Vec<Value>>,
+    pub psbt_version: Option<u32>,
+    pub unknown: Option<HashMap<String, String>>,
+    pub inputs: Vec<Value>,
+    pub outputs: Vec<Value>,
+    pub fee: Option<Amount>, // 全部输入有 utxo 信息时返回
+}
+
+// finalizepsbt
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FinalizedPsbt {
+    pub psbt: Option<String>, // 未完成或 extract=false 时返回
+    pub hex: Option<String>,  // 完成且 extract=true 时返回
+    pub complete: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mempool_entry() {
+        let data = r#"{
+            "vsize": 141, "weight": 561, "time": 1700000000, "height": 820000,
+            "descendantcount": 1, "descendantsize": 141, "ancestorcount": 1, "ancestorsize": 141,
+            "wtxid": "aa", "fees": {"base": 0.00001410, "modified": 0.00001410, "ancestor": 0.00001410, "descendant": 0.00001410},
+            "depends": [], "spentby": [], "bip125-replaceable": true, "unbroadcast": false
+        }"#;
+        let res = serde_json::from_str::<HashMap<String, MempoolEntry>>(&format!("{{\"txid\": {}}}", data)).unwrap();
+        assert_eq!(res["txid"].bip125_replaceable, Some(true));
+        assert_eq!(res["txid"].virtual_size(), 141);
+        assert_eq!(res["txid"].base_fee(), Some(Amount::from_sat(1410)));
+
+        // DOGE 1.14
+        let data = r#"{
+            "size": 226, "fee": 1.00000000, "modifiedfee": 1.00000000, "time": 1700000000, "height": 5000000,
+            "startingpriority": 0, "currentpriority": 0,
+            "descendantcount": 1, "descendantsize": 226, "descendantfees": 100000000,
+            "ancestorcount": 1, "ancestorsize": 226, "ancestorfees": 100000000, "depends": []
+        }"#;
+        let doge = serde_json::from_str::<MempoolEntry>(data).unwrap();
+        assert_eq!(doge.virtual_size(), 226);
+        assert_eq!(doge.base_fee(), Some(Amount::from_sat(100_000_000)));
+        assert_eq!(doge.modified_fee(), Some(Amount::from_sat(100_000_000)));
+        assert!(doge.wtxid.is_none() && doge.bip125_replaceable.is_none());
+
+        // BCHN
+        let data = r#"{
+            "size": 219, "time": 1700000000, "fees": {"base": 0.00000219, "modified": 0.00000219},
+            "depends": [], "spentby": []
+        }"#;
+        let bch = serde_json::from_str::<MempoolEntry>(data).unwrap();
+        assert_eq!(bch.virtual_size(), 219);
+        assert_eq!(bch.base_fee(), Some(Amount::from_sat(219)));
+        assert!(bch.height.is_none() && bch.ancestorcount.is_none());
+        assert!(bch.fees.unwrap().ancestor.is_none());
+    }
+
+    #[test]
+    fn test_chain_tips() {
+        let data = r#"[
+            {"height": 820000, "hash": "00", "branchlen": 0, "status": "active"},
+            {"height": 819990, "hash": "01", "branchlen": 1, "status": "valid-fork"},
+            {"height": 700000, "hash": "02", "branchlen": 2, "status": "headers-only"}
+        ]"#;
+        let tips = serde_json::from_str::<Vec<ChainTip>>(data).unwrap();
+        assert_eq!(tips[0].status, ChainTipStatus::Active);
+        assert_eq!(tips[1].status, ChainTipStatus::ValidFork);
+        assert_eq!(tips[2].status, ChainTipStatus::HeadersOnly);
+    }
+
+    #[test]
+    fn test_block_with_prevouts() {
+        let data = r#"{
+            "hash": "00", "confirmations": 1, "height": 1, "version": 1, "merkleroot": "aa",
+            "time": 1, "nonce": 0, "bits": "1d00ffff", "difficulty": 1.0, "chainwork": "00",
+            "nTx": 2, "size": 400, "weight": 1600,
+            "tx": [
+                {
+                    "txid": "c1", "hash": "c1", "version": 1, "size": 100, "vsize": 100, "weight": 400, "locktime": 0,
+                    "vin": [{"coinbase": "04ff", "sequence": 4294967295}],
+                    "vout": [{"value": 50.0, "n": 0, "scriptPubKey": {"asm": "", "desc": "", "hex": "51", "type": "nonstandard"}}],
+                    "hex": "00"
+                },
+                {
+                    "txid": "t1", "hash": "t1", "version": 2, "size": 200, "vsize": 200, "weight": 800, "locktime": 0,
+                    "vin": [{
+                        "txid": "c0", "vout": 0, "scriptSig": {"asm": "", "hex": ""}, "sequence": 4294967293,
+                        "prevout": {"generated": true, "height": 0, "value": 1.5, "scriptPubKey": {"asm": "", "desc": "", "hex": "51", "type": "nonstandard"}}
+                    }],
+                    "vout": [{"value": 1.4999, "n": 0, "scriptPubKey": {"asm": "", "desc": "", "hex": "51", "type": "nonstandard"}}],
+                    "fee": 0.0001,
+                    "hex": "00"
+                }
+            ]
+        }"#;
+        let block = serde_json::from_str::<BlockWithTxs>(data).unwrap();
+        assert!(block.tx[0].fee.is_none());
+        assert_eq!(block.tx[1].fee, Some(Amount::from_sat(10_000)));
+        assert!(block.tx[1].vin[0].prevout.as_ref().is_some_and(|prevout| prevout.generated));
+    }
+
+    #[test]
+    fn test_fork_block() {
+        // DOGE/BCH 节点: 区块无 nTx/weight, 交易无 vsize/weight, scriptPubKey 无 desc
+        let data = r#"{
+            "hash": "00", "confirmations": 1, "height": 1, "version": 1, "merkleroot": "aa",
+            "time": 1, "nonce": 0, "bits": "1e0ffff0", "difficulty": 1.0, "chainwork": "00", "size": 191,
+            "tx": [
+                {
+                    "txid": "c1", "hash": "c1", "version": 1, "size": 110, "locktime": 0,
+                    "vin": [{"coinbase": "04ff", "sequence": 4294967295}],
+                    "vout": [{"value": 10000.0, "n": 0, "scriptPubKey": {"asm": "", "hex": "51", "type": "nonstandard"}}],
+                    "hex": "00"
+                }
+            ]
+        }"#;
+        let block = serde_json::from_str::<BlockWithTxs>(data).unwrap();
+        assert_eq!(block.tx_count(), 1);
+        assert!(block.weight.is_none());
+        assert_eq!(block.tx[0].virtual_size(), 110);
+        assert!(block.tx[0].vout[0].script_pub_key.desc.is_none());
+    }
+
+    #[test]
+    fn test_warnings() {
+        let old = serde_json::from_str::<Warnings>(r#""""#).unwrap();
+        assert!(matches!(old, Warnings::Single(_)));
+        let new = serde_json::from_str::<Warnings>(r#"["a", "b"]"#).unwrap();
+        assert!(matches!(new, Warnings::List(list) if list.len() == 2));
+    }
+
+    #[test]
+    fn test_mempool_accept() {
+        let data = r#"[
+            {"txid": "t1", "wtxid": "w1", "allowed": true, "vsize": 141,
+             "fees": {"base": 0.00001410, "effective-feerate": 0.00010000, "effective-includes": ["w1"]}},
+            {"txid": "t2", "wtxid": "w2", "allowed": false, "reject-reason": "min relay fee not met",
+             "reject-details": "min relay fee not met, 0 < 141"},
+            {"txid": "t3", "wtxid": "w3", "package-error": "package-not-sorted"}
+        ]"#;
+        let res = serde_json::from_str::<Vec<MempoolAcceptResult>>(data).unwrap();
+        assert!(res[0].is_allowed());
+        assert_eq!(res[0].fees.as_ref().unwrap().base, Amount::from_sat(1410));
+        assert_eq!(res[1].reject(), Some(RejectReason::MinRelayFeeNotMet));
+        assert!(!res[2].is_allowed() && res[2].reject().is_none());
+
+        let data = r#"{
+            "package_msg": "transaction failed",
+            "tx-results": {
+                "w1": {"txid": "t1", "vsize": 100, "fees": {"base": 0.00002}},
+                "w2": {"txid": "t2", "error": "insufficient fee, rejecting replacement"}
+            },
+            "replaced-transactions": []
+        }"#;
+        let res = serde_json::from_str::<SubmitPackageResult>(data).unwrap();
+        assert!(!res.is_success());
+        assert_eq!(res.tx_results["w2"].reject(), Some(RejectReason::InsufficientFee));
+    }
+
+    #[test]
+    fn test_wallet_transactions() {
+        let data = r#"{
+            "transactions": [
+                {"involvesWatchonly": true, "address": "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4", "parent_descs": ["wpkh(xpub/0/*)#abcd"],
+                 "category": "receive", "amount": 0.1, "label": "", "vout": 1, "confirmations": 3,
+                 "blockhash": "00", "blockheight": 800000, "blockindex": 5, "blocktime": 1700000000,
+                 "txid": "t1", "wtxid": "w1", "walletconflicts": [], "time": 1700000000, "timereceived": 1700000001,
+                 "bip125-replaceable": "no"},
+                {"category": "send", "amount": -0.05, "fee": -0.0000141, "vout": 0, "confirmations": -1, "trusted": false,
+                 "txid": "t2", "walletconflicts": ["t3"], "time": 1, "timereceived": 1, "abandoned": false}
+            ],
+            "removed": [],
+            "lastblock": "00"
+        }"#;
+        let res = serde_json::from_str::<SinceBlock>(data).unwrap();
+        assert_eq!(res.transactions[0].category, TransactionCategory::Receive);
+        assert_eq!(res.transactions[1].amount, Amount::from_sat(-5_000_000));
+        assert_eq!(res.transactions[1].fee, Some(Amount::from_sat(-1410)));
+
+        let data = r#"{"mine": {"trusted": 1.5, "untrusted_pending": 0, "immature": 0}, "lastprocessedblock": {"hash": "00", "height": 1}}"#;
+        let res = serde_json::from_str::<WalletBalances>(data).unwrap();
+        assert_eq!(res.mine.trusted, Amount::from_sat(150_000_000));
+        assert_eq!(res.mine.untrusted_pending, Amount::ZERO);
+    }
+}
@@ -0,0 +1,358 @@
+use std::time::Duration;
+
+use anyhow::bail;
+use reqwest::Client;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use super::{
+    amount::Amount,
+    consensus::{hash_from_hex, hash_to_hex, Decoder, RawBlock, RawBlockHeader, RawTransaction, RawTxOut},
+    error::{decode_body, BtcRpcError},
+    response_type::{Block, BlockHeader, BlockWithTxs, BlockchainInfo, Transaction},
+};
+
+/// getutxos 单次请求的最大输出数, 见 src/rest.cpp MAX_GETUTXOS_OUTPOINTS
+pub const MAX_GETUTXOS_OUTPOINTS: usize = 15;
+
+/// REST 响应格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RestFormat {
+    Json,
+    Hex,
+    Bin,
+}
+
+impl RestFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            RestFormat::Json => "json",
+            RestFormat::Hex => "hex",
+            RestFormat::Bin => "bin",
+        }
+    }
+}
+
+/// getutxos 结果
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RestUtxos {
+    pub chain_height: u64,
+    pub chaintip_hash: String,
+    /// 与请求的输出一一对应, true 表示未花费
+    pub bitmap: Vec<bool>,
+    /// 仅包含未花费的输出, 按请求顺序
+    pub utxos: Vec<RestUtxo>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RestUtxo {
+    /// 交易池中的输出为 0x7FFFFFFF (MEMPOOL_HEIGHT)
+    pub height: u64,
+    pub value: Amount,
+    pub script_pubkey: String,
+}
+
+// getutxos 的 json 响应
+#[derive(Debug, Deserialize)]
+struct JsonUtxos {
+    #[serde(rename = "chainHeight")]
+    chain_height: u64,
+    #[serde(rename = "chaintipHash")]
+    chaintip_hash: String,
+    bitmap: String,
+    utxos: Vec<JsonUtxo>,
+}
+
+#[derive(Debug, Deserialize)]
+struct JsonUtxo {
+    height: u64,
+    value: Amount,
+    #[serde(rename = "scriptPubKey")]
+    script_pub_key: JsonScript,
+}
+
+#[derive(Debug, Deserialize)]
+struct JsonScript {
+    hex: String,
+}
+
+/// Bitcoin Core REST 接口 (-rest), 无需认证. 二进制格式由 consensus 模块解析,
+/// 批量同步历史区块时比 JSON-RPC 快得多
+#[derive(Debug, Clone)]
+pub struct BtcRestClient {
+    client: Client,
+    url: String,
+}
+
+impl BtcRestClient {
+    /// url 为节点 RPC 地址, 如 http://127.0.0.1:8332
+    pub fn new(url: &str, timeout: Duration) -> Result<Self, anyhow::Error> {
+        let client = reqwest::Client::builder().timeout(timeout).build()?;
+
+        Ok(BtcRestClient {
+            client,
+            url: url.trim_end_matches('/').to_owned(),
+        })
+    }
+
+    // GET /rest/<path>, 非 2xx 返回 HttpStatus
+    async fn get_bytes(&self, path: &str) -> Result<Vec<u8>, BtcRpcError> {
+        let url = format!("{}/rest/{}", self.url, path);
+        log::debug!("[rest] {}", url);
+
+        let res = self.client.get(&url).send().await?;
+        let status = res.status();
+        let body = res.bytes().await?.to_vec();
+
+        if !status.is_success() {
+            return Err(BtcRpcError::HttpStatus {
+                status: status.as_u16(),
+                body: String::from_utf8_lossy(&body).trim().to_string(),
+            });
+        }
+        Ok(body)
+    }
+
+    async fn get_json<T: DeserializeOwned>(&self, path: &str) -> Result<T, BtcRpcError> {
+        let url = format!("{}/rest/{}", self.url, path);
+        log::debug!("[rest] {}", url);
+
+        let res = self.client.get(&url).send().await?;
+        let status = res.status();
+        let body = res.text().await?;
+
+        decode_body(status, body)
+    }
+
+    async fn get_hex(&self, path: &str) -> Result<String, BtcRpcError> {
+        let body = self.get_bytes(path).await?;
+        Ok(String::from_utf8_lossy(&body).trim().to_string())
+    }
+
+    /// 完整区块, 交易含 prevout
+    pub async fn get_block(&self, hash: &str) -> Result<BlockWithTxs, BtcRpcError> {
+        self.get_json(&format!("block/{}.json", hash)).await
+    }
+
+    /// 区块, tx 仅为 txid 列表
+    pub async fn get_block_txids(&self, hash: &str) -> Result<Block, BtcRpcError> {
+        self.get_json(&format!("block/notxdetails/{}.json", hash)).await
+    }
+
+    pub async fn get_block_hex(&self, hash: &str) -> Result<String, BtcRpcError> {
+        self.get_hex(&format!("block/{}.hex", hash)).await
+    }
+
+    pub async fn get_block_bytes(&self, hash: &str) -> Result<Vec<u8>, BtcRpcError> {
+        self.get_bytes(&format!("block/{}.bin", hash)).await
+    }
+
+    /// 二进制格式获取并解析区块
+    pub async fn get_raw_block(&self, hash: &str) -> Result<RawBlock, anyhow::Error> {
+        RawBlock::from_bytes(&self.get_block_bytes(hash).await?)
+    }
+
+    /// 自 hash 起 (含) 最多 count 个区块头, v24+
+    pub async fn get_headers(&self, hash: &str, count: usize) -> Result<Vec<BlockHeader>, BtcRpcError> {
+        self.get_json(&format!("headers/{}.json?count={}", hash, count)).await
+    }
+
+    pub async fn get_headers_hex(&self, hash: &str, count: usize) -> Result<String, BtcRpcError> {
+        self.get_hex(&format!("headers/{}.hex?count={}", hash, count)).await
+    }
+
+    pub async fn get_raw_headers(&self, hash: &str, count: usize) -> Result<Vec<RawBlockHeader>, anyhow::Error> {
+        parse_headers(&self.get_bytes(&format!("headers/{}.bin?count={}", hash, count)).await?)
+    }
+
+    /// 主链上该高度的区块哈希
+    pub async fn get_block_hash(&self, height: u64) -> Result<String, anyhow::Error> {
+        let body = self.get_bytes(&format!("blockhashbyheight/{}.bin", height)).await?;
+        let hash: [u8; 32] = body.as_slice().try_into()?;
+        Ok(hash_to_hex(&hash))
+    }
+
+    /// 交易池外的交易需要节点开启 -txindex
+    pub async fn get_transaction(&self, txid: &str) -> Result<Transaction, BtcRpcError> {
+        self.get_json(&format!("tx/{}.json", txid)).await
+    }
+
+    pub async fn get_transaction_hex(&self, txid: &str) -> Result<String, BtcRpcError> {
+        self.get_hex(&format!("tx/{}.hex", txid)).await
+    }
+
+    pub async fn get_raw_transaction(&self, txid: &str) -> Result<RawTransaction, anyhow::Error> {
+        RawTransaction::from_bytes(&self.get_bytes(&format!("tx/{}.bin", txid)).await?)
+    }
+
+    /// 查询输出 (txid, vout) 是否未花费, check_mempool 为 true 时计入交易池的花费和新输出
+    pub async fn get_utxos(&self, outpoints: &[(&str, u32)], check_mempool: bool) -> Result<RestUtxos, anyhow::Error> {
+        let path = getutxos_path(outpoints, check_mempool, RestFormat::Bin)?;
+        parse_utxos(&self.get_bytes(&path).await?, outpoints.len())
+    }
+
+    pub async fn get_utxos_json(&self, outpoints: &[(&str, u32)], check_mempool: bool) -> Result<RestUtxos, anyhow::Error> {
+        let path = getutxos_path(outpoints, check_mempool, RestFormat::Json)?;
+        let res: JsonUtxos = self.get_json(&path).await?;
+
+        Ok(RestUtxos {
+            chain_height: res.chain_height,
+            chaintip_hash: res.chaintip_hash,
+            bitmap: res.bitmap.chars().map(|c| c == '1').collect(),
+            utxos: res
+                .utxos
+                .into_iter()
+                .map(|utxo| RestUtxo {
+                    height: utxo.height,
+                    value: utxo.value,
+                    script_pubkey: utxo.script_pub_key.hex,
+                })
+                .collect(),
+        })
+    }
+
+    pub async fn get_chain_info(&self) -> Result<BlockchainInfo, BtcRpcError> {
+        self.get_json("chaininfo.json").await
+    }
+}
+
+fn getutxos_path(outpoints: &[(&str, u32)], check_mempool: bool, format: RestFormat) -> Result<String, anyhow::Error> {
+    if outpoints.is_empty() || outpoints.len() > MAX_GETUTXOS_OUTPOINTS {
+        bail!("getutxos accepts 1 to {} outpoints, got {}", MAX_GETUTXOS_OUTPOINTS, outpoints.len());
+    }
+
+    let mut path = String::from("getutxos");
+    if check_mempool {
+        path.push_str("/checkmempool");
+    }
+    for (txid, vout) in outpoints {
+        // 提前校验, 避免拼出无效路径
+        hash_from_hex(txid)?;
+        path.push_str(&format!("/{}-{}", txid, vout));
+    }
+    path.push('.');
+    path.push_str(format.extension());
+    Ok(path)
+}
+
+/// 连续的 80 字节区块头
+fn parse_headers(data: &[u8]) -> Result<Vec<RawBlockHeader>, anyhow::Error> {
+    if !data.len().is_multiple_of(RawBlockHeader::SIZE) {
+        bail!("headers length {} is not a multiple of {}", data.len(), RawBlockHeader::SIZE);
+    }
+    data.chunks_exact(RawBlockHeader::SIZE).map(RawBlockHeader::from_bytes).collect()
+}
+
+/// <i32 高度><32 字节哈希><bitmap var bytes><CCoin 列表>,
+/// CCoin 为 <u32 占位 0><u32 高度><i64 金额><脚本 var bytes>
+fn parse_utxos(data: &[u8], count: usize) -> Result<RestUtxos, anyhow::Error> {
+    let mut decoder = Decoder::new(data);
+    let chain_height = decoder.read_u32()? as u64;
+    let chaintip_hash = hash_to_hex(&decoder.read_hash()?);
+
+    let bitmap_bytes = decoder.read_var_bytes()?;
+    if bitmap_bytes.len() != count.div_ceil(8) {
+        bail!("bitmap length {} does not match {} outpoints", bitmap_bytes.len(), count);
+    }
+    let bitmap = (0..count)
+        .map(|i| bitmap_bytes[i / 8] & (1 << (i % 8)) != 0)
+        .collect::<Vec<_>>();
+
+    let utxo_len = decoder.read_len()?;
+    let mut utxos = Vec::with_capacity(utxo_len);
+    for _ in 0..utxo_len {
+        decoder.read_u32()?;
+        let height = decoder.read_u32()? as u64;
+        let txout = RawTxOut::decode(&mut decoder)?;
+        utxos.push(RestUtxo {
+            height,
+            value: txout.value,
+            script_pubkey: hex::encode(txout.script_pubkey),
+        });
+    }
+
+    if !decoder.is_empty() {
+        bail!("trailing bytes after getutxos response");
+    }
+    if utxos.len() != bitmap.iter().filter(|unspent| **unspent).count() {
+        bail!("utxo count does not match bitmap");
+    }
+
+    Ok(RestUtxos {
+        chain_height,
+        chaintip_hash,
+        bitmap,
+        utxos,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::btc_client::consensus::{tests::GENESIS_BLOCK, write_var_bytes, write_varint};
+
+    const TXID: &str = "4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b";
+
+    #[test]
+    fn test_getutxos_path() {
+        assert_eq!(
+            getutxos_path(&[(TXID, 0), (TXID, 1)], true, RestFormat::Bin).unwrap(),
+            format!("getutxos/checkmempool/{}-0/{}-1.bin", TXID, TXID)
+        );
+        assert!(getutxos_path(&[], false, RestFormat::Json).is_err());
+        assert!(getutxos_path(&[("zz", 0)], false, RestFormat::Json).is_err());
+        assert!(getutxos_path(&[(TXID, 0); 16], false, RestFormat::Json).is_err());
+    }
+
+    #[test]
+    fn test_parse_utxos() {
+        let mut data = vec![];
+        data.extend(100u32.to_le_bytes());
+        data.extend([0x11; 32]);
+        // 3 个输出, 第 1、3 个未花费
+        write_var_bytes(&mut data, &[0b101]);
+        write_varint(&mut data, 2);
+        for (height, value) in [(90u32, 5_000u64), (0x7fff_ffff, 1)] {
+            data.extend(0u32.to_le_bytes());
+            data.extend(height.to_le_bytes());
+            data.extend(value.to_le_bytes());
+            write_var_bytes(&mut data, &[0x51]);
+        }
+
+        let utxos = parse_utxos(&data, 3).unwrap();
+        assert_eq!(utxos.chain_height, 100);
+        assert_eq!(utxos.chaintip_hash, "11".repeat(32));
+        assert_eq!(utxos.bitmap, vec![true, false, true]);
+        assert_eq!(utxos.utxos[0].value, Amount::from_sat(5_000));
+        assert_eq!(utxos.utxos[1].script_pubkey, "51");
+
+        assert!(parse_utxos(&data, 9).is_err());
+        assert!(parse_utxos(&data[..data.len() - 1], 3).is_err());
+
+        // 金额超过 i64::MAX
+        let value_pos = data.len() - 2 - 8;
+        data[value_pos..value_pos + 8].copy_from_slice(&u64::MAX.to_le_bytes());
+        assert!(parse_utxos(&data, 3).is_err());
+    }
+
+    #[test]
+    fn test_parse_json_utxos() {
+        let res: JsonUtxos = serde_json::from_value(json!({
+            "chainHeight": 100, "chaintipHash": "11".repeat(32), "bitmap": "10",
+            "utxos": [{"height": 90, "value": 0.00005, "scriptPubKey": {"asm": "OP_TRUE", "hex": "51", "type": "nonstandard"}}]
+        }))
+        .unwrap();
+        assert_eq!(res.utxos[0].value, Amount::from_sat(5_000));
+        assert_eq!(res.bitmap, "10");
+    }
+
+    #[test]
+    fn test_parse_headers() {
+        let genesis = hex::decode(&GENESIS_BLOCK[..160]).unwrap();
+        let headers = parse_headers(&[genesis.clone(), genesis].concat()).unwrap();
+        assert_eq!(headers.len(), 2);
+        assert_eq!(headers[0].hash(), "000000000019d6689c085ae165831e934ff763ae46a2a6c172b3f1b60a8ce26f");
+        assert!(parse_headers(&[0u8; 81]).is_err());
+    }
+}
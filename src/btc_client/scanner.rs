@@ -0,0 +1,433 @@
+use std::{collections::VecDeque, time::Duration};
+
+use anyhow::bail;
+use async_trait::async_trait;
+use futures::Stream;
+use serde::{Deserialize, Serialize};
+
+use crate::db::{cache::CacheDb, pgsql::Db};
+
+use super::{response_type::BlockWithTxs, BtcClient};
+
+/// 扫块的数据来源, BtcClient 已实现, 测试时可替换为脚本化的假节点
+#[async_trait]
+pub trait BlockSource: Send + Sync {
+    async fn tip_height(&self) -> Result<u64, anyhow::Error>;
+    async fn block_hash(&self, height: u64) -> Result<String, anyhow::Error>;
+    async fn block(&self, hash: &str) -> Result<BlockWithTxs, anyhow::Error>;
+}
+
+#[async_trait]
+impl BlockSource for BtcClient {
+    async fn tip_height(&self) -> Result<u64, anyhow::Error> {
+        Ok(self.get_block_count_result().await? as u64)
+    }
+
+    async fn block_hash(&self, height: u64) -> Result<String, anyhow::Error> {
+        Ok(self.get_block_hash_result(height).await?)
+    }
+
+    async fn block(&self, hash: &str) -> Result<BlockWithTxs, anyhow::Error> {
+        Ok(self.get_block_with_txs_result(hash).await?)
+    }
+}
+
+/// 扫块游标: 最后处理的区块及最近 N 个区块哈希 (用于重启后的分叉检测)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScanCursor {
+    pub height: u64,
+    pub hash: String,
+    /// (高度, 哈希), 按高度升序
+    pub recent: VecDeque<(u64, String)>,
+}
+
+/// 游标持久化
+#[async_trait]
+pub trait CursorStore: Send + Sync {
+    async fn load(&self) -> Result<Option<ScanCursor>, anyhow::Error>;
+    async fn save(&self, cursor: &ScanCursor) -> Result<(), anyhow::Error>;
+}
+
+#[async_trait]
+impl<T: CursorStore> CursorStore for &T {
+    async fn load(&self) -> Result<Option<ScanCursor>, anyhow::Error> {
+        (**self).load().await
+    }
+
+    async fn save(&self, cursor: &ScanCursor) -> Result<(), anyhow::Error> {
+        (**self).save(cursor).await
+    }
+}
+
+/// 内存游标, 进程重启后丢失
+#[derive(Debug, Default)]
+pub struct MemoryCursorStore {
+    cursor: std::sync::Mutex<Option<ScanCursor>>,
+}
+
+#[async_trait]
+impl CursorStore for MemoryCursorStore {
+    async fn load(&self) -> Result<Option<ScanCursor>, anyhow::Error> {
+        Ok(self.cursor.lock().unwrap().clone())
+    }
+
+    async fn save(&self, cursor: &ScanCursor) -> Result<(), anyhow::Error> {
+        *self.cursor.lock().unwrap() = Some(cursor.clone());
+        Ok(())
+    }
+}
+
+/// Postgres 游标, 存于 btc_scan_cursor 表, name 区分不同扫块任务
+#[derive(Clone)]
+pub struct PgCursorStore {
+    pub db: Db,
+    pub name: String,
+}
+
+impl PgCursorStore {
+    pub fn new(db: Db, name: &str) -> Self {
+        PgCursorStore {
+            db,
+            name: name.to_owned(),
+        }
+    }
+
+    pub async fn init_table(&self) -> Result<(), anyhow::Error> {
+        let sql = "CREATE TABLE IF NOT EXISTS btc_scan_cursor (
+            name TEXT PRIMARY KEY,
+            cursor TEXT NOT NULL,
+            updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+        )";
+        self.db.execute_sql(sql.to_owned(), vec![]).await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl CursorStore for PgCursorStore {
+    async fn load(&self) -> Result<Option<ScanCursor>, anyhow::Error> {
+        let sql = "SELECT cursor FROM btc_scan_cursor WHERE name = $1".to_owned();
+        let rows = self.db.clone().select_all::<(String,)>(sql, vec![self.name.clone()]).await?;
+
+        match rows.into_iter().next() {
+            Some((cursor,)) => Ok(Some(serde_json::from_str(&cursor)?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn save(&self, cursor: &ScanCursor) -> Result<(), anyhow::Error> {
+        let sql = "INSERT INTO btc_scan_cursor (name, cursor) VALUES ($1, $2)
+            ON CONFLICT (name) DO UPDATE SET cursor = EXCLUDED.cursor, updated_at = now()";
+        let params = vec![self.name.clone(), serde_json::to_string(cursor)?];
+        self.db.execute_sql(sql.to_owned(), params).await?;
+        Ok(())
+    }
+}
+
+/// Redis 游标, 以 JSON 字符串存于 key
+#[derive(Debug, Clone)]
+pub struct RedisCursorStore {
+    pub cache_db: CacheDb,
+    pub key: String,
+}
+
+impl RedisCursorStore {
+    pub fn new(cache_db: CacheDb, key: &str) -> Self {
+        RedisCursorStore {
+            cache_db,
+            key: key.to_owned(),
+        }
+    }
+}
+
+#[async_trait]
+impl CursorStore for RedisCursorStore {
+    async fn load(&self) -> Result<Option<ScanCursor>, anyhow::Error> {
+        match self.cache_db.get_val::<&str, String>(&self.key).await? {
+            Some(cursor) => Ok(Some(serde_json::from_str(&cursor)?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn save(&self, cursor: &ScanCursor) -> Result<(), anyhow::Error> {
+        self.cache_db.insert(&self.key, serde_json::to_string(cursor)?, None).await
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ScannerConfig {
+    /// 无游标时的起始高度
+    pub start_height: u64,
+    /// 所需确认数, 1 表示处理到最新块
+    pub confirmations: u64,
+    /// 保留的最近区块哈希数量, 即可处理的最大回滚深度
+    pub max_reorg_depth: usize,
+    /// 追上最新块后的轮询间隔
+    pub poll_interval: Duration,
+}
+
+impl Default for ScannerConfig {
+    fn default() -> Self {
+        ScannerConfig {
+            start_height: 0,
+            confirmations: 1,
+            max_reorg_depth: 100,
+            poll_interval: Duration::from_secs(10),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum ScanEvent {
+    /// 新区块, 按高度依次产出
+    Block(Box<BlockWithTxs>),
+    /// 分叉回滚: removed 中的区块(高度降序)已失效, 之后从 height + 1 重新产出
+    Rollback {
+        height: u64,
+        hash: String,
+        removed: Vec<(u64, String)>,
+    },
+}
+
+/// 比特币扫块器: 按高度产出区块, 通过 previousblockhash 检测分叉并产出回滚事件.
+/// 游标在下一次调用 next_event 时才持久化, 保证每个事件至少被处理一次.
+pub struct BtcBlockScanner<C: BlockSource, S: CursorStore> {
+    source: C,
+    store: S,
+    config: ScannerConfig,
+    cursor: Option<ScanCursor>,
+    dirty: bool,
+}
+
+impl<C: BlockSource, S: CursorStore> BtcBlockScanner<C, S> {
+    pub async fn new(source: C, store: S, config: ScannerConfig) -> Result<Self, anyhow::Error> {
+        let cursor = store.load().await?;
+
+        if let Some(cursor) = &cursor {
+            log::info!("[BtcBlockScanner] resume from {} {}", cursor.height, cursor.hash);
+        }
+
+        Ok(BtcBlockScanner {
+            source,
+            store,
+            config,
+            cursor,
+            dirty: false,
+        })
+    }
+
+    pub fn cursor(&self) -> Option<&ScanCursor> {
+        self.cursor.as_ref()
+    }
+
+    /// 下一个待处理的高度
+    pub fn next_height(&self) -> u64 {
+        match &self.cursor {
+            Some(cursor) => cursor.height + 1,
+            None => self.config.start_height,
+        }
+    }
+
+    /// 处理一步, 没有满足确认数的新区块时返回 None
+    pub async fn next_event(&mut self) -> Result<Option<ScanEvent>, anyhow::Error> {
+        if self.dirty {
+            if let Some(cursor) = &self.cursor {
+                self.store.save(cursor).await?;
+            }
+            self.dirty = false;
+        }
+
+        let tip = self.source.tip_height().await?;
+        let height = self.next_height();
+        if tip + 1 < height + self.config.confirmations.max(1) {
+            return Ok(None);
+        }
+
+        let hash = self.source.block_hash(height).await?;
+        let block = self.source.block(&hash).await?;
+
+        if let Some(cursor) = &self.cursor {
+            if block.previousblockhash.as_deref() != Some(cursor.hash.as_str()) {
+                return self.rollback().await.map(Some);
+            }
+        }
+
+        let mut recent = self.cursor.take().map(|cursor| cursor.recent).unwrap_or_default();
+        recent.push_back((height, hash.clone()));
+        while recent.len() > self.config.max_reorg_depth.max(1) {
+            recent.pop_front();
+        }
+
+        self.cursor = Some(ScanCursor { height, hash, recent });
+        self.dirty = true;
+
+        Ok(Some(ScanEvent::Block(Box::new(block))))
+    }
+
+    /// 等待下一个事件
+    pub async fn next(&mut self) -> Result<ScanEvent, anyhow::Error> {
+        loop {
+            if let Some(event) = self.next_event().await? {
+                return Ok(event);
+            }
+            async_std::task::sleep(self.config.poll_interval).await;
+        }
+    }
+
+    /// 转为事件流, 出错后流继续, 由调用方决定是否重试
+    pub fn into_stream(self) -> impl Stream<Item = Result<ScanEvent, anyhow::Error>> {
+        futures::stream::unfold(self, |mut scanner| async move {
+            let event = scanner.next().await;
+            Some((event, scanner))
+        })
+    }
+
+    // 自最新的已知区块向前查找仍在主链上的区块作为分叉点
+    async fn rollback(&mut self) -> Result<ScanEvent, anyhow::Error> {
+        let mut cursor = match self.cursor.take() {
+            Some(cursor) => cursor,
+            None => bail!("rollback without cursor"),
+        };
+
+        let mut removed = Vec::new();
+        while let Some((height, hash)) = cursor.recent.pop_back() {
+            if self.source.block_hash(height).await? == hash {
+                cursor.recent.push_back((height, hash.clone()));
+                cursor.height = height;
+                cursor.hash = hash.clone();
+                self.cursor = Some(cursor);
+                self.dirty = true;
+
+                log::warn!("[BtcBlockScanner] reorg, rollback to {} {} removed {:?}", height, hash, removed);
+
+                return Ok(ScanEvent::Rollback { height, hash, removed });
+            }
+            removed.push((height, hash));
+        }
+
+        // 恢复游标, 由调用方处理
+        cursor.recent = removed.into_iter().rev().collect();
+        let height = cursor.height;
+        self.cursor = Some(cursor);
+        bail!("reorg deeper than {} blocks at height {}", self.config.max_reorg_depth, height)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, sync::Mutex};
+
+    use super::*;
+    use crate::btc_client::response_type::Block;
+
+    // 脚本化的假节点, chain 为主链各高度的哈希
+    #[derive(Default)]
+    struct FakeNode {
+        chain: Mutex<Vec<String>>,
+        blocks: Mutex<HashMap<String, BlockWithTxs>>,
+    }
+
+    impl FakeNode {
+        fn extend(&self, from: usize, names: &[&str]) {
+            let mut chain = self.chain.lock().unwrap();
+            chain.truncate(from);
+            for name in names {
+                let height = chain.len();
+                let block = Block {
+                    hash: name.to_string(),
+                    confirmations: 1,
+                    height,
+                    version: 1,
+                    version_hex: None,
+                    merkleroot: String::new(),
+                    time: 0,
+                    mediantime: None,
+                    nonce: 0,
+                    bits: String::new(),
+                    difficulty: 1.0,
+                    chainwork: String::new(),
+                    n_tx: None,
+                    previousblockhash: chain.last().cloned(),
+                    nextblockhash: None,
+                    strippedsize: None,
+                    size: 0,
+                    weight: None,
+                    tx: vec![],
+                };
+                self.blocks.lock().unwrap().insert(name.to_string(), block);
+                chain.push(name.to_string());
+            }
+        }
+    }
+
+    #[async_trait]
+    impl BlockSource for &FakeNode {
+        async fn tip_height(&self) -> Result<u64, anyhow::Error> {
+            Ok(self.chain.lock().unwrap().len() as u64 - 1)
+        }
+
+        async fn block_hash(&self, height: u64) -> Result<String, anyhow::Error> {
+            match self.chain.lock().unwrap().get(height as usize) {
+                Some(hash) => Ok(hash.clone()),
+                None => bail!("height out of range"),
+            }
+        }
+
+        async fn block(&self, hash: &str) -> Result<BlockWithTxs, anyhow::Error> {
+            Ok(self.blocks.lock().unwrap()[hash].clone())
+        }
+    }
+
+    async fn drain<C: BlockSource, S: CursorStore>(scanner: &mut BtcBlockScanner<C, S>) -> Vec<String> {
+        let mut events = vec![];
+        while let Some(event) = scanner.next_event().await.unwrap() {
+            events.push(match event {
+                ScanEvent::Block(block) => block.hash,
+                ScanEvent::Rollback { height, .. } => format!("rollback:{}", height),
+            });
+        }
+        events
+    }
+
+    fn config(confirmations: u64) -> ScannerConfig {
+        ScannerConfig {
+            confirmations,
+            max_reorg_depth: 3,
+            ..Default::default()
+        }
+    }
+
+    #[async_std::test]
+    async fn test_reorg() {
+        let node = FakeNode::default();
+        node.extend(0, &["a0", "a1", "a2", "a3"]);
+
+        let mut scanner = BtcBlockScanner::new(&node, MemoryCursorStore::default(), config(1)).await.unwrap();
+        assert_eq!(drain(&mut scanner).await, ["a0", "a1", "a2", "a3"]);
+
+        // a2, a3 被 b2, b3, b4 替换
+        node.extend(2, &["b2", "b3", "b4"]);
+        assert_eq!(drain(&mut scanner).await, ["rollback:1", "b2", "b3", "b4"]);
+
+        // 超出保留深度
+        node.extend(0, &["c0", "c1", "c2", "c3", "c4", "c5"]);
+        assert!(scanner.next_event().await.is_err());
+    }
+
+    #[async_std::test]
+    async fn test_confirmations_and_resume() {
+        let node = FakeNode::default();
+        node.extend(0, &["a0", "a1", "a2", "a3"]);
+        let store = MemoryCursorStore::default();
+
+        let mut scanner = BtcBlockScanner::new(&node, &store, config(3)).await.unwrap();
+        assert_eq!(drain(&mut scanner).await, ["a0", "a1"]);
+        drop(scanner);
+
+        // 重启后从已保存的游标继续
+        node.extend(4, &["a4"]);
+        let mut scanner = BtcBlockScanner::new(&node, &store, config(3)).await.unwrap();
+        assert_eq!(scanner.next_height(), 2);
+        assert_eq!(drain(&mut scanner).await, ["a2"]);
+    }
+}
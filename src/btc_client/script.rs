@@ -0,0 +1,496 @@
+use crate::utils::{
+    address::{cashaddr_encode, AddressType},
+    address_convert::{convert_b58encode, hash160, segwit_encode},
+};
+
+use super::{network::NetworkParams, response_type::ScriptPubKey};
+
+const OP_0: u8 = 0x00;
+const OP_PUSHDATA1: u8 = 0x4c;
+const OP_PUSHDATA2: u8 = 0x4d;
+const OP_PUSHDATA4: u8 = 0x4e;
+const OP_1: u8 = 0x51;
+const OP_16: u8 = 0x60;
+const OP_RETURN: u8 = 0x6a;
+const OP_DUP: u8 = 0x76;
+const OP_EQUAL: u8 = 0x87;
+const OP_EQUALVERIFY: u8 = 0x88;
+const OP_HASH160: u8 = 0xa9;
+const OP_CHECKSIG: u8 = 0xac;
+const OP_CHECKMULTISIG: u8 = 0xae;
+
+/// 脚本中的一条指令
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Instruction<'a> {
+    Push(&'a [u8]),
+    Op(u8),
+}
+
+/// 拆分脚本指令, push 长度越界时返回 None
+pub fn instructions(script: &[u8]) -> Option<Vec<Instruction<'_>>> {
+    let mut res = Vec::new();
+    let mut pos = 0;
+
+    while pos < script.len() {
+        let opcode = script[pos];
+        pos += 1;
+
+        let len = match opcode {
+            OP_0 => 0,
+            0x01..=0x4b => opcode as usize,
+            OP_PUSHDATA1 => {
+                let len = *script.get(pos)? as usize;
+                pos += 1;
+                len
+            }
+            OP_PUSHDATA2 => {
+                let len = u16::from_le_bytes(script.get(pos..pos + 2)?.try_into().ok()?) as usize;
+                pos += 2;
+                len
+            }
+            OP_PUSHDATA4 => {
+                let len = u32::from_le_bytes(script.get(pos..pos + 4)?.try_into().ok()?) as usize;
+                pos += 4;
+                len
+            }
+            _ => {
+                res.push(Instruction::Op(opcode));
+                continue;
+            }
+        };
+
+        res.push(Instruction::Push(script.get(pos..pos + len)?));
+        pos += len;
+    }
+
+    Some(res)
+}
+
+/// 以最短的 push 操作码追加数据 (空数据为 OP_0)
+pub fn push_data(script: &mut Vec<u8>, data: &[u8]) {
+    match data.len() {
+        0..=0x4b => script.push(data.len() as u8),
+        0x4c..=0xff => script.extend([OP_PUSHDATA1, data.len() as u8]),
+        0x100..=0xffff => {
+            script.push(OP_PUSHDATA2);
+            script.extend((data.len() as u16).to_le_bytes());
+        }
+        _ => {
+            script.push(OP_PUSHDATA4);
+            script.extend((data.len() as u32).to_le_bytes());
+        }
+    }
+    script.extend(data);
+}
+
+/// scriptPubKey 类型, 与 Bitcoin Core 的 Solver 分类一致
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScriptType {
+    P2pk { pubkey: Vec<u8> },
+    P2pkh { hash: [u8; 20] },
+    P2sh { hash: [u8; 20] },
+    P2wpkh { program: [u8; 20] },
+    P2wsh { program: [u8; 32] },
+    P2tr { output_key: [u8; 32] },
+    /// 见证版本1的 Pay-to-Anchor (OP_1 <0x4e73>)
+    Anchor,
+    WitnessUnknown { version: u8, program: Vec<u8> },
+    Multisig { required: u8, pubkeys: Vec<Vec<u8>> },
+    /// data 为 OP_RETURN 之后全部 push 数据的拼接
+    OpReturn { data: Vec<u8> },
+    NonStandard,
+}
+
+impl ScriptType {
+    pub fn from_hex(script_hex: &str) -> Self {
+        match hex::decode(script_hex) {
+            Ok(script) => Self::classify(&script),
+            Err(_) => ScriptType::NonStandard,
+        }
+    }
+
+    pub fn classify(script: &[u8]) -> Self {
+        let len = script.len();
+
+        // P2PKH: OP_DUP OP_HASH160 <20> OP_EQUALVERIFY OP_CHECKSIG
+        if len == 25
+            && script[0] == OP_DUP
+            && script[1] == OP_HASH160
+            && script[2] == 20
+            && script[23] == OP_EQUALVERIFY
+            && script[24] == OP_CHECKSIG
+        {
+            return ScriptType::P2pkh { hash: script[3..23].try_into().unwrap() };
+        }
+
+        // P2SH: OP_HASH160 <20> OP_EQUAL
+        if len == 23 && script[0] == OP_HASH160 && script[1] == 20 && script[22] == OP_EQUAL {
+            return ScriptType::P2sh { hash: script[2..22].try_into().unwrap() };
+        }
+
+        if let Some((version, program)) = witness_program(script) {
+            return match (version, program.len()) {
+                (0, 20) => ScriptType::P2wpkh { program: program.try_into().unwrap() },
+                (0, 32) => ScriptType::P2wsh { program: program.try_into().unwrap() },
+                (0, _) => ScriptType::NonStandard,
+                (1, 32) => ScriptType::P2tr { output_key: program.try_into().unwrap() },
+                (1, 2) if program == [0x4e, 0x73] => ScriptType::Anchor,
+                _ => ScriptType::WitnessUnknown {
+                    version,
+                    program: program.to_vec(),
+                },
+            };
+        }
+
+        let ops = match instructions(script) {
+            Some(ops) => ops,
+            None => return ScriptType::NonStandard,
+        };
+
+        if script.first() == Some(&OP_RETURN) {
+            // OP_RETURN 之后只允许 push
+            let mut data = Vec::new();
+            for op in &ops[1..] {
+                match op {
+                    Instruction::Push(bytes) => data.extend(*bytes),
+                    Instruction::Op(opcode) if *opcode <= OP_16 => {}
+                    _ => return ScriptType::NonStandard,
+                }
+            }
+            return ScriptType::OpReturn { data };
+        }
+
+        // P2PK: <pubkey> OP_CHECKSIG
+        if let [Instruction::Push(pubkey), Instruction::Op(OP_CHECKSIG)] = ops.as_slice() {
+            if is_pubkey(pubkey) {
+                return ScriptType::P2pk { pubkey: pubkey.to_vec() };
+            }
+        }
+
+        // 裸多签: OP_m <pubkey>... OP_n OP_CHECKMULTISIG
+        if let [Instruction::Op(m), keys @ .., Instruction::Op(n), Instruction::Op(OP_CHECKMULTISIG)] = ops.as_slice() {
+            let required = small_int(*m);
+            let total = small_int(*n);
+            let pubkeys = keys
+                .iter()
+                .map(|op| match op {
+                    Instruction::Push(key) if is_pubkey(key) => Some(key.to_vec()),
+                    _ => None,
+                })
+                .collect::<Option<Vec<_>>>();
+
+            if let (Some(required), Some(total), Some(pubkeys)) = (required, total, pubkeys) {
+                if required >= 1 && required <= total && total as usize == pubkeys.len() {
+                    return ScriptType::Multisig { required, pubkeys };
+                }
+            }
+        }
+
+        ScriptType::NonStandard
+    }
+
+    /// 与 Core 返回的 scriptPubKey.type 一致
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ScriptType::P2pk { .. } => "pubkey",
+            ScriptType::P2pkh { .. } => "pubkeyhash",
+            ScriptType::P2sh { .. } => "scripthash",
+            ScriptType::P2wpkh { .. } => "witness_v0_keyhash",
+            ScriptType::P2wsh { .. } => "witness_v0_scripthash",
+            ScriptType::P2tr { .. } => "witness_v1_taproot",
+            ScriptType::Anchor => "anchor",
+            ScriptType::WitnessUnknown { .. } => "witness_unknown",
+            ScriptType::Multisig { .. } => "multisig",
+            ScriptType::OpReturn { .. } => "nulldata",
+            ScriptType::NonStandard => "nonstandard",
+        }
+    }
+
+    /// 由类型重建脚本, 与 classify 互逆. OpReturn 按单个 push 重建, NonStandard 为空脚本.
+    /// 多签不满足 1 <= required <= 公钥数 <= 16, 或见证版本大于 16 时无法编码, 返回 None
+    pub fn script_pubkey(&self) -> Option<Vec<u8>> {
+        let mut script = vec![];
+        match self {
+            ScriptType::P2pk { pubkey } => {
+                push_data(&mut script, pubkey);
+                script.push(OP_CHECKSIG);
+            }
+            ScriptType::P2pkh { hash } => {
+                script.extend([OP_DUP, OP_HASH160, 20]);
+                script.extend(hash);
+                script.extend([OP_EQUALVERIFY, OP_CHECKSIG]);
+            }
+            ScriptType::P2sh { hash } => {
+                script.extend([OP_HASH160, 20]);
+                script.extend(hash);
+                script.push(OP_EQUAL);
+            }
+            ScriptType::P2wpkh { program } => witness_script(&mut script, 0, program)?,
+            ScriptType::P2wsh { program } => witness_script(&mut script, 0, program)?,
+            ScriptType::P2tr { output_key } => witness_script(&mut script, 1, output_key)?,
+            ScriptType::Anchor => witness_script(&mut script, 1, &[0x4e, 0x73])?,
+            ScriptType::WitnessUnknown { version, program } => witness_script(&mut script, *version, program)?,
+            ScriptType::Multisig { required, pubkeys } => {
+                let total = u8::try_from(pubkeys.len()).ok()?;
+                if *required == 0 || *required > total {
+                    return None;
+                }
+                script.push(small_int_opcode(*required)?);
+                for pubkey in pubkeys {
+                    push_data(&mut script, pubkey);
+                }
+                script.extend([small_int_opcode(total)?, OP_CHECKMULTISIG]);
+            }
+            ScriptType::OpReturn { data } => {
+                script.push(OP_RETURN);
+                if !data.is_empty() {
+                    push_data(&mut script, data);
+                }
+            }
+            ScriptType::NonStandard => {}
+        }
+        Some(script)
+    }
+
+    /// 推导地址. P2PK 归属到该公钥的 P2PKH 地址; 多签、OP_RETURN 和非标准脚本没有地址.
+    /// 未激活隔离见证的链 (无 bech32 hrp) 见证输出也没有地址
+    pub fn address(&self, params: &NetworkParams) -> Option<String> {
+        let witness = |version: u8, program: &[u8]| segwit_encode(params.bech32_hrp?, version, program).ok();
+        match self {
+            ScriptType::P2pk { pubkey } => Some(legacy_address(params, AddressType::P2pkh, &hash160(pubkey))),
+            ScriptType::P2pkh { hash } => Some(legacy_address(params, AddressType::P2pkh, hash)),
+            ScriptType::P2sh { hash } => Some(legacy_address(params, AddressType::P2sh, hash)),
+            ScriptType::P2wpkh { program } => witness(0, program),
+            ScriptType::P2wsh { program } => witness(0, program),
+            ScriptType::P2tr { output_key } => witness(1, output_key),
+            ScriptType::Anchor => witness(1, &[0x4e, 0x73]),
+            ScriptType::WitnessUnknown { version, program } => witness(*version, program),
+            ScriptType::Multisig { .. } | ScriptType::OpReturn { .. } | ScriptType::NonStandard => None,
+        }
+    }
+}
+
+impl ScriptPubKey {
+    /// 由 hex 字段分类, 不依赖节点返回的 type
+    pub fn script_type(&self) -> ScriptType {
+        ScriptType::from_hex(&self.hex)
+    }
+
+    /// 由 hex 字段推导地址, 不依赖节点返回的 address/addresses, 保证不同版本节点的地址归属一致
+    pub fn derive_address(&self, params: &NetworkParams) -> Option<String> {
+        self.script_type().address(params)
+    }
+}
+
+// P2PKH/P2SH 地址, BCH 使用 CashAddr, 其余链为 Base58Check
+fn legacy_address(params: &NetworkParams, address_type: AddressType, hash: &[u8; 20]) -> String {
+    if let Some(prefix) = params.cashaddr_prefix {
+        return cashaddr_encode(prefix, address_type, hash);
+    }
+
+    let prefix = match address_type {
+        AddressType::P2sh => params.p2sh_prefix,
+        _ => params.p2pkh_prefix,
+    };
+    let mut raw = vec![prefix];
+    raw.extend(hash);
+    convert_b58encode(raw)
+}
+
+fn witness_script(script: &mut Vec<u8>, version: u8, program: &[u8]) -> Option<()> {
+    script.push(if version == 0 { OP_0 } else { small_int_opcode(version)? });
+    push_data(script, program);
+    Some(())
+}
+
+// 见证程序: OP_n <2-40字节>
+fn witness_program(script: &[u8]) -> Option<(u8, &[u8])> {
+    if script.len() < 4 || script.len() > 42 || script[1] as usize != script.len() - 2 {
+        return None;
+    }
+
+    let version = match script[0] {
+        OP_0 => 0,
+        OP_1..=OP_16 => script[0] - OP_1 + 1,
+        _ => return None,
+    };
+
+    Some((version, &script[2..]))
+}
+
+fn small_int(opcode: u8) -> Option<u8> {
+    match opcode {
+        OP_1..=OP_16 => Some(opcode - OP_1 + 1),
+        _ => None,
+    }
+}
+
+// small_int 的逆运算, 只支持 1-16
+fn small_int_opcode(n: u8) -> Option<u8> {
+    match n {
+        1..=16 => Some(OP_1 + n - 1),
+        _ => None,
+    }
+}
+
+fn is_pubkey(key: &[u8]) -> bool {
+    match key.len() {
+        33 => key[0] == 0x02 || key[0] == 0x03,
+        65 => key[0] == 0x04 || key[0] == 0x06 || key[0] == 0x07,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::btc_client::network::Network;
+
+    use super::*;
+
+    fn address(script_hex: &str, network: Network) -> (&'static str, Option<String>) {
+        let script_type = ScriptType::from_hex(script_hex);
+        (script_type.as_str(), script_type.address(&network.params()))
+    }
+
+    #[test]
+    fn test_classify() {
+        // 创世区块 P2PK
+        let (kind, addr) = address("4104678afdb0fe5548271967f1a67130b7105cd6a828e03909a67962e0ea1f61deb649f6bc3f4cef38c4f35504e51ec112de5c384df7ba0b8d578a4c702b6bf11d5fac", Network::Bitcoin);
+        assert_eq!(kind, "pubkey");
+        assert_eq!(addr.as_deref(), Some("1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa"));
+
+        let (kind, addr) = address("76a91462e907b15cbf27d5425399ebf6f0fb50ebb88f1888ac", Network::Bitcoin);
+        assert_eq!(kind, "pubkeyhash");
+        assert_eq!(addr.as_deref(), Some("1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa"));
+
+        let (kind, addr) = address("a914748284390f9e263a4b766a75d0633c50426eb87587", Network::Bitcoin);
+        assert_eq!(kind, "scripthash");
+        assert_eq!(addr.as_deref(), Some("3CK4fEwbMP7heJarmU4eqA3sMbVJyEnU3V"));
+
+        let (kind, addr) = address("0014751e76e8199196d454941c45d1b3a323f1433bd6", Network::Bitcoin);
+        assert_eq!(kind, "witness_v0_keyhash");
+        assert_eq!(addr.as_deref(), Some("bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4"));
+
+        let (kind, addr) = address("0014751e76e8199196d454941c45d1b3a323f1433bd6", Network::Regtest);
+        assert_eq!(kind, "witness_v0_keyhash");
+        assert_eq!(addr.as_deref(), Some("bcrt1qw508d6qejxtdg4y5r3zarvary0c5xw7kygt080"));
+
+        let (kind, addr) = address("00201863143c14c5166804bd19203356da136c985678cd4d27a1b8c6329604903262", Network::Testnet);
+        assert_eq!(kind, "witness_v0_scripthash");
+        assert_eq!(addr.as_deref(), Some("tb1qrp33g0q5c5txsp9arysrx4k6zdkfs4nce4xj0gdcccefvpysxf3q0sl5k7"));
+
+        let (kind, addr) = address("5120a60869f0dbcf1dc659c9cecbaf8050135ea9e8cdc487053f1dc6880949dc684c", Network::Bitcoin);
+        assert_eq!(kind, "witness_v1_taproot");
+        assert_eq!(addr.as_deref(), Some("bc1p5cyxnuxmeuwuvkwfem96lqzszd02n6xdcjrs20cac6yqjjwudpxqkedrcr"));
+
+        let (kind, addr) = address("51024e73", Network::Bitcoin);
+        assert_eq!(kind, "anchor");
+        assert_eq!(addr.as_deref(), Some("bc1pfeessrawgf"));
+
+        let (kind, addr) = address("6a0b68656c6c6f20776f726c64", Network::Bitcoin);
+        assert_eq!(kind, "nulldata");
+        assert_eq!(addr, None);
+
+        let (kind, _) = address("6a76", Network::Bitcoin);
+        assert_eq!(kind, "nonstandard");
+    }
+
+    #[test]
+    fn test_fork_address() {
+        use crate::btc_client::network::Coin;
+
+        let p2pkh = ScriptType::from_hex("76a91462e907b15cbf27d5425399ebf6f0fb50ebb88f1888ac");
+        let address = |coin: Coin, network: Network| p2pkh.address(&coin.params(network)).unwrap();
+        assert_eq!(address(Coin::Litecoin, Network::Bitcoin), "LUEweDxDA4WhvWiNXXSxjM9CYzHPJv4QQF");
+        assert_eq!(address(Coin::Dogecoin, Network::Bitcoin), "DEA5vGb2NpAwCiCp5yTE16F3DueQUVivQp");
+        assert_eq!(address(Coin::Dogecoin, Network::Testnet), "ndD9eHKwJndf5gn17o6gFVqLTn2hXk9FFJ");
+        assert_eq!(
+            address(Coin::BitcoinCash, Network::Bitcoin),
+            "bitcoincash:qp3wjpa3tjlj042z2wv7hahsldgwhwy0rq9sywjpyy"
+        );
+
+        let p2sh = ScriptType::from_hex("a91462e907b15cbf27d5425399ebf6f0fb50ebb88f1887");
+        assert_eq!(
+            p2sh.address(&Coin::Dogecoin.params(Network::Bitcoin)).as_deref(),
+            Some("A1TG3QCihNTvfF67tcng864kBsarnaPyFm")
+        );
+
+        // 未激活隔离见证的链, 见证输出没有地址
+        let p2wpkh = ScriptType::from_hex("0014751e76e8199196d454941c45d1b3a323f1433bd6");
+        assert!(p2wpkh.address(&Coin::Dogecoin.params(Network::Bitcoin)).is_none());
+        assert!(p2wpkh.address(&Coin::Litecoin.params(Network::Bitcoin)).unwrap().starts_with("ltc1q"));
+    }
+
+    #[test]
+    fn test_multisig() {
+        let key1 = format!("02{}", "11".repeat(32));
+        let key2 = format!("03{}", "22".repeat(32));
+        let script = format!("5121{}21{}52ae", key1, key2);
+
+        match ScriptType::from_hex(&script) {
+            ScriptType::Multisig { required, pubkeys } => {
+                assert_eq!(required, 1);
+                assert_eq!(pubkeys.len(), 2);
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+
+        // n 与公钥数量不符
+        let script = format!("5121{}21{}53ae", key1, key2);
+        assert_eq!(ScriptType::from_hex(&script), ScriptType::NonStandard);
+    }
+
+    #[test]
+    fn test_push_data() {
+        for len in [0usize, 75, 76, 255, 256, 70_000] {
+            let data = vec![0xab; len];
+            let mut script = vec![];
+            push_data(&mut script, &data);
+            assert_eq!(instructions(&script), Some(vec![Instruction::Push(&data)]));
+        }
+        let mut script = vec![];
+        push_data(&mut script, &[0x11; 76]);
+        assert_eq!(&script[..2], &[OP_PUSHDATA1, 76]);
+    }
+
+    #[test]
+    fn test_script_pubkey() {
+        let scripts = [
+            "4104678afdb0fe5548271967f1a67130b7105cd6a828e03909a67962e0ea1f61deb649f6bc3f4cef38c4f35504e51ec112de5c384df7ba0b8d578a4c702b6bf11d5fac",
+            "76a91462e907b15cbf27d5425399ebf6f0fb50ebb88f1888ac",
+            "a914748284390f9e263a4b766a75d0633c50426eb87587",
+            "0014751e76e8199196d454941c45d1b3a323f1433bd6",
+            "00201863143c14c5166804bd19203356da136c985678cd4d27a1b8c6329604903262",
+            "5120a60869f0dbcf1dc659c9cecbaf8050135ea9e8cdc487053f1dc6880949dc684c",
+            "51024e73",
+            "5228aabbccddeeff00112233445566778899aabbccddeeff00112233445566778899aabbccddeeff0011",
+            "6a0b68656c6c6f20776f726c64",
+        ];
+        for script in scripts {
+            assert_eq!(hex::encode(ScriptType::from_hex(script).script_pubkey().unwrap()), script);
+        }
+
+        let multisig = format!("512102{}2103{}52ae", "11".repeat(32), "22".repeat(32));
+        assert_eq!(hex::encode(ScriptType::from_hex(&multisig).script_pubkey().unwrap()), multisig);
+
+        // 超出 OP_1..OP_16 范围的参数无法编码
+        let pubkey = vec![0x02; 33];
+        let multisig = |required: u8, count: usize| ScriptType::Multisig {
+            required,
+            pubkeys: vec![pubkey.clone(); count],
+        };
+        assert!(multisig(16, 16).script_pubkey().is_some());
+        assert!(multisig(0, 2).script_pubkey().is_none());
+        assert!(multisig(1, 0).script_pubkey().is_none());
+        assert!(multisig(3, 2).script_pubkey().is_none());
+        assert!(multisig(1, 17).script_pubkey().is_none());
+        assert!(multisig(1, 256).script_pubkey().is_none());
+
+        let witness = |version: u8| ScriptType::WitnessUnknown {
+            version,
+            program: vec![0; 32],
+        };
+        assert!(witness(16).script_pubkey().is_some());
+        assert!(witness(17).script_pubkey().is_none());
+    }
+}
@@ -0,0 +1,483 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Mutex,
+};
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use crate::db::pgsql::Db;
+
+use super::{amount::Amount, network::NetworkParams, response_type::BlockWithTxs, script::ScriptType};
+
+/// 未花费输出
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Utxo {
+    pub txid: String,
+    pub vout: u32,
+    pub value: Amount,
+    pub script_pubkey: String,
+    pub address: Option<String>,
+    pub height: u64,
+    pub block_hash: String,
+    pub coinbase: bool,
+}
+
+impl Utxo {
+    /// txid:vout
+    pub fn outpoint(&self) -> String {
+        format!("{}:{}", self.txid, self.vout)
+    }
+}
+
+/// UTXO 存储. 已花费的输出保留并记录花费所在区块, 以便回滚
+#[async_trait]
+pub trait UtxoStore: Send + Sync {
+    /// 查询未花费输出, 不存在或已花费的不返回
+    async fn get_unspent(&self, outpoints: &[String]) -> Result<HashMap<String, Utxo>, anyhow::Error>;
+    /// 写入区块新建的输出, 并将 spent 中的输出 (outpoint, 花费交易txid) 标记为已花费
+    async fn apply_block(
+        &self,
+        block_hash: &str,
+        created: &[Utxo],
+        spent: &[(String, String)],
+    ) -> Result<(), anyhow::Error>;
+    /// 删除该区块新建的输出, 恢复该区块花费的输出
+    async fn revert_block(&self, block_hash: &str) -> Result<(), anyhow::Error>;
+    /// 地址的全部未花费输出
+    async fn address_utxos(&self, address: &str) -> Result<Vec<Utxo>, anyhow::Error>;
+}
+
+#[async_trait]
+impl<T: UtxoStore> UtxoStore for &T {
+    async fn get_unspent(&self, outpoints: &[String]) -> Result<HashMap<String, Utxo>, anyhow::Error> {
+        (**self).get_unspent(outpoints).await
+    }
+
+    async fn apply_block(
+        &self,
+        block_hash: &str,
+        created: &[Utxo],
+        spent: &[(String, String)],
+    ) -> Result<(), anyhow::Error> {
+        (**self).apply_block(block_hash, created, spent).await
+    }
+
+    async fn revert_block(&self, block_hash: &str) -> Result<(), anyhow::Error> {
+        (**self).revert_block(block_hash).await
+    }
+
+    async fn address_utxos(&self, address: &str) -> Result<Vec<Utxo>, anyhow::Error> {
+        (**self).address_utxos(address).await
+    }
+}
+
+#[derive(Debug, Clone)]
+struct UtxoRecord {
+    utxo: Utxo,
+    // (花费交易txid, 花费所在区块)
+    spent: Option<(String, String)>,
+}
+
+/// 内存存储, 用于测试
+#[derive(Debug, Default)]
+pub struct MemoryUtxoStore {
+    outputs: Mutex<HashMap<String, UtxoRecord>>,
+}
+
+#[async_trait]
+impl UtxoStore for MemoryUtxoStore {
+    async fn get_unspent(&self, outpoints: &[String]) -> Result<HashMap<String, Utxo>, anyhow::Error> {
+        let outputs = self.outputs.lock().unwrap();
+
+        Ok(outpoints
+            .iter()
+            .filter_map(|outpoint| outputs.get(outpoint))
+            .filter(|record| record.spent.is_none())
+            .map(|record| (record.utxo.outpoint(), record.utxo.clone()))
+            .collect())
+    }
+
+    async fn apply_block(
+        &self,
+        block_hash: &str,
+        created: &[Utxo],
+        spent: &[(String, String)],
+    ) -> Result<(), anyhow::Error> {
+        let mut outputs = self.outputs.lock().unwrap();
+
+        for utxo in created {
+            outputs.entry(utxo.outpoint()).or_insert(UtxoRecord {
+                utxo: utxo.clone(),
+                spent: None,
+            });
+        }
+        for (outpoint, spent_txid) in spent {
+            if let Some(record) = outputs.get_mut(outpoint) {
+                record.spent = Some((spent_txid.clone(), block_hash.to_owned()));
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn revert_block(&self, block_hash: &str) -> Result<(), anyhow::Error> {
+        let mut outputs = self.outputs.lock().unwrap();
+
+        outputs.retain(|_, record| record.utxo.block_hash != block_hash);
+        for record in outputs.values_mut() {
+            if record.spent.as_ref().is_some_and(|(_, spent_block)| spent_block == block_hash) {
+                record.spent = None;
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn address_utxos(&self, address: &str) -> Result<Vec<Utxo>, anyhow::Error> {
+        let outputs = self.outputs.lock().unwrap();
+
+        let mut utxos = outputs
+            .values()
+            .filter(|record| record.spent.is_none() && record.utxo.address.as_deref() == Some(address))
+            .map(|record| record.utxo.clone())
+            .collect::<Vec<_>>();
+        utxos.sort_by(|a, b| (a.height, &a.txid, a.vout).cmp(&(b.height, &b.txid, b.vout)));
+
+        Ok(utxos)
+    }
+}
+
+/// Postgres 存储, 表 btc_utxo
+#[derive(Clone)]
+pub struct PgUtxoStore {
+    pub db: Db,
+}
+
+type UtxoRow = (String, i32, i64, String, Option<String>, i64, String, bool);
+
+const UTXO_COLUMNS: &str = "txid, vout, value, script_pubkey, address, height, block_hash, coinbase";
+
+fn utxo_from_row(row: UtxoRow) -> Utxo {
+    let (txid, vout, value, script_pubkey, address, height, block_hash, coinbase) = row;
+    Utxo {
+        txid,
+        vout: vout as u32,
+        value: Amount::from_sat(value),
+        script_pubkey,
+        address,
+        height: height as u64,
+        block_hash,
+        coinbase,
+    }
+}
+
+impl PgUtxoStore {
+    pub fn new(db: Db) -> Self {
+        PgUtxoStore { db }
+    }
+
+    pub async fn init_table(&self) -> Result<(), anyhow::Error> {
+        let sqls = [
+            "CREATE TABLE IF NOT EXISTS btc_utxo (
+                outpoint TEXT PRIMARY KEY,
+                txid TEXT NOT NULL,
+                vout INT NOT NULL,
+                value BIGINT NOT NULL,
+                script_pubkey TEXT NOT NULL,
+                address TEXT,
+                height BIGINT NOT NULL,
+                block_hash TEXT NOT NULL,
+                coinbase BOOLEAN NOT NULL,
+                spent_txid TEXT,
+                spent_block TEXT
+            )",
+            "CREATE INDEX IF NOT EXISTS btc_utxo_address_idx ON btc_utxo (address) WHERE spent_txid IS NULL",
+            "CREATE INDEX IF NOT EXISTS btc_utxo_block_hash_idx ON btc_utxo (block_hash)",
+            "CREATE INDEX IF NOT EXISTS btc_utxo_spent_block_idx ON btc_utxo (spent_block)",
+        ];
+        for sql in sqls {
+            self.db.execute_sql(sql.to_owned(), vec![]).await?;
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl UtxoStore for PgUtxoStore {
+    async fn get_unspent(&self, outpoints: &[String]) -> Result<HashMap<String, Utxo>, anyhow::Error> {
+        let sql = format!(
+            "SELECT {} FROM btc_utxo WHERE outpoint = ANY($1) AND spent_txid IS NULL",
+            UTXO_COLUMNS
+        );
+        let rows = sqlx::query_as::<_, UtxoRow>(&sql)
+            .bind(outpoints)
+            .fetch_all(&self.db.pool)
+            .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(utxo_from_row)
+            .map(|utxo| (utxo.outpoint(), utxo))
+            .collect())
+    }
+
+    async fn apply_block(
+        &self,
+        block_hash: &str,
+        created: &[Utxo],
+        spent: &[(String, String)],
+    ) -> Result<(), anyhow::Error> {
+        let mut tx = self.db.pool.begin().await?;
+
+        let sql = "INSERT INTO btc_utxo (outpoint, txid, vout, value, script_pubkey, address, height, block_hash, coinbase)
+            SELECT * FROM UNNEST($1::TEXT[], $2::TEXT[], $3::INT[], $4::BIGINT[], $5::TEXT[], $6::TEXT[], $7::BIGINT[], $8::TEXT[], $9::BOOL[])
+            ON CONFLICT (outpoint) DO NOTHING";
+        sqlx::query(sql)
+            .bind(created.iter().map(|utxo| utxo.outpoint()).collect::<Vec<_>>())
+            .bind(created.iter().map(|utxo| utxo.txid.clone()).collect::<Vec<_>>())
+            .bind(created.iter().map(|utxo| utxo.vout as i32).collect::<Vec<_>>())
+            .bind(created.iter().map(|utxo| utxo.value.to_sat()).collect::<Vec<_>>())
+            .bind(created.iter().map(|utxo| utxo.script_pubkey.clone()).collect::<Vec<_>>())
+            .bind(created.iter().map(|utxo| utxo.address.clone()).collect::<Vec<_>>())
+            .bind(created.iter().map(|utxo| utxo.height as i64).collect::<Vec<_>>())
+            .bind(created.iter().map(|utxo| utxo.block_hash.clone()).collect::<Vec<_>>())
+            .bind(created.iter().map(|utxo| utxo.coinbase).collect::<Vec<_>>())
+            .execute(&mut *tx)
+            .await?;
+
+        let sql = "UPDATE btc_utxo AS u SET spent_txid = s.spent_txid, spent_block = $3
+            FROM UNNEST($1::TEXT[], $2::TEXT[]) AS s(outpoint, spent_txid)
+            WHERE u.outpoint = s.outpoint";
+        sqlx::query(sql)
+            .bind(spent.iter().map(|(outpoint, _)| outpoint.clone()).collect::<Vec<_>>())
+            .bind(spent.iter().map(|(_, txid)| txid.clone()).collect::<Vec<_>>())
+            .bind(block_hash)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn revert_block(&self, block_hash: &str) -> Result<(), anyhow::Error> {
+        let mut tx = self.db.pool.begin().await?;
+
+        sqlx::query("UPDATE btc_utxo SET spent_txid = NULL, spent_block = NULL WHERE spent_block = $1")
+            .bind(block_hash)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("DELETE FROM btc_utxo WHERE block_hash = $1")
+            .bind(block_hash)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn address_utxos(&self, address: &str) -> Result<Vec<Utxo>, anyhow::Error> {
+        let sql = format!(
+            "SELECT {} FROM btc_utxo WHERE address = $1 AND spent_txid IS NULL ORDER BY height, txid, vout",
+            UTXO_COLUMNS
+        );
+        let rows = self.db.clone().select_all::<UtxoRow>(sql, vec![address.to_owned()]).await?;
+
+        Ok(rows.into_iter().map(utxo_from_row).collect())
+    }
+}
+
+/// 单个区块对 UTXO 集的变更
+#[derive(Debug, Clone, Default)]
+pub struct AppliedBlock {
+    pub created: Vec<Utxo>,
+    /// 被花费的输出, 已解析金额和地址
+    pub spent: Vec<Utxo>,
+    /// 存储中找不到的输入 (如从中间高度开始追踪)
+    pub unresolved: Vec<String>,
+}
+
+/// UTXO 追踪: 按区块顺序应用 (分叉时按高度降序回滚), 维护每个地址的未花费输出
+pub struct UtxoTracker<S: UtxoStore> {
+    store: S,
+    params: NetworkParams,
+}
+
+impl<S: UtxoStore> UtxoTracker<S> {
+    pub fn new(store: S, params: NetworkParams) -> Self {
+        UtxoTracker { store, params }
+    }
+
+    pub fn store(&self) -> &S {
+        &self.store
+    }
+
+    /// 应用 getblock verbosity=2/3 的区块
+    pub async fn apply_block(&self, block: &BlockWithTxs) -> Result<AppliedBlock, anyhow::Error> {
+        let mut applied = AppliedBlock::default();
+
+        for tx in &block.tx {
+            let coinbase = tx.vin.iter().any(|input| input.coinbase.is_some());
+
+            for output in &tx.vout {
+                let script_type = output.script_pub_key.script_type();
+                // OP_RETURN 不可花费, 不进入 UTXO 集
+                if matches!(script_type, ScriptType::OpReturn { .. }) {
+                    continue;
+                }
+
+                applied.created.push(Utxo {
+                    txid: tx.txid.clone(),
+                    vout: output.n,
+                    value: output.value,
+                    script_pubkey: output.script_pub_key.hex.clone(),
+                    address: script_type.address(&self.params),
+                    height: block.height as u64,
+                    block_hash: block.hash.clone(),
+                    coinbase,
+                });
+            }
+        }
+
+        // 输入可能花费同一区块内的输出, 先在本区块内查找
+        let spends = block
+            .tx
+            .iter()
+            .flat_map(|tx| tx.vin.iter().map(move |input| (tx, input)))
+            .filter_map(|(tx, input)| match (&input.txid, input.vout) {
+                (Some(txid), Some(vout)) => Some((format!("{}:{}", txid, vout), tx.txid.clone())),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        let created = applied
+            .created
+            .iter()
+            .map(|utxo| (utxo.outpoint(), utxo.clone()))
+            .collect::<HashMap<_, _>>();
+        let lookups = spends
+            .iter()
+            .filter(|(outpoint, _)| !created.contains_key(outpoint))
+            .map(|(outpoint, _)| outpoint.clone())
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect::<Vec<_>>();
+        let stored = self.store.get_unspent(&lookups).await?;
+
+        for (outpoint, _) in &spends {
+            match created.get(outpoint).or_else(|| stored.get(outpoint)) {
+                Some(utxo) => applied.spent.push(utxo.clone()),
+                None => applied.unresolved.push(outpoint.clone()),
+            }
+        }
+
+        if !applied.unresolved.is_empty() {
+            log::warn!(
+                "[UtxoTracker] block {} {} unresolved inputs: {}",
+                block.height,
+                block.hash,
+                applied.unresolved.len()
+            );
+        }
+
+        self.store.apply_block(&block.hash, &applied.created, &spends).await?;
+
+        Ok(applied)
+    }
+
+    /// 回滚区块, 需按高度降序调用 (如 ScanEvent::Rollback 的 removed)
+    pub async fn revert_block(&self, block_hash: &str) -> Result<(), anyhow::Error> {
+        self.store.revert_block(block_hash).await
+    }
+
+    pub async fn utxos(&self, address: &str) -> Result<Vec<Utxo>, anyhow::Error> {
+        self.store.address_utxos(address).await
+    }
+
+    /// 地址余额
+    pub async fn balance(&self, address: &str) -> Result<Amount, anyhow::Error> {
+        Amount::checked_sum(self.utxos(address).await?.iter().map(|utxo| utxo.value))
+            .ok_or_else(|| anyhow::anyhow!("balance overflow for {}", address))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::*;
+    use crate::btc_client::network::Network;
+
+    // P2PKH: 1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa
+    const SCRIPT_A: &str = "76a91462e907b15cbf27d5425399ebf6f0fb50ebb88f1888ac";
+    const ADDR_A: &str = "1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa";
+    // P2WPKH: bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4
+    const SCRIPT_B: &str = "0014751e76e8199196d454941c45d1b3a323f1433bd6";
+    const ADDR_B: &str = "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4";
+
+    fn tx(txid: &str, inputs: &[(&str, u32)], outputs: &[(f64, &str)]) -> Value {
+        let vin = if inputs.is_empty() {
+            vec![json!({"coinbase": "00", "sequence": 0})]
+        } else {
+            inputs
+                .iter()
+                .map(|(txid, vout)| json!({"txid": txid, "vout": vout, "sequence": 0}))
+                .collect()
+        };
+        let vout = outputs
+            .iter()
+            .enumerate()
+            .map(|(n, (value, script))| {
+                json!({"value": value, "n": n, "scriptPubKey": {"asm": "", "desc": "", "hex": script}})
+            })
+            .collect::<Vec<_>>();
+
+        json!({
+            "txid": txid, "hash": txid, "version": 2, "size": 0, "vsize": 0, "weight": 0,
+            "locktime": 0, "vin": vin, "vout": vout, "hex": ""
+        })
+    }
+
+    fn block(hash: &str, height: u64, txs: Vec<Value>) -> BlockWithTxs {
+        serde_json::from_value(json!({
+            "hash": hash, "confirmations": 1, "height": height, "version": 1, "merkleroot": "",
+            "time": 0, "nonce": 0, "bits": "", "difficulty": 1.0, "chainwork": "",
+            "nTx": txs.len(), "size": 0, "weight": 0, "tx": txs
+        }))
+        .unwrap()
+    }
+
+    #[async_std::test]
+    async fn test_apply_and_revert() {
+        let store = MemoryUtxoStore::default();
+        let tracker = UtxoTracker::new(&store, Network::Bitcoin.params());
+
+        let block1 = block("b1", 1, vec![tx("c1", &[], &[(50.0, SCRIPT_A)])]);
+        tracker.apply_block(&block1).await.unwrap();
+        assert_eq!(tracker.balance(ADDR_A).await.unwrap(), Amount::from_sat(50 * 100_000_000));
+
+        // t1 花费 c1, t2 在同一区块内花费 t1 的找零
+        let block2 = block(
+            "b2",
+            2,
+            vec![
+                tx("c2", &[], &[(50.0, SCRIPT_B), (0.0, "6a00")]),
+                tx("t1", &[("c1", 0)], &[(10.1, SCRIPT_B), (39.8, SCRIPT_A)]),
+                tx("t2", &[("t1", 1)], &[(39.79999999, SCRIPT_B)]),
+                tx("t3", &[("unknown", 0)], &[]),
+            ],
+        );
+        let applied = tracker.apply_block(&block2).await.unwrap();
+        assert_eq!(applied.created.len(), 4);
+        assert_eq!(applied.spent.len(), 2);
+        assert_eq!(applied.unresolved, vec!["unknown:0".to_string()]);
+
+        assert_eq!(tracker.balance(ADDR_A).await.unwrap(), Amount::from_sat(0));
+        assert_eq!(tracker.balance(ADDR_B).await.unwrap(), Amount::from_sat(5_000_000_000 + 1_010_000_000 + 3_979_999_999));
+        assert_eq!(tracker.utxos(ADDR_B).await.unwrap().len(), 3);
+
+        tracker.revert_block("b2").await.unwrap();
+        assert_eq!(tracker.balance(ADDR_A).await.unwrap(), Amount::from_sat(50 * 100_000_000));
+        assert_eq!(tracker.balance(ADDR_B).await.unwrap(), Amount::from_sat(0));
+    }
+}
@@ -0,0 +1,305 @@
+use serde::{Serialize, Serializer};
+use serde_json::{json, Value};
+
+use super::{error::BtcRpcError, response_type::*, BtcClient};
+
+/// createwallet 参数
+#[derive(Debug, Clone, Serialize)]
+pub struct CreateWalletOptions {
+    pub disable_private_keys: bool,
+    pub blank: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub passphrase: Option<String>,
+    pub avoid_reuse: bool,
+    pub descriptors: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub load_on_startup: Option<bool>,
+}
+
+impl CreateWalletOptions {
+    /// 无私钥的空白描述符钱包, 之后通过 importdescriptors 导入 xpub 描述符
+    pub fn watch_only() -> Self {
+        CreateWalletOptions {
+            disable_private_keys: true,
+            blank: true,
+            passphrase: None,
+            avoid_reuse: false,
+            descriptors: true,
+            load_on_startup: Some(true),
+        }
+    }
+}
+
+/// importdescriptors 的重新扫描起点
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportTimestamp {
+    /// 不扫描历史
+    Now,
+    /// 自该 unix 时间起扫描, 0 为全部历史
+    Time(u64),
+}
+
+impl Serialize for ImportTimestamp {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            ImportTimestamp::Now => serializer.serialize_str("now"),
+            ImportTimestamp::Time(time) => serializer.serialize_u64(*time),
+        }
+    }
+}
+
+/// importdescriptors 请求项
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportDescriptor {
+    /// 需带校验和, 可先调用 getdescriptorinfo 获取
+    pub desc: String,
+    pub timestamp: ImportTimestamp,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub active: Option<bool>,
+    /// 派生范围 [start, end]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub range: Option<(u32, u32)>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_index: Option<u32>,
+    /// 找零描述符
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub internal: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+}
+
+impl ImportDescriptor {
+    pub fn new(desc: &str, timestamp: ImportTimestamp) -> Self {
+        ImportDescriptor {
+            desc: desc.to_string(),
+            timestamp,
+            active: None,
+            range: None,
+            next_index: None,
+            internal: None,
+            label: None,
+        }
+    }
+
+    /// 作为 getnewaddress 使用的活跃描述符
+    pub fn active(mut self, internal: bool, range: (u32, u32)) -> Self {
+        self.active = Some(true);
+        self.internal = Some(internal);
+        self.range = Some(range);
+        self
+    }
+}
+
+/// getnewaddress 的地址类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum WalletAddressType {
+    Legacy,
+    P2shSegwit,
+    Bech32,
+    Bech32m,
+}
+
+// 命名参数
+fn create_wallet_params(name: &str, options: &CreateWalletOptions) -> Value {
+    let mut params = json!(options);
+    params["wallet_name"] = json!(name);
+    params
+}
+
+fn list_unspent_params(minconf: u32, maxconf: Option<u32>, addresses: Option<&[String]>) -> Value {
+    json!([minconf, maxconf.unwrap_or(9_999_999), addresses.unwrap_or_default()])
+}
+
+// 包含观察地址
+fn list_transactions_params(label: Option<&str>, count: usize, skip: usize) -> Value {
+    json!([label.unwrap_or("*"), count, skip, true])
+}
+
+// 包含观察地址及因分叉失效的交易
+fn list_since_block_params(blockhash: Option<&str>, target_confirmations: u32) -> Value {
+    json!([blockhash.unwrap_or(""), target_confirmations.max(1), true, true])
+}
+
+fn get_new_address_params(label: Option<&str>, address_type: Option<WalletAddressType>) -> Value {
+    match address_type {
+        Some(address_type) => json!([label.unwrap_or(""), address_type]),
+        None => json!([label.unwrap_or("")]),
+    }
+}
+
+// 钱包相关接口. createwallet/loadwallet/getdescriptorinfo 为节点级接口,
+// 其余需通过 wallet(name) 得到的客户端调用
+impl BtcClient {
+    /// 指向 /wallet/<name> 的客户端, 节点加载多个钱包时钱包接口必须指定钱包
+    pub fn wallet(&self, name: &str) -> BtcClient {
+        BtcClient {
+            wallet_path: format!("/wallet/{}", urlencoding::encode(name)),
+            ..self.clone()
+        }
+    }
+
+    pub async fn create_wallet(
+        &self,
+        name: &str,
+        options: &CreateWalletOptions,
+    ) -> Result<JsonResponse<LoadWalletResult>, anyhow::Error> {
+        self.call("createwallet", create_wallet_params(name, options)).await
+    }
+
+    pub async fn load_wallet(&self, name: &str) -> Result<JsonResponse<LoadWalletResult>, anyhow::Error> {
+        self.call("loadwallet", json!([name])).await
+    }
+
+    pub async fn import_descriptors(
+        &self,
+        requests: &[ImportDescriptor],
+    ) -> Result<JsonResponse<Vec<ImportDescriptorResult>>, anyhow::Error> {
+        self.call("importdescriptors", json!([requests])).await
+    }
+
+    pub async fn list_unspent(
+        &self,
+        minconf: u32,
+        maxconf: Option<u32>,
+        addresses: Option<&[String]>,
+    ) -> Result<JsonResponse<Vec<WalletUtxo>>, anyhow::Error> {
+        self.call("listunspent", list_unspent_params(minconf, maxconf, addresses)).await
+    }
+
+    /// 最近的 count 条, 跳过最新的 skip 条, 按时间升序返回
+    pub async fn list_transactions(
+        &self,
+        label: Option<&str>,
+        count: usize,
+        skip: usize,
+    ) -> Result<JsonResponse<Vec<WalletTransaction>>, anyhow::Error> {
+        self.call("listtransactions", list_transactions_params(label, count, skip)).await
+    }
+
+    /// blockhash 为 None 时返回全部交易. 下次以返回的 lastblock 继续,
+    /// target_confirmations > 1 时 lastblock 会回退相应深度, 便于重新检查未充分确认的交易
+    pub async fn list_since_block(
+        &self,
+        blockhash: Option<&str>,
+        target_confirmations: u32,
+    ) -> Result<JsonResponse<SinceBlock>, anyhow::Error> {
+        self.call("listsinceblock", list_since_block_params(blockhash, target_confirmations)).await
+    }
+
+    pub async fn get_balances(&self) -> Result<JsonResponse<WalletBalances>, anyhow::Error> {
+        self.call("getbalances", json!([])).await
+    }
+
+    pub async fn get_new_address(
+        &self,
+        label: Option<&str>,
+        address_type: Option<WalletAddressType>,
+    ) -> Result<JsonResponse<String>, anyhow::Error> {
+        self.call("getnewaddress", get_new_address_params(label, address_type)).await
+    }
+
+    pub async fn get_descriptor_info(&self, descriptor: &str) -> Result<JsonResponse<DescriptorInfo>, anyhow::Error> {
+        self.call("getdescriptorinfo", json!([descriptor])).await
+    }
+}
+
+impl BtcClient {
+    pub async fn create_wallet_result(
+        &self,
+        name: &str,
+        options: &CreateWalletOptions,
+    ) -> Result<LoadWalletResult, BtcRpcError> {
+        self.call_result("createwallet", create_wallet_params(name, options)).await
+    }
+
+    pub async fn load_wallet_result(&self, name: &str) -> Result<LoadWalletResult, BtcRpcError> {
+        self.call_result("loadwallet", json!([name])).await
+    }
+
+    pub async fn import_descriptors_result(
+        &self,
+        requests: &[ImportDescriptor],
+    ) -> Result<Vec<ImportDescriptorResult>, BtcRpcError> {
+        self.call_result("importdescriptors", json!([requests])).await
+    }
+
+    pub async fn list_unspent_result(
+        &self,
+        minconf: u32,
+        maxconf: Option<u32>,
+        addresses: Option<&[String]>,
+    ) -> Result<Vec<WalletUtxo>, BtcRpcError> {
+        self.call_result("listunspent", list_unspent_params(minconf, maxconf, addresses)).await
+    }
+
+    pub async fn list_transactions_result(
+        &self,
+        label: Option<&str>,
+        count: usize,
+        skip: usize,
+    ) -> Result<Vec<WalletTransaction>, BtcRpcError> {
+        self.call_result("listtransactions", list_transactions_params(label, count, skip)).await
+    }
+
+    pub async fn list_since_block_result(
+        &self,
+        blockhash: Option<&str>,
+        target_confirmations: u32,
+    ) -> Result<SinceBlock, BtcRpcError> {
+        self.call_result("listsinceblock", list_since_block_params(blockhash, target_confirmations)).await
+    }
+
+    pub async fn get_balances_result(&self) -> Result<WalletBalances, BtcRpcError> {
+        self.call_result("getbalances", json!([])).await
+    }
+
+    pub async fn get_new_address_result(
+        &self,
+        label: Option<&str>,
+        address_type: Option<WalletAddressType>,
+    ) -> Result<String, BtcRpcError> {
+        self.call_result("getnewaddress", get_new_address_params(label, address_type)).await
+    }
+
+    pub async fn get_descriptor_info_result(&self, descriptor: &str) -> Result<DescriptorInfo, BtcRpcError> {
+        self.call_result("getdescriptorinfo", json!([descriptor])).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn test_wallet_url() {
+        let client = BtcClient::new("http://127.0.0.1:8332/", "", "", Duration::from_secs(1)).unwrap();
+        let wallet = client.wallet("deposit 1");
+        assert_eq!(wallet.url(), "http://127.0.0.1:8332/wallet/deposit%201");
+        assert_eq!(wallet.wallet("b").url(), "http://127.0.0.1:8332/wallet/b");
+    }
+
+    #[test]
+    fn test_params() {
+        assert_eq!(
+            create_wallet_params("w", &CreateWalletOptions::watch_only()),
+            json!({"wallet_name": "w", "disable_private_keys": true, "blank": true, "avoid_reuse": false,
+                   "descriptors": true, "load_on_startup": true})
+        );
+
+        let request = ImportDescriptor::new("wpkh(xpub/0/*)#abcd", ImportTimestamp::Now).active(false, (0, 999));
+        assert_eq!(
+            json!([[request]]),
+            json!([[{"desc": "wpkh(xpub/0/*)#abcd", "timestamp": "now", "active": true, "range": [0, 999], "internal": false}]])
+        );
+        assert_eq!(
+            json!([ImportDescriptor::new("addr(x)", ImportTimestamp::Time(0))])[0]["timestamp"],
+            json!(0)
+        );
+
+        assert_eq!(get_new_address_params(None, Some(WalletAddressType::P2shSegwit)), json!(["", "p2sh-segwit"]));
+        assert_eq!(list_since_block_params(None, 0), json!(["", 1, true, true]));
+    }
+}
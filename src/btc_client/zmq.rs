@@ -0,0 +1,697 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt,
+    str::FromStr,
+    time::Duration,
+};
+
+use anyhow::{anyhow, bail};
+use async_std::net::TcpStream;
+use futures::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, Stream};
+
+use super::{
+    consensus::{RawBlock, RawTransaction},
+    scanner::BlockSource,
+    BtcClient,
+};
+
+/// 节点 -zmqpub<topic> 的主题
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ZmqTopic {
+    HashBlock,
+    RawBlock,
+    HashTx,
+    RawTx,
+    Sequence,
+}
+
+impl ZmqTopic {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ZmqTopic::HashBlock => "hashblock",
+            ZmqTopic::RawBlock => "rawblock",
+            ZmqTopic::HashTx => "hashtx",
+            ZmqTopic::RawTx => "rawtx",
+            ZmqTopic::Sequence => "sequence",
+        }
+    }
+}
+
+impl fmt::Display for ZmqTopic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ZmqTopic {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "hashblock" => Ok(ZmqTopic::HashBlock),
+            "rawblock" => Ok(ZmqTopic::RawBlock),
+            "hashtx" => Ok(ZmqTopic::HashTx),
+            "rawtx" => Ok(ZmqTopic::RawTx),
+            "sequence" => Ok(ZmqTopic::Sequence),
+            _ => bail!("unknown zmq topic: {}", s),
+        }
+    }
+}
+
+/// sequence 主题的消息
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SequenceEvent {
+    BlockConnected { hash: String },
+    BlockDisconnected { hash: String },
+    /// 交易进入交易池, mempool_sequence 为节点交易池序号
+    TxAdded { txid: String, mempool_sequence: u64 },
+    /// 交易离开交易池 (非打包原因, 如替换/过期)
+    TxRemoved { txid: String, mempool_sequence: u64 },
+}
+
+#[derive(Debug, Clone)]
+pub enum ZmqEvent {
+    HashBlock { hash: String, sequence: u32 },
+    RawBlock { block: Box<RawBlock>, sequence: u32 },
+    HashTx { txid: String, sequence: u32 },
+    RawTx { tx: Box<RawTransaction>, sequence: u32 },
+    Sequence { event: SequenceEvent, sequence: u32 },
+    /// 消息序号不连续, 期间的消息已丢失, 调用方需自行补齐 (如重新同步交易池)
+    Gap { topic: ZmqTopic, expected: u32, received: u32 },
+    /// 连接建立 (首次或重连)
+    Connected,
+    /// 连接断开, 之后改为通过 BlockSource 轮询新区块, 直到重连成功
+    Disconnected { error: String },
+    /// 断线期间轮询发现的新区块
+    PolledBlock { height: u64, hash: String },
+}
+
+#[derive(Debug, Clone)]
+pub struct ZmqConfig {
+    /// 如 tcp://127.0.0.1:28332
+    pub endpoint: String,
+    pub topics: Vec<ZmqTopic>,
+    pub connect_timeout: Duration,
+    /// 断线时的重连及轮询间隔
+    pub reconnect_interval: Duration,
+    /// 超过该时间没有消息时通过 BlockSource 检查链高度, 连续两次检查之间高度变化
+    /// (漏掉了区块通知) 时视为连接失效 (如节点重启、NAT 断开的半开连接). 应远大于出块间隔,
+    /// 没有 BlockSource 时不做检查, 只依赖 TCP 错误和序号缺口
+    pub idle_timeout: Duration,
+}
+
+impl Default for ZmqConfig {
+    fn default() -> Self {
+        ZmqConfig {
+            endpoint: "tcp://127.0.0.1:28332".to_string(),
+            topics: vec![ZmqTopic::HashBlock],
+            connect_timeout: Duration::from_secs(5),
+            reconnect_interval: Duration::from_secs(10),
+            idle_timeout: Duration::from_secs(30 * 60),
+        }
+    }
+}
+
+/// 比特币节点 ZMQ 订阅. 内置 ZMTP 3.0 (NULL 机制) SUB 客户端, 不依赖 libzmq.
+/// 各主题按消息序号检测丢失; 断线后自动重连, 期间通过 BlockSource 轮询新区块.
+pub struct ZmqSubscriber<C: BlockSource = BtcClient> {
+    config: ZmqConfig,
+    fallback: Option<C>,
+    conn: Option<ZmtpStream<TcpStream>>,
+    sequences: HashMap<ZmqTopic, u32>,
+    pending: VecDeque<ZmqEvent>,
+    poll_height: Option<u64>,
+    // 上次空闲检查时的链高度, 收到消息后清空
+    idle_tip: Option<u64>,
+}
+
+impl ZmqSubscriber<BtcClient> {
+    /// 不轮询, 断线期间只重连
+    pub fn new(config: ZmqConfig) -> Self {
+        ZmqSubscriber::build(config, None)
+    }
+}
+
+impl<C: BlockSource> ZmqSubscriber<C> {
+    pub fn with_fallback(config: ZmqConfig, fallback: C) -> Self {
+        ZmqSubscriber::build(config, Some(fallback))
+    }
+
+    fn build(config: ZmqConfig, fallback: Option<C>) -> Self {
+        ZmqSubscriber {
+            config,
+            fallback,
+            conn: None,
+            sequences: HashMap::new(),
+            pending: VecDeque::new(),
+            poll_height: None,
+            idle_tip: None,
+        }
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.conn.is_some()
+    }
+
+    /// 等待下一个事件. 消息格式错误返回 Err, 之后仍可继续调用
+    pub async fn next(&mut self) -> Result<ZmqEvent, anyhow::Error> {
+        loop {
+            if let Some(event) = self.pending.pop_front() {
+                return Ok(event);
+            }
+
+            let Some(conn) = &mut self.conn else {
+                if self.connect().await {
+                    return Ok(ZmqEvent::Connected);
+                }
+                if let Err(err) = self.poll().await {
+                    async_std::task::sleep(self.config.reconnect_interval).await;
+                    return Err(err);
+                }
+                if self.pending.is_empty() {
+                    async_std::task::sleep(self.config.reconnect_interval).await;
+                }
+                continue;
+            };
+
+            match conn.read_message(self.config.idle_timeout).await {
+                Ok(Some(frames)) => {
+                    self.idle_tip = None;
+                    self.handle_message(frames)?
+                }
+                Ok(None) => {
+                    // 从上次检查的高度开始轮询, 补上漏掉的区块
+                    let last_tip = self.idle_tip;
+                    if self.is_stale().await {
+                        let error = format!("no message for {:?}", self.config.idle_timeout);
+                        return Ok(self.disconnect(error, last_tip));
+                    }
+                }
+                Err(err) => return Ok(self.disconnect(err.to_string(), None)),
+            }
+        }
+    }
+
+    fn disconnect(&mut self, error: String, poll_height: Option<u64>) -> ZmqEvent {
+        log::warn!("[ZmqSubscriber] {} disconnected: {}", self.config.endpoint, error);
+        self.conn = None;
+        self.poll_height = poll_height;
+        self.idle_tip = None;
+        ZmqEvent::Disconnected { error }
+    }
+
+    // 空闲超时后检查连接是否失效. 没有 BlockSource 时无法判断, 区块间隔内没有消息是正常的, 保持连接
+    async fn is_stale(&mut self) -> bool {
+        let Some(source) = &self.fallback else {
+            return false;
+        };
+
+        match source.tip_height().await {
+            Ok(tip) => self.idle_tip.replace(tip).is_some_and(|last_tip| last_tip != tip),
+            Err(err) => {
+                log::warn!("[ZmqSubscriber] idle check tip failed: {}", err);
+                false
+            }
+        }
+    }
+
+    /// 转为事件流, 出错后流继续
+    pub fn into_stream(self) -> impl Stream<Item = Result<ZmqEvent, anyhow::Error>> {
+        futures::stream::unfold(self, |mut subscriber| async move {
+            let event = subscriber.next().await;
+            Some((event, subscriber))
+        })
+    }
+
+    async fn connect(&mut self) -> bool {
+        let addr = self.config.endpoint.trim_start_matches("tcp://");
+        let connect = async {
+            let stream = TcpStream::connect(addr).await?;
+            stream.set_nodelay(true)?;
+            ZmtpStream::handshake(stream, &self.config.topics).await
+        };
+
+        match async_std::future::timeout(self.config.connect_timeout, connect).await {
+            Ok(Ok(conn)) => {
+                log::info!("[ZmqSubscriber] connected to {}", self.config.endpoint);
+                self.conn = Some(conn);
+                true
+            }
+            Ok(Err(err)) => {
+                log::warn!("[ZmqSubscriber] connect {} failed: {}", self.config.endpoint, err);
+                false
+            }
+            Err(_) => {
+                log::warn!("[ZmqSubscriber] connect {} timeout", self.config.endpoint);
+                false
+            }
+        }
+    }
+
+    // 断线期间的轮询, 首次轮询只记录当前高度
+    async fn poll(&mut self) -> Result<(), anyhow::Error> {
+        let Some(source) = &self.fallback else {
+            return Ok(());
+        };
+
+        let tip = source.tip_height().await?;
+        let from = match self.poll_height {
+            Some(height) => height + 1,
+            None => tip + 1,
+        };
+        for height in from..=tip {
+            let hash = source.block_hash(height).await?;
+            self.pending.push_back(ZmqEvent::PolledBlock { height, hash });
+            self.poll_height = Some(height);
+        }
+        if self.poll_height.is_none() {
+            self.poll_height = Some(tip);
+        }
+        Ok(())
+    }
+
+    // 节点的消息为 [topic, body, 4 字节小端序号]
+    fn handle_message(&mut self, frames: Vec<Vec<u8>>) -> Result<(), anyhow::Error> {
+        let [topic, body, sequence]: [Vec<u8>; 3] = frames
+            .try_into()
+            .map_err(|frames: Vec<Vec<u8>>| anyhow!("expected 3 frames, got {}", frames.len()))?;
+
+        let topic = String::from_utf8_lossy(&topic).parse::<ZmqTopic>()?;
+        let sequence = u32::from_le_bytes(
+            sequence
+                .as_slice()
+                .try_into()
+                .map_err(|_| anyhow!("invalid {} sequence length {}", topic, sequence.len()))?,
+        );
+
+        if let Some(last) = self.sequences.insert(topic, sequence) {
+            let expected = last.wrapping_add(1);
+            if sequence != expected {
+                log::warn!("[ZmqSubscriber] {} gap: expected {} received {}", topic, expected, sequence);
+                self.pending.push_back(ZmqEvent::Gap { topic, expected, received: sequence });
+            }
+        }
+
+        let event = match topic {
+            ZmqTopic::HashBlock => ZmqEvent::HashBlock { hash: hash_hex(&body)?, sequence },
+            ZmqTopic::HashTx => ZmqEvent::HashTx { txid: hash_hex(&body)?, sequence },
+            ZmqTopic::RawBlock => ZmqEvent::RawBlock { block: Box::new(RawBlock::from_bytes(&body)?), sequence },
+            ZmqTopic::RawTx => ZmqEvent::RawTx { tx: Box::new(RawTransaction::from_bytes(&body)?), sequence },
+            ZmqTopic::Sequence => ZmqEvent::Sequence { event: parse_sequence(&body)?, sequence },
+        };
+        self.pending.push_back(event);
+        Ok(())
+    }
+}
+
+// hashblock/hashtx/sequence 中的哈希已是显示顺序
+fn hash_hex(body: &[u8]) -> Result<String, anyhow::Error> {
+    if body.len() != 32 {
+        bail!("invalid hash length {}", body.len());
+    }
+    Ok(hex::encode(body))
+}
+
+// <32 字节哈希><标签 C/D/A/R>[<8 字节小端交易池序号>]
+fn parse_sequence(body: &[u8]) -> Result<SequenceEvent, anyhow::Error> {
+    if body.len() < 33 {
+        bail!("invalid sequence message length {}", body.len());
+    }
+    let hash = hash_hex(&body[..32])?;
+    let mempool_sequence = || -> Result<u64, anyhow::Error> {
+        let bytes: [u8; 8] = body[33..]
+            .try_into()
+            .map_err(|_| anyhow!("invalid sequence message length {}", body.len()))?;
+        Ok(u64::from_le_bytes(bytes))
+    };
+
+    Ok(match body[32] {
+        b'C' => SequenceEvent::BlockConnected { hash },
+        b'D' => SequenceEvent::BlockDisconnected { hash },
+        b'A' => SequenceEvent::TxAdded { txid: hash, mempool_sequence: mempool_sequence()? },
+        b'R' => SequenceEvent::TxRemoved { txid: hash, mempool_sequence: mempool_sequence()? },
+        label => bail!("unknown sequence label {:?}", label as char),
+    })
+}
+
+const FLAG_MORE: u8 = 0x01;
+const FLAG_LONG: u8 = 0x02;
+const FLAG_COMMAND: u8 = 0x04;
+// 区块最大 4MB, 留足余量
+const MAX_FRAME_SIZE: u64 = 32 * 1024 * 1024;
+
+/// ZMTP 3.0 问候: 签名(10) + 版本(2) + 机制(20) + as-server(1) + 填充(31)
+fn greeting(as_server: bool) -> [u8; 64] {
+    let mut greeting = [0u8; 64];
+    greeting[0] = 0xff;
+    greeting[9] = 0x7f;
+    greeting[10] = 3;
+    greeting[11] = 0;
+    greeting[12..16].copy_from_slice(b"NULL");
+    greeting[32] = as_server as u8;
+    greeting
+}
+
+/// READY 命令, 携带 Socket-Type 属性
+fn ready_command(socket_type: &str) -> Vec<u8> {
+    let mut body = vec![5];
+    body.extend(b"READY");
+    body.push(11);
+    body.extend(b"Socket-Type");
+    body.extend((socket_type.len() as u32).to_be_bytes());
+    body.extend(socket_type.as_bytes());
+    body
+}
+
+/// 解析 READY 命令的属性
+fn parse_ready(body: &[u8]) -> Result<HashMap<String, Vec<u8>>, anyhow::Error> {
+    if body.first() != Some(&5) || body.get(1..6) != Some(b"READY".as_slice()) {
+        bail!("expected READY command");
+    }
+
+    let mut properties = HashMap::new();
+    let mut rest = &body[6..];
+    while !rest.is_empty() {
+        let name_len = rest[0] as usize;
+        let name = rest.get(1..1 + name_len).ok_or_else(|| anyhow!("truncated READY property"))?;
+        rest = &rest[1 + name_len..];
+
+        let value_len = rest
+            .get(..4)
+            .map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]) as usize)
+            .ok_or_else(|| anyhow!("truncated READY property"))?;
+        let value = rest.get(4..4 + value_len).ok_or_else(|| anyhow!("truncated READY property"))?;
+        rest = &rest[4 + value_len..];
+
+        properties.insert(String::from_utf8_lossy(name).to_string(), value.to_vec());
+    }
+    Ok(properties)
+}
+
+/// ZMTP 帧读写
+struct ZmtpStream<S> {
+    stream: S,
+}
+
+impl<S: AsyncRead + AsyncWrite + Unpin> ZmtpStream<S> {
+    /// 作为 SUB 端完成握手并订阅主题
+    async fn handshake(stream: S, topics: &[ZmqTopic]) -> Result<Self, anyhow::Error> {
+        let mut conn = ZmtpStream { stream };
+
+        conn.stream.write_all(&greeting(false)).await?;
+        let mut peer = [0u8; 64];
+        conn.stream.read_exact(&mut peer).await?;
+        if peer[0] != 0xff || peer[9] != 0x7f {
+            bail!("invalid ZMTP signature");
+        }
+        if peer[10] < 3 {
+            bail!("unsupported ZMTP version {}.{}", peer[10], peer[11]);
+        }
+        if &peer[12..32] != b"NULL\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0" {
+            bail!("unsupported ZMTP mechanism");
+        }
+
+        conn.write_frame(FLAG_COMMAND, &ready_command("SUB")).await?;
+        let (flags, body) = conn.read_frame().await?;
+        if flags & FLAG_COMMAND == 0 {
+            bail!("expected READY command");
+        }
+        let properties = parse_ready(&body)?;
+        match properties.get("Socket-Type").map(Vec::as_slice) {
+            Some(b"PUB") | Some(b"XPUB") => {}
+            other => bail!("unexpected peer socket type {:?}", other.map(String::from_utf8_lossy)),
+        }
+
+        // ZMTP 3.0 的订阅为首字节 0x01 的消息
+        for topic in topics {
+            let mut body = vec![1];
+            body.extend(topic.as_str().as_bytes());
+            conn.write_frame(0, &body).await?;
+        }
+        conn.stream.flush().await?;
+
+        Ok(conn)
+    }
+
+    async fn write_frame(&mut self, flags: u8, body: &[u8]) -> Result<(), anyhow::Error> {
+        let mut frame = Vec::with_capacity(body.len() + 9);
+        if body.len() > 255 {
+            frame.push(flags | FLAG_LONG);
+            frame.extend((body.len() as u64).to_be_bytes());
+        } else {
+            frame.push(flags);
+            frame.push(body.len() as u8);
+        }
+        frame.extend(body);
+        self.stream.write_all(&frame).await?;
+        Ok(())
+    }
+
+    async fn read_frame(&mut self) -> Result<(u8, Vec<u8>), anyhow::Error> {
+        let mut flags = [0u8; 1];
+        self.stream.read_exact(&mut flags).await?;
+        self.read_frame_body(flags[0]).await
+    }
+
+    async fn read_frame_body(&mut self, flags: u8) -> Result<(u8, Vec<u8>), anyhow::Error> {
+        let size = if flags & FLAG_LONG != 0 {
+            let mut size = [0u8; 8];
+            self.stream.read_exact(&mut size).await?;
+            u64::from_be_bytes(size)
+        } else {
+            let mut size = [0u8; 1];
+            self.stream.read_exact(&mut size).await?;
+            size[0] as u64
+        };
+        if size > MAX_FRAME_SIZE {
+            bail!("frame too large: {}", size);
+        }
+
+        let mut body = vec![0u8; size as usize];
+        self.stream.read_exact(&mut body).await?;
+        Ok((flags, body))
+    }
+
+    /// 读取一条完整消息, 忽略命令帧. 等待消息超过 idle 时返回 None;
+    /// 消息读到一半超时视为连接断开 (只在帧首字节处取消读取, 不会留下半个帧)
+    async fn read_message(&mut self, idle: Duration) -> Result<Option<Vec<Vec<u8>>>, anyhow::Error> {
+        let mut frames = Vec::new();
+        loop {
+            let mut flags = [0u8; 1];
+            match async_std::future::timeout(idle, self.stream.read_exact(&mut flags)).await {
+                Ok(res) => res?,
+                Err(_) if frames.is_empty() => return Ok(None),
+                Err(_) => bail!("read timeout"),
+            }
+            let (flags, body) = async_std::future::timeout(idle, self.read_frame_body(flags[0]))
+                .await
+                .map_err(|_| anyhow!("read timeout"))??;
+            if flags & FLAG_COMMAND != 0 {
+                continue;
+            }
+            frames.push(body);
+            if flags & FLAG_MORE == 0 {
+                return Ok(Some(frames));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use async_std::net::TcpListener;
+    use async_trait::async_trait;
+
+    use super::*;
+    use crate::btc_client::{consensus::tests::GENESIS_BLOCK, response_type::BlockWithTxs};
+
+    // 每次查询高度 +1
+    struct FakeSource {
+        tip: Mutex<u64>,
+    }
+
+    #[async_trait]
+    impl BlockSource for FakeSource {
+        async fn tip_height(&self) -> Result<u64, anyhow::Error> {
+            let mut tip = self.tip.lock().unwrap();
+            *tip += 1;
+            Ok(*tip)
+        }
+
+        async fn block_hash(&self, height: u64) -> Result<String, anyhow::Error> {
+            Ok(format!("h{}", height))
+        }
+
+        async fn block(&self, _hash: &str) -> Result<BlockWithTxs, anyhow::Error> {
+            bail!("unused")
+        }
+    }
+
+    // 模拟节点 PUB 端: 握手, 接收订阅后发送 messages. 返回订阅的主题及连接, 连接关闭后断开
+    async fn publisher(
+        listener: TcpListener,
+        messages: Vec<(&'static str, Vec<u8>, u32)>,
+    ) -> (Vec<String>, ZmtpStream<TcpStream>) {
+        let (stream, _) = listener.accept().await.unwrap();
+        drop(listener);
+
+        let mut conn = ZmtpStream { stream };
+        conn.stream.write_all(&greeting(true)).await.unwrap();
+        let mut peer = [0u8; 64];
+        conn.stream.read_exact(&mut peer).await.unwrap();
+        assert_eq!(&peer[12..16], b"NULL");
+
+        let (flags, body) = conn.read_frame().await.unwrap();
+        assert!(flags & FLAG_COMMAND != 0);
+        assert_eq!(parse_ready(&body).unwrap()["Socket-Type"], b"SUB");
+        conn.write_frame(FLAG_COMMAND, &ready_command("PUB")).await.unwrap();
+
+        let mut subscriptions = vec![];
+        for _ in 0..2 {
+            let (_, body) = conn.read_frame().await.unwrap();
+            assert_eq!(body[0], 1);
+            subscriptions.push(String::from_utf8(body[1..].to_vec()).unwrap());
+        }
+
+        for (topic, body, sequence) in messages {
+            conn.write_frame(FLAG_MORE, topic.as_bytes()).await.unwrap();
+            conn.write_frame(FLAG_MORE, &body).await.unwrap();
+            conn.write_frame(0, &sequence.to_le_bytes()).await.unwrap();
+        }
+        conn.stream.flush().await.unwrap();
+        (subscriptions, conn)
+    }
+
+    #[async_std::test]
+    async fn test_subscriber() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let mut added = vec![0xab; 32];
+        added.push(b'A');
+        added.extend(7u64.to_le_bytes());
+        let messages = vec![
+            ("hashblock", vec![0x11; 32], 0),
+            ("sequence", added, 5),
+            ("hashblock", vec![0x22; 32], 2),
+            ("rawblock", hex::decode(GENESIS_BLOCK).unwrap(), 0),
+        ];
+        let server = async_std::task::spawn(publisher(listener, messages));
+
+        let config = ZmqConfig {
+            endpoint: format!("tcp://{}", addr),
+            topics: vec![ZmqTopic::HashBlock, ZmqTopic::Sequence],
+            connect_timeout: Duration::from_secs(1),
+            reconnect_interval: Duration::from_millis(10),
+            idle_timeout: Duration::from_secs(5),
+        };
+        let mut subscriber = ZmqSubscriber::with_fallback(config, FakeSource { tip: Mutex::new(99) });
+
+        assert!(matches!(subscriber.next().await.unwrap(), ZmqEvent::Connected));
+        assert!(subscriber.is_connected());
+        assert!(matches!(
+            subscriber.next().await.unwrap(),
+            ZmqEvent::HashBlock { hash, sequence: 0 } if hash == "11".repeat(32)
+        ));
+        assert!(matches!(
+            subscriber.next().await.unwrap(),
+            ZmqEvent::Sequence { event: SequenceEvent::TxAdded { mempool_sequence: 7, .. }, sequence: 5 }
+        ));
+        assert!(matches!(
+            subscriber.next().await.unwrap(),
+            ZmqEvent::Gap { topic: ZmqTopic::HashBlock, expected: 1, received: 2 }
+        ));
+        assert!(matches!(subscriber.next().await.unwrap(), ZmqEvent::HashBlock { sequence: 2, .. }));
+        match subscriber.next().await.unwrap() {
+            ZmqEvent::RawBlock { block, .. } => {
+                assert_eq!(block.hash(), "000000000019d6689c085ae165831e934ff763ae46a2a6c172b3f1b60a8ce26f")
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+
+        let (subscriptions, conn) = server.await;
+        assert_eq!(subscriptions, vec!["hashblock", "sequence"]);
+        drop(conn);
+        assert!(matches!(subscriber.next().await.unwrap(), ZmqEvent::Disconnected { .. }));
+
+        // 重连失败, 首次轮询记录高度 100, 之后发现 101
+        assert!(matches!(
+            subscriber.next().await.unwrap(),
+            ZmqEvent::PolledBlock { height: 101, hash } if hash == "h101"
+        ));
+    }
+
+    #[async_std::test]
+    async fn test_idle_timeout() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        // 握手后不再发送也不关闭, 模拟半开连接
+        let (tx, rx) = futures::channel::oneshot::channel::<()>();
+        async_std::task::spawn(async move {
+            let (_, conn) = publisher(listener, vec![]).await;
+            let _ = rx.await;
+            drop(conn);
+        });
+
+        let config = ZmqConfig {
+            endpoint: format!("tcp://{}", addr),
+            topics: vec![ZmqTopic::HashBlock, ZmqTopic::Sequence],
+            connect_timeout: Duration::from_secs(1),
+            reconnect_interval: Duration::from_millis(10),
+            idle_timeout: Duration::from_millis(100),
+        };
+        let mut subscriber = ZmqSubscriber::with_fallback(config, FakeSource { tip: Mutex::new(99) });
+        assert!(matches!(subscriber.next().await.unwrap(), ZmqEvent::Connected));
+
+        // 第一次空闲检查记录高度 100, 第二次为 101, 期间没有收到区块通知
+        assert!(matches!(subscriber.next().await.unwrap(), ZmqEvent::Disconnected { .. }));
+        assert!(!subscriber.is_connected());
+        assert_eq!(subscriber.poll_height, Some(100));
+        drop(tx);
+    }
+
+    #[async_std::test]
+    async fn test_idle_without_fallback() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (tx, rx) = futures::channel::oneshot::channel::<()>();
+        async_std::task::spawn(async move {
+            let (_, conn) = publisher(listener, vec![]).await;
+            let _ = rx.await;
+            drop(conn);
+        });
+
+        let config = ZmqConfig {
+            endpoint: format!("tcp://{}", addr),
+            topics: vec![ZmqTopic::HashBlock, ZmqTopic::Sequence],
+            connect_timeout: Duration::from_secs(1),
+            reconnect_interval: Duration::from_millis(10),
+            idle_timeout: Duration::from_millis(50),
+        };
+        let mut subscriber = ZmqSubscriber::new(config);
+        assert!(matches!(subscriber.next().await.unwrap(), ZmqEvent::Connected));
+
+        // 多个空闲周期内没有消息也不断开重连
+        let next = async_std::future::timeout(Duration::from_millis(300), subscriber.next()).await;
+        assert!(next.is_err());
+        assert!(subscriber.is_connected());
+        drop(tx);
+    }
+
+    #[test]
+    fn test_parse_sequence() {
+        let mut body = vec![0x01; 32];
+        body.push(b'C');
+        assert_eq!(parse_sequence(&body).unwrap(), SequenceEvent::BlockConnected { hash: "01".repeat(32) });
+
+        body[32] = b'R';
+        assert!(parse_sequence(&body).is_err());
+        body.extend(1u64.to_le_bytes());
+        assert!(matches!(parse_sequence(&body).unwrap(), SequenceEvent::TxRemoved { mempool_sequence: 1, .. }));
+
+        body[32] = b'X';
+        assert!(parse_sequence(&body).is_err());
+    }
+}
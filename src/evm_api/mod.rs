@@ -1,74 +1,176 @@
-use std::time::Duration;
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
+use anyhow::{anyhow, bail};
+use futures::future::join_all;
 use reqwest::Client;
-use serde::{Deserialize, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use serde_json::{json, Value};
 
-use self::types::EthApiData;
+use crate::utils::rpc_batch::{batch_call, BatchConfig, BatchError, RpcRequest};
 
+use self::{pool::*, primitives::*, types::*};
+
+pub mod pool;
+pub mod primitives;
 pub mod types;
 
+/// EVM 节点客户端. 克隆后共享节点池, 可在多个任务中同时使用
 #[derive(Debug, Clone)]
 pub struct EvmNode {
     client: Client,
-    pub rpcs: Vec<String>,
-    pub rpcs_index: usize,
+    pool: Arc<EndpointPool>,
+    pub batch_config: BatchConfig,
 }
 
 impl EvmNode {
+    /// # Panics
+    ///
+    /// rpcs 为空时 panic, 需要处理错误时使用 with_config
     pub fn new(rpcs: Vec<String>, timeout: u64) -> Self {
+        EvmNode::with_config(rpcs, timeout, PoolConfig::default()).expect("EvmNode requires at least one rpc")
+    }
+
+    pub fn with_config(rpcs: Vec<String>, timeout: u64, config: PoolConfig) -> Result<Self, anyhow::Error> {
+        if rpcs.is_empty() {
+            bail!("no evm rpc");
+        }
+
         let client = reqwest::Client::builder()
             .timeout(Duration::from_secs(timeout))
-            .build()
-            .unwrap();
+            .build()?;
 
-        EvmNode {
+        Ok(EvmNode {
             client,
-            rpcs,
-            rpcs_index: 0,
+            pool: Arc::new(EndpointPool::new(rpcs, config)),
+            batch_config: BatchConfig::default(),
+        })
+    }
+
+    /// 各节点的错误率、延迟、区块高度等统计
+    pub fn metrics(&self) -> Vec<EndpointMetrics> {
+        self.pool.metrics()
+    }
+
+    /// 同时请求各节点的 eth_blockNumber, 更新区块高度, 落后过多的节点暂停使用
+    pub async fn check_endpoints(&self) -> Vec<EndpointMetrics> {
+        let post_json = json!({
+            "id": 1,
+            "jsonrpc": "2.0",
+            "method": "eth_blockNumber",
+            "params": [],
+        });
+
+        let checks = (0..self.pool.len()).map(|index| self.post::<EthApiData<U64>>(index, &post_json));
+        for (index, res) in join_all(checks).await.into_iter().enumerate() {
+            match res {
+                Ok(EthApiData { result: Some(head), .. }) => self.pool.update_head(index, head.as_u64()),
+                Ok(data) => log::warn!("[EvmNode] health check {} error: {:?}", self.pool.url(index), data.error),
+                Err(err) => log::warn!("[EvmNode] health check {} failed: {}", self.pool.url(index), err),
+            }
         }
+
+        self.metrics()
     }
 
-    pub fn update_index(&mut self) {
-        self.rpcs_index = (self.rpcs_index + 1) % self.rpcs.len();
+    /// 按 interval 定期检查节点, 不会返回, 需在单独的任务中运行
+    pub async fn run_health_check(&self, interval: Duration) {
+        loop {
+            self.check_endpoints().await;
+            async_std::task::sleep(interval).await;
+        }
     }
 
+    /// 每次选择最优节点请求, 失败后换下一个节点; 全部节点失败后等待 1s 重新开始.
+    /// try_count 为总尝试次数, None 时一直重试
     pub async fn http<T: for<'de> Deserialize<'de>>(
-        &mut self,
+        &self,
         post_json: Value,
-        try_count: Option<usize>
+        try_count: Option<usize>,
     ) -> Result<EthApiData<T>, anyhow::Error> {
         let mut count = 1;
+        let mut tried = Vec::new();
 
         loop {
-            let res = self
-                .client
-                .post(self.rpcs[self.rpcs_index].clone())
-                .json(&post_json)
-                .send()
-                .await?
-                .json::<EthApiData<T>>()
-                .await;
-            
-            match res {
+            let Some(index) = self.pool.select(&tried) else {
+                tried.clear();
+                async_std::task::sleep(Duration::from_secs(1)).await;
+                continue;
+            };
+
+            match self.post::<EthApiData<T>>(index, &post_json).await {
                 Ok(data) => return Ok(data),
                 Err(err) => {
-                    println!("--EvmNode--index: {} try_count {} failed: {}. sleep 1s", self.rpcs_index, count, err);
+                    log::warn!("[EvmNode] {} try_count {} failed: {}", self.pool.url(index), count, err);
                     // 达到最大尝试次数, 返回最后一次错误
-                    if try_count.is_some_and(|try_count|try_count <= count) {
-                        return Err(err.into());
+                    if try_count.is_some_and(|try_count| try_count <= count) {
+                        return Err(err);
                     }
-                    async_std::task::sleep(Duration::from_secs(1)).await;
-                },
+                    tried.push(index);
+                }
             }
 
-            self.update_index();
-
             count += 1;
         }
     }
 
-    pub async fn eth_block_number(&mut self, try_count: Option<usize>) -> Result<EthApiData<String>, anyhow::Error> {
+    // 请求指定节点并记录结果. JSON-RPC 错误 (如 execution reverted) 不计为节点失败
+    async fn post<T: DeserializeOwned>(&self, index: usize, post_json: &Value) -> Result<T, anyhow::Error> {
+        let start = Instant::now();
+        let res = async {
+            self.client
+                .post(self.pool.url(index))
+                .json(post_json)
+                .send()
+                .await?
+                .error_for_status()?
+                .json::<T>()
+                .await
+        }
+        .await;
+
+        match res {
+            Ok(data) => {
+                self.pool.record_success(index, start.elapsed());
+                Ok(data)
+            }
+            Err(err) => {
+                self.pool.record_failure(index);
+                Err(err.into())
+            }
+        }
+    }
+
+    /// 批量rpc请求, 返回值与 requests 一一对应. 每个分块重新选择最优节点, 失败后换下一个节点;
+    /// 全部节点失败时由 batch_config 控制等待和重试
+    pub async fn batch_call<T: for<'de> Deserialize<'de>>(
+        &self,
+        requests: Vec<RpcRequest>,
+    ) -> Vec<Result<T, BatchError>> {
+        batch_call(requests, &self.batch_config, |post_json| {
+            let post_json = Value::Array(post_json);
+            async move {
+                let mut tried = Vec::new();
+                let mut last_err = anyhow!("no evm rpc");
+                while let Some(index) = self.pool.select(&tried) {
+                    match self.post::<Vec<Value>>(index, &post_json).await {
+                        Ok(data) => return Ok(data),
+                        Err(err) => {
+                            log::warn!("[EvmNode] {} batch failed: {}", self.pool.url(index), err);
+                            tried.push(index);
+                            last_err = err;
+                        }
+                    }
+                }
+                Err(last_err)
+            }
+        })
+        .await
+    }
+
+    pub async fn eth_block_number(&self, try_count: Option<usize>) -> Result<EthApiData<U64>, anyhow::Error> {
         let post_json = json!({
             "id": 1,
             "jsonrpc": "2.0",
@@ -78,6 +180,169 @@ impl EvmNode {
 
         self.http(post_json, try_count).await
     }
+
+    // 通用rpc请求, params 为 json 数组
+    pub async fn request<T: for<'de> Deserialize<'de>>(
+        &self,
+        method: &str,
+        params: Value,
+        try_count: Option<usize>,
+    ) -> Result<EthApiData<T>, anyhow::Error> {
+        let post_json = json!({
+            "id": 1,
+            "jsonrpc": "2.0",
+            "method": method,
+            "params": params,
+        });
+
+        self.http(post_json, try_count).await
+    }
+
+    /// transactions 为交易哈希, 区块不存在时 result 为 None
+    pub async fn eth_get_block_by_number(
+        &self,
+        number: BlockNumber,
+        try_count: Option<usize>,
+    ) -> Result<EthApiData<Block>, anyhow::Error> {
+        self.request("eth_getBlockByNumber", json!([number, false]), try_count).await
+    }
+
+    /// transactions 为完整交易
+    pub async fn eth_get_block_by_number_with_txs(
+        &self,
+        number: BlockNumber,
+        try_count: Option<usize>,
+    ) -> Result<EthApiData<BlockWithTxs>, anyhow::Error> {
+        self.request("eth_getBlockByNumber", json!([number, true]), try_count).await
+    }
+
+    pub async fn eth_get_block_by_hash(
+        &self,
+        hash: H256,
+        try_count: Option<usize>,
+    ) -> Result<EthApiData<Block>, anyhow::Error> {
+        self.request("eth_getBlockByHash", json!([hash, false]), try_count).await
+    }
+
+    pub async fn eth_get_block_by_hash_with_txs(
+        &self,
+        hash: H256,
+        try_count: Option<usize>,
+    ) -> Result<EthApiData<BlockWithTxs>, anyhow::Error> {
+        self.request("eth_getBlockByHash", json!([hash, true]), try_count).await
+    }
+
+    /// 交易不存在时 result 为 None
+    pub async fn eth_get_transaction_by_hash(
+        &self,
+        hash: H256,
+        try_count: Option<usize>,
+    ) -> Result<EthApiData<Transaction>, anyhow::Error> {
+        self.request("eth_getTransactionByHash", json!([hash]), try_count).await
+    }
+
+    /// 交易未打包时 result 为 None
+    pub async fn eth_get_transaction_receipt(
+        &self,
+        hash: H256,
+        try_count: Option<usize>,
+    ) -> Result<EthApiData<Receipt>, anyhow::Error> {
+        self.request("eth_getTransactionReceipt", json!([hash]), try_count).await
+    }
+
+    /// 区块内全部收据, 按交易顺序排列
+    pub async fn eth_get_block_receipts(
+        &self,
+        number: BlockNumber,
+        try_count: Option<usize>,
+    ) -> Result<EthApiData<Vec<Receipt>>, anyhow::Error> {
+        self.request("eth_getBlockReceipts", json!([number]), try_count).await
+    }
+
+    pub async fn eth_get_logs(
+        &self,
+        filter: &LogFilter,
+        try_count: Option<usize>,
+    ) -> Result<EthApiData<Vec<Log>>, anyhow::Error> {
+        self.request("eth_getLogs", json!([filter]), try_count).await
+    }
+
+    /// 返回调用结果
+    pub async fn eth_call(
+        &self,
+        call: &CallRequest,
+        block: BlockNumber,
+        try_count: Option<usize>,
+    ) -> Result<EthApiData<Bytes>, anyhow::Error> {
+        self.request("eth_call", json!([call, block]), try_count).await
+    }
+
+    pub async fn eth_estimate_gas(
+        &self,
+        call: &CallRequest,
+        try_count: Option<usize>,
+    ) -> Result<EthApiData<U64>, anyhow::Error> {
+        self.request("eth_estimateGas", json!([call]), try_count).await
+    }
+
+    /// 余额, 单位 wei
+    pub async fn eth_get_balance(
+        &self,
+        address: &H160,
+        block: BlockNumber,
+        try_count: Option<usize>,
+    ) -> Result<EthApiData<U256>, anyhow::Error> {
+        self.request("eth_getBalance", json!([address, block]), try_count).await
+    }
+
+    /// 合约字节码, 非合约地址为空
+    pub async fn eth_get_code(
+        &self,
+        address: &H160,
+        block: BlockNumber,
+        try_count: Option<usize>,
+    ) -> Result<EthApiData<Bytes>, anyhow::Error> {
+        self.request("eth_getCode", json!([address, block]), try_count).await
+    }
+
+    /// nonce, 使用 Pending 时包含交易池中的交易
+    pub async fn eth_get_transaction_count(
+        &self,
+        address: &H160,
+        block: BlockNumber,
+        try_count: Option<usize>,
+    ) -> Result<EthApiData<U64>, anyhow::Error> {
+        self.request("eth_getTransactionCount", json!([address, block]), try_count).await
+    }
+
+    pub async fn eth_gas_price(&self, try_count: Option<usize>) -> Result<EthApiData<U256>, anyhow::Error> {
+        self.request("eth_gasPrice", json!([]), try_count).await
+    }
+
+    /// 最近 block_count 个区块的 base fee 及各百分位的小费
+    pub async fn eth_fee_history(
+        &self,
+        block_count: u64,
+        newest_block: BlockNumber,
+        reward_percentiles: &[f64],
+        try_count: Option<usize>,
+    ) -> Result<EthApiData<FeeHistory>, anyhow::Error> {
+        let params = json!([U64::from(block_count), newest_block, reward_percentiles]);
+        self.request("eth_feeHistory", params, try_count).await
+    }
+
+    pub async fn eth_chain_id(&self, try_count: Option<usize>) -> Result<EthApiData<U64>, anyhow::Error> {
+        self.request("eth_chainId", json!([]), try_count).await
+    }
+
+    /// 广播已签名交易, 返回交易哈希
+    pub async fn eth_send_raw_transaction(
+        &self,
+        raw_tx: &Bytes,
+        try_count: Option<usize>,
+    ) -> Result<EthApiData<H256>, anyhow::Error> {
+        self.request("eth_sendRawTransaction", json!([raw_tx]), try_count).await
+    }
 }
 
 #[cfg(test)]
@@ -90,7 +355,7 @@ mod tests {
             "http://192.168.195.239:50545/jsonc".to_string(),
             "http://192.168.195.239:50545/jsonrpc".to_string(),
         ];
-        let mut evm_node = EvmNode::new(urls, 10);
+        let evm_node = EvmNode::new(urls, 10);
 
         match evm_node.eth_block_number(Some(3)).await {
             Ok(data) => println!("{:#?}", data),
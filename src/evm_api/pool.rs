@@ -0,0 +1,359 @@
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+// 选择节点时一次失败 (按错误率折算) 相当于的延迟, 毫秒
+const ERROR_PENALTY_MS: f64 = 1000.0;
+
+/// 节点池配置
+#[derive(Debug, Clone)]
+pub struct PoolConfig {
+    /// 连续失败达到该次数时剔除
+    pub max_consecutive_failures: u32,
+    /// 错误率超过该值时剔除
+    pub max_error_rate: f64,
+    /// 请求数达到该值后才按错误率剔除
+    pub min_samples: u64,
+    /// 落后最高节点的区块数超过该值时剔除, 下次检查追上后恢复
+    pub max_block_lag: u64,
+    /// 因失败剔除的时长, 到期后重新参与选择
+    pub eject_cooldown: Duration,
+    /// 错误率和延迟的指数滑动平均权重
+    pub ewma_alpha: f64,
+}
+
+impl Default for PoolConfig {
+    fn default() -> Self {
+        PoolConfig {
+            max_consecutive_failures: 3,
+            max_error_rate: 0.5,
+            min_samples: 10,
+            max_block_lag: 3,
+            eject_cooldown: Duration::from_secs(30),
+            ewma_alpha: 0.2,
+        }
+    }
+}
+
+/// 节点被剔除的原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EjectReason {
+    /// 连续失败
+    Failures,
+    /// 错误率过高
+    ErrorRate,
+    /// 区块高度落后
+    Lagging,
+}
+
+/// 节点统计, 由 EvmNode::metrics 导出
+#[derive(Debug, Clone, PartialEq)]
+pub struct EndpointMetrics {
+    pub url: String,
+    /// 是否参与选择
+    pub healthy: bool,
+    pub eject_reason: Option<EjectReason>,
+    pub requests: u64,
+    pub failures: u64,
+    pub consecutive_failures: u32,
+    /// 滑动平均错误率, 0 ~ 1
+    pub error_rate: f64,
+    /// 成功请求的滑动平均延迟, 尚无成功请求时为 None
+    pub latency: Option<Duration>,
+    /// 最近一次检查时的区块高度
+    pub head_block: Option<u64>,
+    /// 落后各节点最高高度的区块数
+    pub lag: Option<u64>,
+}
+
+#[derive(Debug, Default)]
+struct Stats {
+    requests: u64,
+    failures: u64,
+    consecutive_failures: u32,
+    error_rate: f64,
+    latency_ms: Option<f64>,
+    head_block: Option<u64>,
+    ejected_until: Option<Instant>,
+    eject_reason: Option<EjectReason>,
+}
+
+#[derive(Debug)]
+struct Endpoint {
+    url: String,
+    stats: Mutex<Stats>,
+}
+
+/// RPC 节点池, 记录各节点的错误率、延迟和区块高度, 每次请求选择最优节点.
+/// 由 EvmNode 的各个克隆共享
+#[derive(Debug)]
+pub struct EndpointPool {
+    endpoints: Vec<Endpoint>,
+    config: PoolConfig,
+}
+
+impl EndpointPool {
+    pub fn new(urls: Vec<String>, config: PoolConfig) -> Self {
+        let endpoints = urls
+            .into_iter()
+            .map(|url| Endpoint {
+                url,
+                stats: Mutex::new(Stats::default()),
+            })
+            .collect();
+
+        EndpointPool { endpoints, config }
+    }
+
+    pub fn len(&self) -> usize {
+        self.endpoints.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.endpoints.is_empty()
+    }
+
+    pub fn url(&self, index: usize) -> &str {
+        &self.endpoints[index].url
+    }
+
+    pub fn config(&self) -> &PoolConfig {
+        &self.config
+    }
+
+    /// 选择不在 exclude 中的最优节点: 健康节点按 延迟 + 错误率惩罚 排序;
+    /// 没有健康节点时退而选择最早恢复的节点. 全部排除时返回 None
+    pub fn select(&self, exclude: &[usize]) -> Option<usize> {
+        let now = Instant::now();
+        let max_head = self.max_head();
+
+        let mut best: Option<(usize, f64)> = None;
+        let mut fallback: Option<(usize, Option<Instant>)> = None;
+        for (index, endpoint) in self.endpoints.iter().enumerate() {
+            if exclude.contains(&index) {
+                continue;
+            }
+
+            let stats = endpoint.stats.lock().unwrap();
+            if self.is_healthy(&stats, now, max_head) {
+                let score = stats.latency_ms.unwrap_or(0.0) + stats.error_rate * ERROR_PENALTY_MS;
+                if best.is_none_or(|(_, best_score)| score < best_score) {
+                    best = Some((index, score));
+                }
+            } else if fallback.is_none_or(|(_, until)| stats.ejected_until < until) {
+                fallback = Some((index, stats.ejected_until));
+            }
+        }
+
+        best.map(|(index, _)| index).or(fallback.map(|(index, _)| index))
+    }
+
+    /// 记录一次成功请求
+    pub fn record_success(&self, index: usize, latency: Duration) {
+        let alpha = self.config.ewma_alpha;
+        let latency_ms = latency.as_secs_f64() * 1000.0;
+
+        let mut stats = self.endpoints[index].stats.lock().unwrap();
+        stats.requests += 1;
+        stats.consecutive_failures = 0;
+        stats.error_rate *= 1.0 - alpha;
+        stats.latency_ms = Some(match stats.latency_ms {
+            Some(avg) => avg * (1.0 - alpha) + latency_ms * alpha,
+            None => latency_ms,
+        });
+        if stats.eject_reason == Some(EjectReason::Failures) {
+            stats.ejected_until = None;
+            stats.eject_reason = None;
+        }
+    }
+
+    /// 记录一次失败请求, 达到阈值时剔除
+    pub fn record_failure(&self, index: usize) {
+        let config = &self.config;
+        let endpoint = &self.endpoints[index];
+
+        let mut stats = endpoint.stats.lock().unwrap();
+        stats.requests += 1;
+        stats.failures += 1;
+        stats.consecutive_failures += 1;
+        stats.error_rate = stats.error_rate * (1.0 - config.ewma_alpha) + config.ewma_alpha;
+
+        let reason = if stats.consecutive_failures >= config.max_consecutive_failures {
+            EjectReason::Failures
+        } else if stats.requests >= config.min_samples && stats.error_rate > config.max_error_rate {
+            EjectReason::ErrorRate
+        } else {
+            return;
+        };
+
+        if stats.ejected_until.is_none_or(|until| Instant::now() >= until) {
+            log::warn!(
+                "[EndpointPool] eject {} for {:?}: {:?}, error rate {:.2}",
+                endpoint.url,
+                config.eject_cooldown,
+                reason,
+                stats.error_rate
+            );
+        }
+        stats.ejected_until = Some(Instant::now() + config.eject_cooldown);
+        stats.eject_reason = Some(reason);
+    }
+
+    /// 更新节点区块高度, 由 EvmNode::check_endpoints 同时检查各节点后调用
+    pub fn update_head(&self, index: usize, head_block: u64) {
+        self.endpoints[index].stats.lock().unwrap().head_block = Some(head_block);
+    }
+
+    pub fn metrics(&self) -> Vec<EndpointMetrics> {
+        let now = Instant::now();
+        let max_head = self.max_head();
+
+        self.endpoints
+            .iter()
+            .map(|endpoint| {
+                let stats = endpoint.stats.lock().unwrap();
+                let lag = self.lag(&stats, max_head);
+                let lagging = lag.is_some_and(|lag| lag > self.config.max_block_lag);
+                let ejected = stats.ejected_until.is_some_and(|until| now < until);
+
+                EndpointMetrics {
+                    url: endpoint.url.clone(),
+                    healthy: !lagging && !ejected,
+                    eject_reason: if lagging {
+                        Some(EjectReason::Lagging)
+                    } else if ejected {
+                        stats.eject_reason
+                    } else {
+                        None
+                    },
+                    requests: stats.requests,
+                    failures: stats.failures,
+                    consecutive_failures: stats.consecutive_failures,
+                    error_rate: stats.error_rate,
+                    latency: stats.latency_ms.map(|ms| Duration::from_secs_f64(ms / 1000.0)),
+                    head_block: stats.head_block,
+                    lag,
+                }
+            })
+            .collect()
+    }
+
+    fn max_head(&self) -> Option<u64> {
+        self.endpoints
+            .iter()
+            .filter_map(|endpoint| endpoint.stats.lock().unwrap().head_block)
+            .max()
+    }
+
+    fn lag(&self, stats: &Stats, max_head: Option<u64>) -> Option<u64> {
+        Some(max_head?.saturating_sub(stats.head_block?))
+    }
+
+    fn is_healthy(&self, stats: &Stats, now: Instant, max_head: Option<u64>) -> bool {
+        let lagging = self
+            .lag(stats, max_head)
+            .is_some_and(|lag| lag > self.config.max_block_lag);
+        let ejected = stats.ejected_until.is_some_and(|until| now < until);
+        !lagging && !ejected
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use serde_json::json;
+
+    use super::*;
+    use crate::{
+        evm_api::{primitives::U64, EvmNode},
+        utils::{
+            rpc_batch::RpcRequest,
+            test_node::{down_node, fake_node},
+        },
+    };
+
+    #[test]
+    fn test_select() {
+        let urls = vec!["a".to_string(), "b".to_string(), "c".to_string()];
+        let config = PoolConfig {
+            max_consecutive_failures: 2,
+            ..Default::default()
+        };
+        let pool = EndpointPool::new(urls, config);
+
+        pool.record_success(0, Duration::from_millis(50));
+        pool.record_success(1, Duration::from_millis(10));
+        pool.record_success(2, Duration::from_millis(30));
+        assert_eq!(pool.select(&[]), Some(1));
+        assert_eq!(pool.select(&[1]), Some(2));
+
+        // 一次失败降低优先级, 连续两次失败剔除
+        pool.record_failure(1);
+        assert_eq!(pool.select(&[]), Some(2));
+        pool.record_failure(1);
+        let metrics = pool.metrics();
+        assert!(!metrics[1].healthy);
+        assert_eq!(metrics[1].eject_reason, Some(EjectReason::Failures));
+        assert_eq!((metrics[1].requests, metrics[1].failures), (3, 2));
+        assert_eq!(pool.select(&[2]), Some(0));
+
+        // 落后的节点不参与选择
+        pool.update_head(0, 100);
+        pool.update_head(1, 100);
+        pool.update_head(2, 90);
+        assert_eq!(pool.select(&[]), Some(0));
+        assert_eq!(pool.metrics()[2].lag, Some(10));
+        assert_eq!(pool.metrics()[2].eject_reason, Some(EjectReason::Lagging));
+
+        // 没有健康节点时选择最早恢复的节点
+        pool.record_failure(0);
+        pool.record_failure(0);
+        assert_eq!(pool.select(&[]), Some(2));
+        assert_eq!(pool.select(&[0, 1, 2]), None);
+
+        // 成功后恢复
+        pool.record_success(1, Duration::from_millis(10));
+        assert!(pool.metrics()[1].healthy);
+    }
+
+    // 模拟节点: 对所有请求返回区块高度
+    async fn node(head: u64) -> String {
+        fake_node(move |_, _| Ok(json!(format!("{:#x}", head)))).await.0
+    }
+
+    #[async_std::test]
+    async fn test_check_endpoints() {
+        let urls = vec![down_node().await, node(90).await, node(100).await];
+        let evm_node = Arc::new(EvmNode::new(urls, 2));
+
+        let metrics = evm_node.check_endpoints().await;
+        assert_eq!((metrics[0].failures, metrics[0].head_block), (1, None));
+        assert_eq!((metrics[1].lag, metrics[1].healthy), (Some(10), false));
+        assert_eq!((metrics[2].lag, metrics[2].healthy), (Some(0), true));
+
+        // 失败和落后的节点都不会被选中
+        let node = evm_node.clone();
+        let task = async_std::task::spawn(async move { node.eth_block_number(Some(1)).await.unwrap() });
+        assert_eq!(task.await.result.unwrap().as_u64(), 100);
+        assert_eq!(evm_node.metrics()[2].requests, 2);
+    }
+
+    #[async_std::test]
+    async fn test_batch_failover() {
+        let urls = vec![down_node().await, node(100).await];
+        assert!(EvmNode::with_config(vec![], 2, PoolConfig::default()).is_err());
+
+        // 不重试, 同一次请求内切换到第二个节点
+        let mut evm_node = EvmNode::with_config(urls, 2, PoolConfig::default()).unwrap();
+        evm_node.batch_config.max_retries = 0;
+        let results = evm_node
+            .batch_call::<U64>(vec![RpcRequest::new("eth_blockNumber", json!([]))])
+            .await;
+        assert_eq!(results[0].as_ref().unwrap().as_u64(), 100);
+
+        let metrics = evm_node.metrics();
+        assert_eq!((metrics[0].failures, metrics[1].requests), (1, 1));
+    }
+}
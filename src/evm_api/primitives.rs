@@ -0,0 +1,743 @@
+use std::{
+    cmp::Ordering,
+    fmt,
+    ops::{Add, Deref, Div, Mul, Rem, Sub},
+    str::FromStr,
+};
+
+use anyhow::{anyhow, bail};
+use bigdecimal::{
+    num_bigint::{BigInt, Sign},
+    BigDecimal,
+};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::utils::address_convert::eip55_address;
+
+// 去掉 0x 前缀, 没有前缀时返回 None
+fn strip_hex_prefix(s: &str) -> Option<&str> {
+    s.strip_prefix("0x").or_else(|| s.strip_prefix("0X"))
+}
+
+/// 256 位无符号整数, 对应 EVM 的 uint256. 以 4 个 u64 小端序保存 (0 为最低位)
+/// JSON-RPC 中为 0x 开头的 hex (quantity), 如 "0x0", "0xde0b6b3a7640000"
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct U256([u64; 4]);
+
+impl U256 {
+    pub const ZERO: U256 = U256([0; 4]);
+    pub const ONE: U256 = U256([1, 0, 0, 0]);
+    pub const MAX: U256 = U256([u64::MAX; 4]);
+
+    pub const fn from_limbs(limbs: [u64; 4]) -> Self {
+        U256(limbs)
+    }
+
+    pub const fn as_limbs(&self) -> &[u64; 4] {
+        &self.0
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.0 == [0; 4]
+    }
+
+    /// 有效位数, 0 为 0
+    pub fn bits(&self) -> u32 {
+        for i in (0..4).rev() {
+            if self.0[i] != 0 {
+                return 64 * i as u32 + 64 - self.0[i].leading_zeros();
+            }
+        }
+        0
+    }
+
+    fn bit(&self, index: u32) -> bool {
+        self.0[index as usize / 64] >> (index % 64) & 1 == 1
+    }
+
+    /// 低 64 位, 高位被截断
+    pub fn low_u64(&self) -> u64 {
+        self.0[0]
+    }
+
+    /// 大端序 32 字节, 与 ABI 编码一致
+    pub fn to_be_bytes(&self) -> [u8; 32] {
+        let mut bytes = [0u8; 32];
+        for (i, limb) in self.0.iter().rev().enumerate() {
+            bytes[i * 8..i * 8 + 8].copy_from_slice(&limb.to_be_bytes());
+        }
+        bytes
+    }
+
+    pub fn from_be_bytes(bytes: [u8; 32]) -> Self {
+        let mut limbs = [0u64; 4];
+        for (i, chunk) in bytes.chunks(8).enumerate() {
+            limbs[3 - i] = u64::from_be_bytes(chunk.try_into().unwrap());
+        }
+        U256(limbs)
+    }
+
+    /// 不超过 32 字节的大端序数据, 如 eth_call 返回值中的一个字
+    pub fn from_be_slice(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() > 32 {
+            return None;
+        }
+        let mut buf = [0u8; 32];
+        buf[32 - bytes.len()..].copy_from_slice(bytes);
+        Some(U256::from_be_bytes(buf))
+    }
+
+    /// 解析 hex, 可带 0x 前缀, 允许前导零, "0x" 视为 0
+    pub fn from_hex_str(s: &str) -> Result<Self, anyhow::Error> {
+        let digits = strip_hex_prefix(s).unwrap_or(s).trim_start_matches('0');
+        if !digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+            bail!("invalid hex quantity: {:?}", s);
+        }
+        if digits.len() > 64 {
+            bail!("uint256 overflow: {}", s);
+        }
+
+        let mut limbs = [0u64; 4];
+        for (i, chunk) in digits.as_bytes().rchunks(16).enumerate() {
+            let chunk = std::str::from_utf8(chunk).unwrap();
+            limbs[i] = u64::from_str_radix(chunk, 16)?;
+        }
+        Ok(U256(limbs))
+    }
+
+    pub fn from_dec_str(s: &str) -> Result<Self, anyhow::Error> {
+        if s.is_empty() || !s.bytes().all(|b| b.is_ascii_digit()) {
+            bail!("invalid decimal: {:?}", s);
+        }
+
+        // 每次处理 19 位, 10^19 < 2^64
+        let mut value = U256::ZERO;
+        for chunk in s.as_bytes().chunks(19) {
+            let scale = 10u64.pow(chunk.len() as u32);
+            let chunk = std::str::from_utf8(chunk).unwrap().parse::<u64>()?;
+            value = value
+                .checked_mul(U256::from(scale))
+                .and_then(|value| value.checked_add(U256::from(chunk)))
+                .ok_or_else(|| anyhow!("uint256 overflow: {}", s))?;
+        }
+        Ok(value)
+    }
+
+    pub fn overflowing_add(self, rhs: U256) -> (U256, bool) {
+        let mut limbs = [0u64; 4];
+        let mut carry = false;
+        for (i, limb) in limbs.iter_mut().enumerate() {
+            let (sum, c1) = self.0[i].overflowing_add(rhs.0[i]);
+            let (sum, c2) = sum.overflowing_add(carry as u64);
+            *limb = sum;
+            carry = c1 || c2;
+        }
+        (U256(limbs), carry)
+    }
+
+    pub fn overflowing_sub(self, rhs: U256) -> (U256, bool) {
+        let mut limbs = [0u64; 4];
+        let mut borrow = false;
+        for (i, limb) in limbs.iter_mut().enumerate() {
+            let (diff, b1) = self.0[i].overflowing_sub(rhs.0[i]);
+            let (diff, b2) = diff.overflowing_sub(borrow as u64);
+            *limb = diff;
+            borrow = b1 || b2;
+        }
+        (U256(limbs), borrow)
+    }
+
+    pub fn checked_add(self, rhs: U256) -> Option<U256> {
+        match self.overflowing_add(rhs) {
+            (value, false) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn checked_sub(self, rhs: U256) -> Option<U256> {
+        match self.overflowing_sub(rhs) {
+            (value, false) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn saturating_sub(self, rhs: U256) -> U256 {
+        self.checked_sub(rhs).unwrap_or(U256::ZERO)
+    }
+
+    pub fn checked_mul(self, rhs: U256) -> Option<U256> {
+        let mut product = [0u64; 8];
+        for i in 0..4 {
+            let mut carry = 0u128;
+            for j in 0..4 {
+                let t = self.0[i] as u128 * rhs.0[j] as u128 + product[i + j] as u128 + carry;
+                product[i + j] = t as u64;
+                carry = t >> 64;
+            }
+            product[i + 4] = carry as u64;
+        }
+
+        if product[4..].iter().any(|&limb| limb != 0) {
+            return None;
+        }
+        Some(U256([product[0], product[1], product[2], product[3]]))
+    }
+
+    /// 商和余数, 除数为 0 时返回 None
+    pub fn checked_div_rem(self, rhs: U256) -> Option<(U256, U256)> {
+        if rhs.is_zero() {
+            return None;
+        }
+        if self < rhs {
+            return Some((U256::ZERO, self));
+        }
+
+        // 逐位长除法
+        let mut quotient = U256::ZERO;
+        let mut remainder = U256::ZERO;
+        for i in (0..self.bits()).rev() {
+            // remainder < rhs, 左移后最高位溢出时实际值必然 >= rhs
+            let overflow = remainder.0[3] >> 63 == 1;
+            remainder = remainder.shl1();
+            remainder.0[0] |= self.bit(i) as u64;
+            if overflow || remainder >= rhs {
+                remainder = remainder.overflowing_sub(rhs).0;
+                quotient.0[i as usize / 64] |= 1 << (i % 64);
+            }
+        }
+        Some((quotient, remainder))
+    }
+
+    pub fn checked_div(self, rhs: U256) -> Option<U256> {
+        self.checked_div_rem(rhs).map(|(quotient, _)| quotient)
+    }
+
+    pub fn checked_rem(self, rhs: U256) -> Option<U256> {
+        self.checked_div_rem(rhs).map(|(_, remainder)| remainder)
+    }
+
+    fn shl1(self) -> U256 {
+        let l = self.0;
+        U256([
+            l[0] << 1,
+            l[1] << 1 | l[0] >> 63,
+            l[2] << 1 | l[1] >> 63,
+            l[3] << 1 | l[2] >> 63,
+        ])
+    }
+
+    // 除以 u64, 用于十进制格式化
+    fn div_rem_u64(self, rhs: u64) -> (U256, u64) {
+        let mut limbs = [0u64; 4];
+        let mut remainder = 0u128;
+        for i in (0..4).rev() {
+            let t = remainder << 64 | self.0[i] as u128;
+            limbs[i] = (t / rhs as u128) as u64;
+            remainder = t % rhs as u128;
+        }
+        (U256(limbs), remainder as u64)
+    }
+
+    /// 按精度转为小数, 如代币余额按 decimals 换算
+    pub fn to_big_decimal(&self, decimals: u32) -> BigDecimal {
+        let digits = BigInt::from_bytes_be(Sign::Plus, &self.to_be_bytes());
+        BigDecimal::new(digits, decimals as i64)
+    }
+}
+
+impl Ord for U256 {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.iter().rev().cmp(other.0.iter().rev())
+    }
+}
+
+impl PartialOrd for U256 {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl From<u64> for U256 {
+    fn from(value: u64) -> Self {
+        U256([value, 0, 0, 0])
+    }
+}
+
+impl From<u128> for U256 {
+    fn from(value: u128) -> Self {
+        U256([value as u64, (value >> 64) as u64, 0, 0])
+    }
+}
+
+impl From<U64> for U256 {
+    fn from(value: U64) -> Self {
+        U256::from(value.0)
+    }
+}
+
+impl TryFrom<U256> for u64 {
+    type Error = anyhow::Error;
+
+    fn try_from(value: U256) -> Result<Self, Self::Error> {
+        if value.bits() > 64 {
+            bail!("{} does not fit in u64", value);
+        }
+        Ok(value.0[0])
+    }
+}
+
+impl TryFrom<U256> for u128 {
+    type Error = anyhow::Error;
+
+    fn try_from(value: U256) -> Result<Self, Self::Error> {
+        if value.bits() > 128 {
+            bail!("{} does not fit in u128", value);
+        }
+        Ok((value.0[1] as u128) << 64 | value.0[0] as u128)
+    }
+}
+
+// 运算符溢出或除数为 0 时 panic, 与整数运算一致; 需要处理溢出时使用 checked_*
+impl Add for U256 {
+    type Output = U256;
+
+    fn add(self, rhs: U256) -> U256 {
+        self.checked_add(rhs).expect("uint256 addition overflow")
+    }
+}
+
+impl Sub for U256 {
+    type Output = U256;
+
+    fn sub(self, rhs: U256) -> U256 {
+        self.checked_sub(rhs).expect("uint256 subtraction overflow")
+    }
+}
+
+impl Mul for U256 {
+    type Output = U256;
+
+    fn mul(self, rhs: U256) -> U256 {
+        self.checked_mul(rhs).expect("uint256 multiplication overflow")
+    }
+}
+
+impl Div for U256 {
+    type Output = U256;
+
+    fn div(self, rhs: U256) -> U256 {
+        self.checked_div(rhs).expect("uint256 division by zero")
+    }
+}
+
+impl Rem for U256 {
+    type Output = U256;
+
+    fn rem(self, rhs: U256) -> U256 {
+        self.checked_rem(rhs).expect("uint256 division by zero")
+    }
+}
+
+/// 十进制
+impl fmt::Display for U256 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const TEN19: u64 = 10_000_000_000_000_000_000;
+
+        let mut chunks = Vec::new();
+        let mut value = *self;
+        loop {
+            let (quotient, remainder) = value.div_rem_u64(TEN19);
+            chunks.push(remainder);
+            if quotient.is_zero() {
+                break;
+            }
+            value = quotient;
+        }
+
+        let mut s = chunks.pop().unwrap().to_string();
+        for chunk in chunks.iter().rev() {
+            s.push_str(&format!("{:019}", chunk));
+        }
+        f.pad_integral(true, "", &s)
+    }
+}
+
+/// 不含前导零的 hex, {:#x} 带 0x 前缀
+impl fmt::LowerHex for U256 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let top = (0..4).rev().find(|&i| self.0[i] != 0).unwrap_or(0);
+        let mut s = format!("{:x}", self.0[top]);
+        for i in (0..top).rev() {
+            s.push_str(&format!("{:016x}", self.0[i]));
+        }
+        f.pad_integral(true, "0x", &s)
+    }
+}
+
+impl fmt::Debug for U256 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+/// 0x 开头按 hex 解析, 否则按十进制解析
+impl FromStr for U256 {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match strip_hex_prefix(s) {
+            Some(_) => U256::from_hex_str(s),
+            None => U256::from_dec_str(s),
+        }
+    }
+}
+
+impl Serialize for U256 {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&format!("{:#x}", self))
+    }
+}
+
+/// 接受 hex 字符串, 以及部分节点返回的十进制字符串或 JSON 整数
+impl<'de> Deserialize<'de> for U256 {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct U256Visitor;
+
+        impl<'de> de::Visitor<'de> for U256Visitor {
+            type Value = U256;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a uint256 as hex string or integer")
+            }
+
+            fn visit_u64<E: de::Error>(self, v: u64) -> Result<U256, E> {
+                Ok(U256::from(v))
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<U256, E> {
+                v.parse().map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_any(U256Visitor)
+    }
+}
+
+/// 64 位 quantity, 用于区块高度、gas、nonce、时间戳等
+#[derive(Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct U64(u64);
+
+impl U64 {
+    pub const ZERO: U64 = U64(0);
+
+    pub const fn as_u64(&self) -> u64 {
+        self.0
+    }
+}
+
+impl From<u64> for U64 {
+    fn from(value: u64) -> Self {
+        U64(value)
+    }
+}
+
+impl From<U64> for u64 {
+    fn from(value: U64) -> Self {
+        value.0
+    }
+}
+
+impl fmt::Display for U64 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl fmt::LowerHex for U64 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::LowerHex::fmt(&self.0, f)
+    }
+}
+
+impl fmt::Debug for U64 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+/// 0x 开头按 hex 解析, 否则按十进制解析
+impl FromStr for U64 {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let value = U256::from_str(s)?;
+        Ok(U64(u64::try_from(value)?))
+    }
+}
+
+impl Serialize for U64 {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&format!("{:#x}", self.0))
+    }
+}
+
+impl<'de> Deserialize<'de> for U64 {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = U256::deserialize(deserializer)?;
+        u64::try_from(value).map(U64).map_err(de::Error::custom)
+    }
+}
+
+// 定长字节类型: 地址、哈希等, 序列化为带 0x 前缀的小写 hex
+macro_rules! fixed_bytes {
+    ($(#[$doc:meta])* $name:ident, $len:expr) => {
+        $(#[$doc])*
+        #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        pub struct $name(pub [u8; $len]);
+
+        impl $name {
+            pub const LEN: usize = $len;
+
+            pub const fn zero() -> Self {
+                $name([0; $len])
+            }
+
+            pub fn from_slice(bytes: &[u8]) -> Result<Self, anyhow::Error> {
+                let bytes: [u8; $len] = bytes
+                    .try_into()
+                    .map_err(|_| anyhow!("expected {} bytes, got {}", $len, bytes.len()))?;
+                Ok($name(bytes))
+            }
+
+            pub fn as_bytes(&self) -> &[u8] {
+                &self.0
+            }
+        }
+
+        impl Default for $name {
+            fn default() -> Self {
+                $name::zero()
+            }
+        }
+
+        impl From<[u8; $len]> for $name {
+            fn from(bytes: [u8; $len]) -> Self {
+                $name(bytes)
+            }
+        }
+
+        impl AsRef<[u8]> for $name {
+            fn as_ref(&self) -> &[u8] {
+                &self.0
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "0x{}", hex::encode(self.0))
+            }
+        }
+
+        impl fmt::Debug for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                fmt::Display::fmt(self, f)
+            }
+        }
+
+        /// 可省略 0x 前缀, 不区分大小写
+        impl FromStr for $name {
+            type Err = anyhow::Error;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                let bytes = hex::decode(strip_hex_prefix(s).unwrap_or(s))
+                    .map_err(|err| anyhow!("invalid hex {:?}: {}", s, err))?;
+                $name::from_slice(&bytes)
+            }
+        }
+
+        impl Serialize for $name {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.collect_str(self)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let s = <std::borrow::Cow<str>>::deserialize(deserializer)?;
+                s.parse().map_err(de::Error::custom)
+            }
+        }
+    };
+}
+
+fixed_bytes!(
+    /// 20 字节地址
+    H160,
+    20
+);
+
+fixed_bytes!(
+    /// 32 字节哈希 (区块、交易、日志 topic、存储槽)
+    H256,
+    32
+);
+
+impl H160 {
+    /// EIP-55 校验和格式
+    pub fn to_checksum(&self) -> String {
+        eip55_address(&self.0)
+    }
+}
+
+/// 变长字节数据, 如交易 input、合约代码、eth_call 返回值. 空数据为 "0x"
+#[derive(Clone, Default, PartialEq, Eq, Hash)]
+pub struct Bytes(pub Vec<u8>);
+
+impl Bytes {
+    pub fn new() -> Self {
+        Bytes(Vec::new())
+    }
+
+    pub fn into_vec(self) -> Vec<u8> {
+        self.0
+    }
+}
+
+impl Deref for Bytes {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl AsRef<[u8]> for Bytes {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl From<Vec<u8>> for Bytes {
+    fn from(bytes: Vec<u8>) -> Self {
+        Bytes(bytes)
+    }
+}
+
+impl From<&[u8]> for Bytes {
+    fn from(bytes: &[u8]) -> Self {
+        Bytes(bytes.to_vec())
+    }
+}
+
+impl fmt::Display for Bytes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "0x{}", hex::encode(&self.0))
+    }
+}
+
+impl fmt::Debug for Bytes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+/// 可省略 0x 前缀, 长度须为偶数
+impl FromStr for Bytes {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        hex::decode(strip_hex_prefix(s).unwrap_or(s))
+            .map(Bytes)
+            .map_err(|err| anyhow!("invalid hex {:?}: {}", s, err))
+    }
+}
+
+impl Serialize for Bytes {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Bytes {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = <std::borrow::Cow<str>>::deserialize(deserializer)?;
+        s.parse().map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_u256() {
+        let max = "115792089237316195423570985008687907853269984665640564039457584007913129639935";
+        assert_eq!(U256::from_dec_str(max).unwrap(), U256::MAX);
+        assert_eq!(U256::MAX.to_string(), max);
+        assert_eq!(format!("{:#x}", U256::MAX), format!("0x{}", "f".repeat(64)));
+        assert!(
+            U256::from_dec_str("115792089237316195423570985008687907853269984665640564039457584007913129639936")
+                .is_err()
+        );
+        assert!(U256::from_hex_str(&format!("0x1{}", "0".repeat(64))).is_err());
+        assert!(U256::from_hex_str("0xzz").is_err());
+
+        // 1e30, 超过 i128 的代币余额
+        let wei = U256::from_str("0xc9f2c9cd04674edea40000000").unwrap();
+        assert_eq!(wei.to_string(), "1000000000000000000000000000000");
+        assert_eq!(wei.to_big_decimal(18), BigDecimal::from(10u64.pow(12)));
+        assert_eq!(
+            U256::from(1_500_000_000_000_000_000u64).to_big_decimal(18),
+            BigDecimal::from_str("1.5").unwrap()
+        );
+        assert_eq!(U256::from_hex_str("0x").unwrap(), U256::ZERO);
+        assert_eq!(U256::from_hex_str("0x000001").unwrap(), U256::ONE);
+        assert_eq!(format!("{:#x}", U256::ZERO), "0x0");
+
+        let a = U256::from(u128::MAX);
+        assert_eq!(a.checked_mul(a).unwrap() / a, a);
+        assert_eq!((a + U256::ONE) % U256::from(7u64), U256::from(4u64)); // 2^128 mod 7
+        assert_eq!(
+            U256::MAX.checked_div_rem(U256::MAX - U256::ONE).unwrap(),
+            (U256::ONE, U256::ONE)
+        );
+        assert!(U256::MAX.checked_add(U256::ONE).is_none());
+        assert!(U256::ZERO.checked_sub(U256::ONE).is_none());
+        assert!(U256::MAX.checked_mul(U256::from(2u64)).is_none());
+        assert!(U256::ONE.checked_div(U256::ZERO).is_none());
+        assert!(U256::MAX > a && u128::try_from(a).unwrap() == u128::MAX && u64::try_from(a).is_err());
+
+        let bytes = wei.to_be_bytes();
+        assert_eq!(U256::from_be_bytes(bytes), wei);
+        assert_eq!(U256::from_be_slice(&[0x01, 0x00]).unwrap(), U256::from(256u64));
+    }
+
+    #[test]
+    fn test_serde() {
+        let value: U256 = serde_json::from_value(json!("0xde0b6b3a7640000")).unwrap();
+        assert_eq!(value, U256::from(10u64.pow(18)));
+        assert_eq!(json!(value), json!("0xde0b6b3a7640000"));
+        assert_eq!(serde_json::from_value::<U256>(json!(16)).unwrap(), U256::from(16u64));
+
+        let height: U64 = serde_json::from_value(json!("0x1b4")).unwrap();
+        assert_eq!(height.as_u64(), 436);
+        assert_eq!(json!(height), json!("0x1b4"));
+        assert!(serde_json::from_value::<U64>(json!("0x10000000000000000")).is_err());
+
+        let address = "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed";
+        let h160: H160 = serde_json::from_value(json!(address)).unwrap();
+        assert_eq!(json!(h160), json!(address.to_lowercase()));
+        assert_eq!(h160.to_checksum(), address);
+        assert!(serde_json::from_value::<H160>(json!("0x5aaeb6")).is_err());
+
+        let hash = format!("0x{}", "ab".repeat(32));
+        assert_eq!(serde_json::from_value::<H256>(json!(hash)).unwrap().to_string(), hash);
+
+        let data: Bytes = serde_json::from_value(json!("0xa9059cbb")).unwrap();
+        assert_eq!(&data[..], &[0xa9, 0x05, 0x9c, 0xbb]);
+        assert_eq!(json!(Bytes::new()), json!("0x"));
+        assert!(serde_json::from_value::<Bytes>(json!("0xabc")).is_err());
+    }
+}
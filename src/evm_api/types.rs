@@ -1,3 +1,5 @@
+use serde::{de, Deserializer, Serializer};
+
 use super::*;
 
 
@@ -7,4 +9,385 @@ pub struct EthApiData<T> {
     pub jsonrpc: String,
     pub result: Option<T>,
     pub error: Option<Value>
-}
\ No newline at end of file
+}
+
+/// 区块参数: 高度或标签, 序列化为 "0x..." 或 "latest" 等
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockNumber {
+    Number(u64),
+    Earliest,
+    Latest,
+    Pending,
+    Safe,
+    Finalized,
+}
+
+impl Serialize for BlockNumber {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            BlockNumber::Number(number) => serializer.serialize_str(&format!("{:#x}", number)),
+            BlockNumber::Earliest => serializer.serialize_str("earliest"),
+            BlockNumber::Latest => serializer.serialize_str("latest"),
+            BlockNumber::Pending => serializer.serialize_str("pending"),
+            BlockNumber::Safe => serializer.serialize_str("safe"),
+            BlockNumber::Finalized => serializer.serialize_str("finalized"),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for BlockNumber {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        match value.as_str() {
+            "earliest" => Ok(BlockNumber::Earliest),
+            "latest" => Ok(BlockNumber::Latest),
+            "pending" => Ok(BlockNumber::Pending),
+            "safe" => Ok(BlockNumber::Safe),
+            "finalized" => Ok(BlockNumber::Finalized),
+            _ => value
+                .strip_prefix("0x")
+                .and_then(|hex| u64::from_str_radix(hex, 16).ok())
+                .map(BlockNumber::Number)
+                .ok_or_else(|| de::Error::custom(format!("invalid block number {}", value))),
+        }
+    }
+}
+
+impl From<u64> for BlockNumber {
+    fn from(number: u64) -> Self {
+        BlockNumber::Number(number)
+    }
+}
+
+// eth_getBlockByNumber / eth_getBlockByHash. 第二个参数为 false 时 transactions 为交易哈希, true 时为完整交易(BlockWithTxs)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Block<T = H256> {
+    pub number: U64,
+    pub hash: Option<H256>, // pending 区块为空
+    pub parent_hash: H256,
+    pub nonce: Option<Bytes>,
+    pub sha3_uncles: H256,
+    pub logs_bloom: Bytes,
+    pub transactions_root: H256,
+    pub state_root: H256,
+    pub receipts_root: H256,
+    pub miner: H160,
+    pub difficulty: U256,
+    pub total_difficulty: Option<U256>, // 合并后部分客户端不再返回
+    pub extra_data: Bytes,
+    pub size: U64,
+    pub gas_limit: U64,
+    pub gas_used: U64,
+    pub timestamp: U64,
+    pub mix_hash: Option<H256>,
+    pub transactions: Vec<T>,
+    #[serde(default)]
+    pub uncles: Vec<H256>,
+    pub base_fee_per_gas: Option<U256>, // London
+    pub withdrawals_root: Option<H256>, // Shanghai
+    pub withdrawals: Option<Vec<Withdrawal>>, // Shanghai
+    pub blob_gas_used: Option<U64>, // Cancun
+    pub excess_blob_gas: Option<U64>, // Cancun
+    pub parent_beacon_block_root: Option<H256>, // Cancun
+    pub requests_hash: Option<H256>, // Prague
+}
+
+pub type BlockWithTxs = Block<Transaction>;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Withdrawal {
+    pub index: U64,
+    pub validator_index: U64,
+    pub address: H160,
+    pub amount: U64, // Gwei
+}
+
+/// 交易类型 (EIP-2718)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TxType {
+    Legacy,
+    /// EIP-2930
+    AccessList,
+    /// EIP-1559
+    DynamicFee,
+    /// EIP-4844
+    Blob,
+    /// EIP-7702
+    SetCode,
+    /// L2 等链自定义的类型
+    Other(u64),
+}
+
+impl TxType {
+    pub fn from_u64(value: u64) -> Self {
+        match value {
+            0 => TxType::Legacy,
+            1 => TxType::AccessList,
+            2 => TxType::DynamicFee,
+            3 => TxType::Blob,
+            4 => TxType::SetCode,
+            other => TxType::Other(other),
+        }
+    }
+}
+
+// eth_getTransactionByHash, 各类型交易共用, 不适用的字段为空
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Transaction {
+    pub hash: H256,
+    pub r#type: Option<U64>, // Berlin 之前的节点不返回, 视为 legacy
+    pub nonce: U64,
+    pub block_hash: Option<H256>, // 交易池中的交易为空
+    pub block_number: Option<U64>,
+    pub transaction_index: Option<U64>,
+    pub from: H160,
+    pub to: Option<H160>, // 创建合约时为空
+    pub value: U256,
+    pub gas: U64,
+    pub gas_price: Option<U256>, // legacy/2930; 已打包的 1559 交易为实际 gas 价格
+    pub max_fee_per_gas: Option<U256>, // 1559/4844
+    pub max_priority_fee_per_gas: Option<U256>, // 1559/4844
+    pub max_fee_per_blob_gas: Option<U256>, // 4844
+    pub blob_versioned_hashes: Option<Vec<H256>>, // 4844
+    pub access_list: Option<Vec<AccessListItem>>, // 2930 及之后
+    pub chain_id: Option<U64>, // EIP-155 之前的 legacy 交易为空
+    pub input: Bytes,
+    pub v: Option<U64>,
+    pub r: Option<U256>,
+    pub s: Option<U256>,
+    pub y_parity: Option<U64>,
+}
+
+impl Transaction {
+    pub fn tx_type(&self) -> TxType {
+        TxType::from_u64(self.r#type.map(|value| value.as_u64()).unwrap_or(0))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccessListItem {
+    pub address: H160,
+    pub storage_keys: Vec<H256>,
+}
+
+// eth_getTransactionReceipt / eth_getBlockReceipts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Receipt {
+    pub transaction_hash: H256,
+    pub transaction_index: U64,
+    pub block_hash: H256,
+    pub block_number: U64,
+    pub from: H160,
+    pub to: Option<H160>,
+    pub r#type: Option<U64>,
+    pub cumulative_gas_used: U64,
+    pub gas_used: U64,
+    pub effective_gas_price: Option<U256>, // London 之前的节点不返回
+    pub contract_address: Option<H160>,
+    pub logs: Vec<Log>,
+    pub logs_bloom: Bytes,
+    pub status: Option<U64>, // Byzantium 之前为 root
+    pub root: Option<H256>,
+    pub blob_gas_used: Option<U64>, // 4844
+    pub blob_gas_price: Option<U256>, // 4844
+}
+
+impl Receipt {
+    /// 执行是否成功, Byzantium 之前的收据无 status 时为 None
+    pub fn is_success(&self) -> Option<bool> {
+        self.status.map(|status| status.as_u64() == 1)
+    }
+}
+
+// eth_getLogs 及收据中的日志. 交易池中的日志区块相关字段为空
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Log {
+    pub address: H160,
+    pub topics: Vec<H256>,
+    pub data: Bytes,
+    pub block_number: Option<U64>,
+    pub block_hash: Option<H256>,
+    pub block_timestamp: Option<U64>,
+    pub transaction_hash: Option<H256>,
+    pub transaction_index: Option<U64>,
+    pub log_index: Option<U64>,
+    #[serde(default)]
+    pub removed: bool, // 分叉回滚时为 true
+}
+
+/// eth_getLogs 的过滤条件, block_hash 与 from_block/to_block 互斥
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogFilter {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from_block: Option<BlockNumber>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub to_block: Option<BlockNumber>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub block_hash: Option<H256>,
+    /// 合约地址, 多个时为或
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub address: Vec<H160>,
+    /// 按位置匹配, None 表示该位置不限, 同一位置多个值为或
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub topics: Vec<Option<Vec<H256>>>,
+}
+
+// eth_call / eth_estimateGas 的交易参数, 为空的字段不传
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CallRequest {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from: Option<H160>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub to: Option<H160>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gas: Option<U64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gas_price: Option<U256>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_fee_per_gas: Option<U256>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_priority_fee_per_gas: Option<U256>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<U256>,
+    /// 调用数据, 使用 data 字段名以兼容旧节点
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<Bytes>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nonce: Option<U64>,
+}
+
+// eth_feeHistory
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeeHistory {
+    pub oldest_block: U64,
+    /// 比请求的区块数多一个, 最后一个为下一区块的 base fee
+    pub base_fee_per_gas: Vec<U256>,
+    pub gas_used_ratio: Vec<f64>,
+    pub reward: Option<Vec<Vec<U256>>>, // 请求 reward_percentiles 时返回
+    pub base_fee_per_blob_gas: Option<Vec<U256>>, // Cancun
+    pub blob_gas_used_ratio: Option<Vec<f64>>, // Cancun
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hash(n: u8) -> String {
+        format!("0x{}", hex::encode([n; 32]))
+    }
+
+    fn address(n: u8) -> String {
+        format!("0x{}", hex::encode([n; 20]))
+    }
+
+    #[test]
+    fn test_block_number() {
+        assert_eq!(json!([BlockNumber::Number(255), BlockNumber::Finalized]), json!(["0xff", "finalized"]));
+        assert_eq!(serde_json::from_value::<BlockNumber>(json!("0x10")).unwrap(), BlockNumber::Number(16));
+        assert!(serde_json::from_value::<BlockNumber>(json!("16")).is_err());
+
+        let filter = LogFilter {
+            from_block: Some(1.into()),
+            to_block: Some(BlockNumber::Latest),
+            topics: vec![Some(vec![H256([0xdd; 32])]), None],
+            ..Default::default()
+        };
+        assert_eq!(
+            serde_json::to_value(&filter).unwrap(),
+            json!({"fromBlock": "0x1", "toBlock": "latest", "topics": [[hash(0xdd)], null]})
+        );
+
+        let call = CallRequest {
+            to: Some(H160([0xa2; 20])),
+            value: Some(U256::from(10u64.pow(18))),
+            data: Some(Bytes(vec![0x70, 0xa0, 0x82, 0x31])),
+            ..Default::default()
+        };
+        assert_eq!(
+            serde_json::to_value(&call).unwrap(),
+            json!({"to": address(0xa2), "value": "0xde0b6b3a7640000", "data": "0x70a08231"})
+        );
+    }
+
+    #[test]
+    fn test_transaction() {
+        let legacy = json!({
+            "hash": hash(1), "nonce": "0x0", "blockHash": hash(0xb1), "blockNumber": "0x1", "transactionIndex": "0x0",
+            "from": address(0xa1), "to": address(0xa2), "value": "0xc9f2c9cd04674edea40000000", "gas": "0x5208",
+            "gasPrice": "0x1", "input": "0x", "v": "0x1b", "r": "0x1", "s": "0x1"
+        });
+        let tx = serde_json::from_value::<Transaction>(legacy).unwrap();
+        assert_eq!(tx.tx_type(), TxType::Legacy);
+        assert!(tx.chain_id.is_none() && tx.input.is_empty());
+        // 超过 i128 的金额
+        assert_eq!(tx.value.to_string(), "1000000000000000000000000000000");
+
+        let blob = json!({
+            "type": "0x3", "hash": hash(3), "nonce": "0x5", "blockHash": null, "blockNumber": null,
+            "transactionIndex": null, "from": address(0xa1), "to": address(0xa2), "value": "0x0", "gas": "0x5208",
+            "maxFeePerGas": "0x2", "maxPriorityFeePerGas": "0x1", "maxFeePerBlobGas": "0x3",
+            "blobVersionedHashes": [hash(0x01)], "accessList": [{"address": address(0xa3), "storageKeys": [hash(0)]}],
+            "chainId": "0x1", "input": "0x", "v": "0x0", "r": "0x1", "s": "0x1", "yParity": "0x0"
+        });
+        let tx = serde_json::from_value::<Transaction>(blob).unwrap();
+        assert_eq!(tx.tx_type(), TxType::Blob);
+        assert_eq!(tx.blob_versioned_hashes.unwrap().len(), 1);
+        assert_eq!(tx.access_list.unwrap()[0].storage_keys, vec![H256::zero()]);
+        assert!(tx.block_hash.is_none());
+
+        let deposit = json!({
+            "type": "0x7e", "hash": hash(0x7e), "nonce": "0x0", "from": address(0xa1), "to": null, "value": "0x0",
+            "gas": "0xf4240", "input": "0x"
+        });
+        assert_eq!(serde_json::from_value::<Transaction>(deposit).unwrap().tx_type(), TxType::Other(0x7e));
+    }
+
+    #[test]
+    fn test_block_and_receipt() {
+        let tx = json!({
+            "type": "0x2", "hash": hash(2), "nonce": "0x1", "blockHash": hash(0xb1), "blockNumber": "0x10",
+            "transactionIndex": "0x0", "from": address(0xa1), "to": address(0xa2), "value": "0x0", "gas": "0x5208",
+            "gasPrice": "0x3", "maxFeePerGas": "0x4", "maxPriorityFeePerGas": "0x1", "accessList": [],
+            "chainId": "0x1", "input": "0xa9059cbb", "v": "0x1", "r": "0x1", "s": "0x1", "yParity": "0x1"
+        });
+        let block = json!({
+            "number": "0x10", "hash": hash(0xb1), "parentHash": hash(0xb0), "nonce": "0x0000000000000000",
+            "sha3Uncles": hash(0x1d), "logsBloom": "0x00", "transactionsRoot": hash(1), "stateRoot": hash(2),
+            "receiptsRoot": hash(3), "miner": address(0xa0), "difficulty": "0x0", "extraData": "0x", "size": "0x220",
+            "gasLimit": "0x1c9c380", "gasUsed": "0x5208", "timestamp": "0x6553f100", "mixHash": hash(4),
+            "transactions": [tx], "uncles": [], "baseFeePerGas": "0x2", "withdrawalsRoot": hash(5),
+            "withdrawals": [{"index": "0x1", "validatorIndex": "0x2", "address": address(0xa4), "amount": "0x3"}],
+            "blobGasUsed": "0x0", "excessBlobGas": "0x0", "parentBeaconBlockRoot": hash(6)
+        });
+        let block = serde_json::from_value::<BlockWithTxs>(block).unwrap();
+        assert_eq!(block.transactions[0].tx_type(), TxType::DynamicFee);
+        assert_eq!(block.timestamp.as_u64(), 1700000000);
+        assert!(block.total_difficulty.is_none());
+        assert_eq!(block.withdrawals.unwrap()[0].validator_index, 2.into());
+
+        let receipt = json!({
+            "transactionHash": hash(2), "transactionIndex": "0x0", "blockHash": hash(0xb1), "blockNumber": "0x10",
+            "from": address(0xa1), "to": address(0xa2), "type": "0x2", "cumulativeGasUsed": "0x5208",
+            "gasUsed": "0x5208", "effectiveGasPrice": "0x3", "contractAddress": null, "logsBloom": "0x00",
+            "status": "0x1",
+            "logs": [{
+                "address": address(0xa2), "topics": [hash(0xdd), hash(1), hash(2)], "data": "0x64",
+                "blockNumber": "0x10", "blockHash": hash(0xb1), "transactionHash": hash(2),
+                "transactionIndex": "0x0", "logIndex": "0x0", "removed": false
+            }]
+        });
+        let receipt = serde_json::from_value::<Receipt>(receipt).unwrap();
+        assert_eq!(receipt.is_success(), Some(true));
+        assert_eq!(receipt.logs[0].topics.len(), 3);
+        assert_eq!(U256::from_be_slice(&receipt.logs[0].data), Some(U256::from(100u64)));
+    }
+}
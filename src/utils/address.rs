@@ -0,0 +1,537 @@
+use std::{fmt, str::FromStr};
+
+use base58::{FromBase58, FromBase58Error};
+use serde::{Deserialize, Serialize};
+
+use crate::btc_client::{
+    consensus::sha256d,
+    network::{Coin, Network},
+    script::ScriptType,
+};
+
+use super::encoding::{convert_b58encode, convert_bits, eip55_address, segwit_decode, segwit_encode, BECH32_CHARSET};
+
+/// 地址所属的链, UTXO 链均为主网
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Chain {
+    Btc,
+    Ltc,
+    Doge,
+    /// 以 CashAddr 为规范格式, 兼容旧 Base58 地址
+    Bch,
+    /// 以太坊及兼容链
+    Evm,
+    Tron,
+}
+
+impl Chain {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Chain::Btc => "btc",
+            Chain::Ltc => "ltc",
+            Chain::Doge => "doge",
+            Chain::Bch => "bch",
+            Chain::Evm => "evm",
+            Chain::Tron => "tron",
+        }
+    }
+
+    pub fn is_utxo(&self) -> bool {
+        matches!(self, Chain::Btc | Chain::Ltc | Chain::Doge | Chain::Bch)
+    }
+
+    // Base58 版本字节 (P2PKH, P2SH), 首个 P2SH 前缀为规范格式
+    fn base58_prefixes(&self) -> (u8, &'static [u8]) {
+        match self {
+            Chain::Btc | Chain::Bch => (0x00, &[0x05]),
+            // 莱特币早期 P2SH 与比特币共用 0x05
+            Chain::Ltc => (0x30, &[0x32, 0x05]),
+            Chain::Doge => (0x1e, &[0x16]),
+            Chain::Evm | Chain::Tron => (0, &[]),
+        }
+    }
+
+    /// 对应的 Bitcoin Core 兼容链, 账户链为 None
+    pub fn coin(&self) -> Option<Coin> {
+        match self {
+            Chain::Btc => Some(Coin::Bitcoin),
+            Chain::Ltc => Some(Coin::Litecoin),
+            Chain::Doge => Some(Coin::Dogecoin),
+            Chain::Bch => Some(Coin::BitcoinCash),
+            Chain::Evm | Chain::Tron => None,
+        }
+    }
+
+    fn bech32_hrp(&self) -> Option<&'static str> {
+        self.coin().and_then(|coin| coin.params(Network::Bitcoin).bech32_hrp)
+    }
+}
+
+impl fmt::Display for Chain {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Chain {
+    type Err = AddressError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "btc" | "bitcoin" => Ok(Chain::Btc),
+            "ltc" | "litecoin" => Ok(Chain::Ltc),
+            "doge" | "dogecoin" => Ok(Chain::Doge),
+            "bch" | "bitcoincash" => Ok(Chain::Bch),
+            "evm" | "eth" | "ethereum" => Ok(Chain::Evm),
+            "tron" | "trx" => Ok(Chain::Tron),
+            _ => Err(AddressError::UnknownChain(s.to_string())),
+        }
+    }
+}
+
+/// 地址解析和转换错误
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AddressError {
+    UnknownChain(String),
+    /// 含有编码字符集以外的字符
+    InvalidCharacter(char),
+    InvalidChecksum,
+    /// 解码后长度不符
+    InvalidLength(usize),
+    /// 版本字节或 hrp 不属于该链
+    InvalidPrefix(String),
+    /// bech32/隔离见证格式错误
+    InvalidSegwit(String),
+    /// 大小写混合但不符合 EIP-55, 或 bech32 大小写混合
+    MixedCase,
+    UnsupportedConversion {
+        from: Chain,
+        to: Chain,
+    },
+}
+
+impl fmt::Display for AddressError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AddressError::UnknownChain(chain) => write!(f, "unknown chain {}", chain),
+            AddressError::InvalidCharacter(c) => write!(f, "invalid character '{}'", c),
+            AddressError::InvalidChecksum => write!(f, "invalid checksum"),
+            AddressError::InvalidLength(len) => write!(f, "invalid address length {}", len),
+            AddressError::InvalidPrefix(prefix) => write!(f, "invalid address prefix {}", prefix),
+            AddressError::InvalidSegwit(err) => write!(f, "invalid segwit address: {}", err),
+            AddressError::MixedCase => write!(f, "invalid mixed-case checksum"),
+            AddressError::UnsupportedConversion { from, to } => {
+                write!(f, "cannot convert {} address to {}", from, to)
+            }
+        }
+    }
+}
+
+impl std::error::Error for AddressError {}
+
+/// 地址类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AddressType {
+    P2pkh,
+    P2sh,
+    /// 隔离见证 (v0) 及 taproot (v1) 等
+    Witness {
+        version: u8,
+    },
+    /// EVM / Tron 账户地址
+    Account,
+}
+
+/// 多链地址. 解析时校验格式和校验和, Display 输出规范格式:
+/// bech32 小写、BCH CashAddr 带前缀、EVM 为 EIP-55、Tron 为 Base58
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Address {
+    pub chain: Chain,
+    pub address_type: AddressType,
+    /// P2PKH/P2SH/账户地址为 20 字节哈希, 隔离见证为见证程序
+    pub payload: Vec<u8>,
+}
+
+const TRON_PREFIX: u8 = 0x41;
+const CASHADDR_PREFIX: &str = "bitcoincash";
+/// Base58Check 解码, 返回去掉校验和的数据
+pub fn base58check_decode(s: &str) -> Result<Vec<u8>, AddressError> {
+    let mut raw = s.from_base58().map_err(|err| match err {
+        FromBase58Error::InvalidBase58Character(c, _) => AddressError::InvalidCharacter(c),
+        FromBase58Error::InvalidBase58Length => AddressError::InvalidLength(s.len()),
+    })?;
+    if raw.len() < 4 {
+        return Err(AddressError::InvalidLength(raw.len()));
+    }
+
+    let check = raw.split_off(raw.len() - 4);
+    if sha256d(&raw)[..4] != check[..] {
+        return Err(AddressError::InvalidChecksum);
+    }
+    Ok(raw)
+}
+
+fn cashaddr_polymod(values: &[u8]) -> u64 {
+    const GEN: [u64; 5] = [0x98f2bc8e61, 0x79b76d99e2, 0xf33e5fb3c4, 0xae2eabe2a8, 0x1e4f43e470];
+
+    let mut chk: u64 = 1;
+    for &value in values {
+        let top = chk >> 35;
+        chk = ((chk & 0x07ffffffff) << 5) ^ value as u64;
+        for (i, g) in GEN.iter().enumerate() {
+            if (top >> i) & 1 == 1 {
+                chk ^= g;
+            }
+        }
+    }
+    chk ^ 1
+}
+
+fn cashaddr_prefix_expand(prefix: &str) -> Vec<u8> {
+    let mut values = prefix.bytes().map(|b| b & 31).collect::<Vec<_>>();
+    values.push(0);
+    values
+}
+
+// 8 位与 5 位分组互转, 与 bech32 相同
+fn regroup(data: &[u8], from: u32, to: u32, pad: bool) -> Result<Vec<u8>, AddressError> {
+    convert_bits(data, from, to, pad).map_err(|_| AddressError::InvalidLength(data.len()))
+}
+
+pub(crate) fn cashaddr_encode(prefix: &str, address_type: AddressType, hash: &[u8]) -> String {
+    // 版本字节: 类型 (0 P2PKH, 1 P2SH) << 3, 低 3 位为长度, 0 表示 160 位
+    let version = if address_type == AddressType::P2sh { 8 } else { 0 };
+    let mut payload = vec![version];
+    payload.extend(hash);
+    let mut data = regroup(&payload, 8, 5, true).unwrap_or_default();
+
+    let mut values = cashaddr_prefix_expand(prefix);
+    values.extend(&data);
+    values.extend([0; 8]);
+    let chk = cashaddr_polymod(&values);
+    data.extend((0..8).map(|i| ((chk >> (5 * (7 - i))) & 31) as u8));
+
+    let encoded = data
+        .iter()
+        .map(|&d| BECH32_CHARSET[d as usize] as char)
+        .collect::<String>();
+    format!("{}:{}", prefix, encoded)
+}
+
+// 前缀可省略, 只支持 160 位哈希
+pub(crate) fn cashaddr_decode(expected_prefix: &str, s: &str) -> Result<(AddressType, Vec<u8>), AddressError> {
+    if s.chars().any(|c| c.is_ascii_lowercase()) && s.chars().any(|c| c.is_ascii_uppercase()) {
+        return Err(AddressError::MixedCase);
+    }
+    let s = s.to_lowercase();
+    let (prefix, payload) = s.split_once(':').unwrap_or((expected_prefix, &s));
+    if prefix != expected_prefix {
+        return Err(AddressError::InvalidPrefix(prefix.to_string()));
+    }
+
+    let data = payload
+        .chars()
+        .map(|c| {
+            BECH32_CHARSET
+                .iter()
+                .position(|&b| b as char == c)
+                .map(|v| v as u8)
+                .ok_or(AddressError::InvalidCharacter(c))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    if data.len() < 8 {
+        return Err(AddressError::InvalidLength(data.len()));
+    }
+
+    let mut values = cashaddr_prefix_expand(prefix);
+    values.extend(&data);
+    if cashaddr_polymod(&values) != 0 {
+        return Err(AddressError::InvalidChecksum);
+    }
+
+    let payload = regroup(&data[..data.len() - 8], 5, 8, false)?;
+    if payload.len() != 21 {
+        return Err(AddressError::InvalidLength(payload.len()));
+    }
+    let address_type = match payload[0] {
+        0 => AddressType::P2pkh,
+        8 => AddressType::P2sh,
+        version => return Err(AddressError::InvalidPrefix(format!("cashaddr version {}", version))),
+    };
+    Ok((address_type, payload[1..].to_vec()))
+}
+
+// 0x 开头的 40 位 hex, 大小写混合时校验 EIP-55
+fn parse_evm(s: &str) -> Result<Vec<u8>, AddressError> {
+    let hex_part = s
+        .strip_prefix("0x")
+        .or_else(|| s.strip_prefix("0X"))
+        .ok_or_else(|| AddressError::InvalidPrefix(s.chars().take(2).collect()))?;
+    if hex_part.len() != 40 {
+        return Err(AddressError::InvalidLength(hex_part.len()));
+    }
+    if let Some(c) = hex_part.chars().find(|c| !c.is_ascii_hexdigit()) {
+        return Err(AddressError::InvalidCharacter(c));
+    }
+
+    let payload = hex::decode(hex_part).map_err(|_| AddressError::InvalidLength(hex_part.len()))?;
+    let mixed = hex_part.chars().any(|c| c.is_ascii_lowercase()) && hex_part.chars().any(|c| c.is_ascii_uppercase());
+    if mixed && eip55_address(&payload)[2..] != *hex_part {
+        return Err(AddressError::MixedCase);
+    }
+    Ok(payload)
+}
+
+// Base58 地址, 兼容 41 开头的 hex 格式
+fn parse_tron(s: &str) -> Result<Vec<u8>, AddressError> {
+    let raw = if s.len() == 42 && s.chars().all(|c| c.is_ascii_hexdigit()) {
+        hex::decode(s).map_err(|_| AddressError::InvalidLength(s.len()))?
+    } else {
+        base58check_decode(s)?
+    };
+
+    if raw.len() != 21 {
+        return Err(AddressError::InvalidLength(raw.len()));
+    }
+    if raw[0] != TRON_PREFIX {
+        return Err(AddressError::InvalidPrefix(format!("{:#04x}", raw[0])));
+    }
+    Ok(raw[1..].to_vec())
+}
+
+impl Address {
+    pub fn parse(chain: Chain, s: &str) -> Result<Self, AddressError> {
+        let s = s.trim();
+        let (address_type, payload) = match chain {
+            Chain::Evm => (AddressType::Account, parse_evm(s)?),
+            Chain::Tron => (AddressType::Account, parse_tron(s)?),
+            Chain::Bch if s.contains(':') || s.starts_with(['q', 'p', 'Q', 'P']) => cashaddr_decode(CASHADDR_PREFIX, s)?,
+            _ => Self::parse_utxo(chain, s)?,
+        };
+
+        Ok(Address {
+            chain,
+            address_type,
+            payload,
+        })
+    }
+
+    fn parse_utxo(chain: Chain, s: &str) -> Result<(AddressType, Vec<u8>), AddressError> {
+        if let Some(hrp) = chain.bech32_hrp() {
+            if s.to_lowercase().starts_with(&format!("{}1", hrp)) {
+                let (version, program) = segwit_decode(hrp, s).map_err(AddressError::InvalidSegwit)?;
+                return Ok((AddressType::Witness { version }, program));
+            }
+        }
+
+        let raw = base58check_decode(s)?;
+        if raw.len() != 21 {
+            return Err(AddressError::InvalidLength(raw.len()));
+        }
+
+        let (p2pkh, p2sh) = chain.base58_prefixes();
+        let address_type = match raw[0] {
+            prefix if prefix == p2pkh => AddressType::P2pkh,
+            prefix if p2sh.contains(&prefix) => AddressType::P2sh,
+            prefix => return Err(AddressError::InvalidPrefix(format!("{:#04x}", prefix))),
+        };
+        Ok((address_type, raw[1..].to_vec()))
+    }
+
+    pub fn is_valid(chain: Chain, s: &str) -> bool {
+        Self::parse(chain, s).is_ok()
+    }
+
+    /// 同一账户在 EVM 与 Tron 间转换, 其他链之间不可转换
+    pub fn to_chain(&self, chain: Chain) -> Result<Self, AddressError> {
+        match (self.chain, chain) {
+            (from, to) if from == to => Ok(self.clone()),
+            (Chain::Evm, Chain::Tron) | (Chain::Tron, Chain::Evm) => Ok(Address { chain, ..self.clone() }),
+            (from, to) => Err(AddressError::UnsupportedConversion { from, to }),
+        }
+    }
+
+    /// UTXO 地址对应的 scriptPubKey, 账户地址为 None
+    pub fn script_pubkey(&self) -> Option<Vec<u8>> {
+        let script_type = match self.address_type {
+            AddressType::P2pkh => ScriptType::P2pkh {
+                hash: self.payload.as_slice().try_into().ok()?,
+            },
+            AddressType::P2sh => ScriptType::P2sh {
+                hash: self.payload.as_slice().try_into().ok()?,
+            },
+            AddressType::Witness { version } => ScriptType::WitnessUnknown {
+                version,
+                program: self.payload.clone(),
+            },
+            AddressType::Account => return None,
+        };
+        script_type.script_pubkey()
+    }
+}
+
+impl fmt::Display for Address {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (p2pkh, p2sh) = self.chain.base58_prefixes();
+        let base58 = |prefix: u8| {
+            let mut raw = vec![prefix];
+            raw.extend(&self.payload);
+            convert_b58encode(raw)
+        };
+
+        let address = match (self.chain, self.address_type) {
+            (Chain::Evm, _) => eip55_address(&self.payload),
+            (Chain::Tron, _) => base58(TRON_PREFIX),
+            (Chain::Bch, address_type) => cashaddr_encode(CASHADDR_PREFIX, address_type, &self.payload),
+            (_, AddressType::Witness { version }) => {
+                segwit_encode(self.chain.bech32_hrp().unwrap_or_default(), version, &self.payload)
+                    .map_err(|_| fmt::Error)?
+            }
+            (_, AddressType::P2sh) => base58(p2sh[0]),
+            _ => base58(p2pkh),
+        };
+        f.write_str(&address)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_utxo() {
+        let cases = [
+            (
+                Chain::Btc,
+                "1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa",
+                "1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa",
+            ),
+            (
+                Chain::Btc,
+                "BC1QW508D6QEJXTDG4Y5R3ZARVARY0C5XW7KV8F3T4",
+                "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4",
+            ),
+            // 旧格式 P2SH 转为 M 开头
+            (
+                Chain::Ltc,
+                "3CWFddi6m4ndiGyKqzYvsFYagqDLPVMTzC",
+                "MJiPwX84iBe4WnFDwsYGgtnz1XonPhUqhf",
+            ),
+            (
+                Chain::Bch,
+                "1BpEi6DfDAUFd7GtittLSdBeYJvcoaVggu",
+                "bitcoincash:qpm2qsznhks23z7629mms6s4cwef74vcwvy22gdx6a",
+            ),
+            (
+                Chain::Bch,
+                "ppm2qsznhks23z7629mms6s4cwef74vcwvn0h829pq",
+                "bitcoincash:ppm2qsznhks23z7629mms6s4cwef74vcwvn0h829pq",
+            ),
+            (
+                Chain::Bch,
+                "BITCOINCASH:QPM2QSZNHKS23Z7629MMS6S4CWEF74VCWVY22GDX6A",
+                "bitcoincash:qpm2qsznhks23z7629mms6s4cwef74vcwvy22gdx6a",
+            ),
+        ];
+        for (chain, input, canonical) in cases {
+            let address = Address::parse(chain, input).unwrap();
+            assert_eq!(address.to_string(), canonical, "{}", input);
+            assert_eq!(Address::parse(chain, canonical).unwrap(), address);
+        }
+
+        // BCH 旧地址与 CashAddr 对应相同脚本
+        let legacy = Address::parse(Chain::Btc, "3CWFddi6m4ndiGyKqzYvsFYagqDLPVMTzC").unwrap();
+        let cash = Address::parse(Chain::Bch, "bitcoincash:ppm2qsznhks23z7629mms6s4cwef74vcwvn0h829pq").unwrap();
+        assert_eq!(legacy.script_pubkey(), cash.script_pubkey());
+
+        let taproot = "bc1p5cyxnuxmeuwuvkwfem96lqzszd02n6xdcjrs20cac6yqjjwudpxqkedrcr";
+        assert_eq!(
+            hex::encode(Address::parse(Chain::Btc, taproot).unwrap().script_pubkey().unwrap()),
+            "5120a60869f0dbcf1dc659c9cecbaf8050135ea9e8cdc487053f1dc6880949dc684c"
+        );
+    }
+
+    #[test]
+    fn test_evm_tron() {
+        let eth = Address::parse(Chain::Evm, "0xa614f803b6fd780986a42c78ec9c7f77e6ded13c").unwrap();
+        assert_eq!(eth.to_string(), "0xa614f803B6FD780986A42c78Ec9c7f77e6DeD13C");
+        assert_eq!(
+            eth.to_chain(Chain::Tron).unwrap().to_string(),
+            "TR7NHqjeKQxGTCi8q8ZY4pL8otSzgjLj6t"
+        );
+
+        let tron = Address::parse(Chain::Tron, "41a614f803b6fd780986a42c78ec9c7f77e6ded13c").unwrap();
+        assert_eq!(tron.to_chain(Chain::Evm).unwrap(), eth);
+        assert_eq!(tron.script_pubkey(), None);
+        assert_eq!(
+            eth.to_chain(Chain::Btc),
+            Err(AddressError::UnsupportedConversion {
+                from: Chain::Evm,
+                to: Chain::Btc
+            })
+        );
+    }
+
+    #[test]
+    fn test_invalid() {
+        let cases = [
+            (
+                Chain::Btc,
+                "1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNb",
+                AddressError::InvalidChecksum,
+            ),
+            (
+                Chain::Btc,
+                "1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfN0",
+                AddressError::InvalidCharacter('0'),
+            ),
+            (
+                Chain::Ltc,
+                "1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa",
+                AddressError::InvalidPrefix("0x00".to_string()),
+            ),
+            (
+                Chain::Doge,
+                "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4",
+                AddressError::InvalidCharacter('0'),
+            ),
+            (
+                Chain::Bch,
+                "bitcoincash:qpm2qsznhks23z7629mms6s4cwef74vcwvy22gdx6q",
+                AddressError::InvalidChecksum,
+            ),
+            (
+                Chain::Evm,
+                "0xA614f803b6fd780986a42c78ec9c7f77e6ded13c",
+                AddressError::MixedCase,
+            ),
+            (
+                Chain::Evm,
+                "0xa614f803b6fd780986a42c78ec9c7f77e6ded1",
+                AddressError::InvalidLength(38),
+            ),
+            (
+                Chain::Evm,
+                "a614f803b6fd780986a42c78ec9c7f77e6ded13c",
+                AddressError::InvalidPrefix("a6".to_string()),
+            ),
+            (
+                Chain::Tron,
+                "1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa",
+                AddressError::InvalidPrefix("0x00".to_string()),
+            ),
+        ];
+        for (chain, input, err) in cases {
+            assert_eq!(Address::parse(chain, input), Err(err), "{}", input);
+        }
+        assert!(matches!(
+            Address::parse(Chain::Btc, "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t5"),
+            Err(AddressError::InvalidSegwit(_))
+        ));
+        assert_eq!(
+            "sol".parse::<Chain>(),
+            Err(AddressError::UnknownChain("sol".to_string()))
+        );
+    }
+}
@@ -1,85 +1,184 @@
-use digest::Digest;
-use hex::decode;
-use sha2::Sha256;
-use base58::{ToBase58, FromBase58};
-use sha3::Keccak256;
-
-pub fn eth2trx(address: &str) -> String {
-    let addr = address.replace("0x", "41");    
-    let h = decode(addr).unwrap();    
-    convert_b58encode(h)
-}
-
-pub fn convert_b58encode<T: AsRef<[u8]>>(raw: T) -> String {
-    let mut hasher = Sha256::new();
-    hasher.update(raw.as_ref());
-    let digest1 = hasher.finalize();
+use crate::btc_client::network::NetworkParams;
 
-    let mut hasher = Sha256::new();
-    hasher.update(&digest1);
-    let digest = hasher.finalize();
+use super::address::{base58check_decode, cashaddr_decode, Address, AddressError, AddressType, Chain};
+pub use super::encoding::{
+    bech32_decode, bech32_encode, convert_b58encode, convert_bits, eip55_address, hash160, segwit_decode, segwit_encode,
+    Bech32Variant,
+};
 
-    let mut raw = raw.as_ref().to_owned();
-    raw.extend(&digest[..4]);
-    raw.to_base58()
+/// 以太坊地址转为波场地址, 大小写混合时校验 EIP-55
+pub fn eth2trx(address: &str) -> Result<String, AddressError> {
+    Ok(Address::parse(Chain::Evm, address)?.to_chain(Chain::Tron)?.to_string())
 }
 
 pub fn convert_prefix(address: &str) -> String {
     address.replace("0x", "41")
 }
 
-pub fn trx2eth(addr: &str) -> Result<String, String> {
-    let mut addr_vec_u8 = b58decode_check(addr)?;
-    addr_vec_u8 = addr_vec_u8[1..].to_vec();
-    let mut addr = format!("0x{}", hex::encode(addr_vec_u8));
-    eip55_checksum(unsafe { &mut addr.as_bytes_mut()[2..] });
-    
-    Ok(addr.to_lowercase())
+/// 波场地址转为 EIP-55 格式的以太坊地址
+pub fn trx2eth(addr: &str) -> Result<String, AddressError> {
+    Ok(Address::parse(Chain::Tron, addr)?.to_chain(Chain::Evm)?.to_string())
 }
 
 /// Base58check decode.
 pub fn b58decode_check(s: &str) -> Result<Vec<u8>, String> {
-    if let Ok(mut result) = s.from_base58() {
-        let check = result.split_off(result.len() - 4);
-
-        let mut hasher = Sha256::new();
-        hasher.update(&result);
-        let digest1 = hasher.finalize();
-
-        let mut hasher = Sha256::new();
-        hasher.update(&digest1);
-        let digest = hasher.finalize();
-        
-        if check == &digest[..4] {
-            return Ok(result);
+    base58check_decode(s).map_err(|err| err.to_string())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BtcAddressType {
+    P2pkh,
+    P2sh,
+    P2wpkh,
+    P2wsh,
+    P2tr,
+    /// 未定义的见证版本/长度
+    WitnessUnknown,
+}
+
+/// 解码后的 BTC 地址
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BtcAddress {
+    pub address_type: BtcAddressType,
+    /// Base58 地址为 None
+    pub witness_version: Option<u8>,
+    /// P2PKH/P2SH 为 20 字节哈希, 隔离见证为见证程序
+    pub program: Vec<u8>,
+}
+
+impl BtcAddress {
+    /// 该地址对应的 scriptPubKey
+    pub fn script_pubkey(&self) -> Vec<u8> {
+        match (self.address_type, self.witness_version) {
+            (BtcAddressType::P2pkh, _) => [&[0x76, 0xa9, 0x14][..], &self.program, &[0x88, 0xac]].concat(),
+            (BtcAddressType::P2sh, _) => [&[0xa9, 0x14][..], &self.program, &[0x87]].concat(),
+            (_, version) => {
+                let version = version.unwrap_or_default();
+                let opcode = if version == 0 { 0x00 } else { 0x50 + version };
+                [&[opcode, self.program.len() as u8][..], &self.program].concat()
+            }
         }
     }
-    
-    Err("The Address is invaild".to_string())
 }
 
-fn eip55_checksum(hex_address: &mut [u8]) {
-    let mut hasher = Keccak256::new();
-    hasher.update(&hex_address);
-    let hashed_address = hex::encode(hasher.finalize());
-
-    hex_address
-        .iter_mut()
-        .zip(hashed_address.as_bytes().iter())
-        .for_each(|(c, &h)| match *c {
-            b'a'..=b'f' if h > b'7' => {
-                *c = c.to_ascii_uppercase();
-            }
-            _ => (),
+/// 校验 BTC 地址是否属于该网络, 支持 Base58 P2PKH/P2SH 和隔离见证/taproot 地址.
+/// 网络参数带 CashAddr 前缀时 (BCH) 同时接受 CashAddr 地址
+pub fn validate_btc_address(address: &str, params: &NetworkParams) -> Result<BtcAddress, String> {
+    let lower = address.to_lowercase();
+    if let Some(prefix) = params.cashaddr_prefix {
+        if lower.contains(':') || lower.starts_with(['q', 'p']) {
+            let (address_type, program) = cashaddr_decode(prefix, address).map_err(|err| err.to_string())?;
+            let address_type = match address_type {
+                AddressType::P2sh => BtcAddressType::P2sh,
+                _ => BtcAddressType::P2pkh,
+            };
+            return Ok(BtcAddress {
+                address_type,
+                witness_version: None,
+                program,
+            });
+        }
+    }
+
+    if let Some(hrp) = params.bech32_hrp.filter(|hrp| lower.starts_with(&format!("{}1", hrp))) {
+        let (version, program) = segwit_decode(hrp, address)?;
+        let address_type = match (version, program.len()) {
+            (0, 20) => BtcAddressType::P2wpkh,
+            (0, 32) => BtcAddressType::P2wsh,
+            (1, 32) => BtcAddressType::P2tr,
+            _ => BtcAddressType::WitnessUnknown,
+        };
+
+        return Ok(BtcAddress {
+            address_type,
+            witness_version: Some(version),
+            program,
         });
+    }
+
+    let raw = b58decode_check(address)?;
+    if raw.len() != 21 {
+        return Err(format!("invalid base58 address length {}", raw.len()));
+    }
+
+    let address_type = if raw[0] == params.p2pkh_prefix {
+        BtcAddressType::P2pkh
+    } else if raw[0] == params.p2sh_prefix {
+        BtcAddressType::P2sh
+    } else {
+        return Err(format!("address version byte {} not valid for network", raw[0]));
+    };
+
+    Ok(BtcAddress {
+        address_type,
+        witness_version: None,
+        program: raw[1..].to_vec(),
+    })
 }
 
+
 #[test]
 fn test() {
     // TWjxTu8E5N4gDVySohe42E3pxPLhfCwzUE 地址
     let add = String::from("0x641725ed2b61cf433b0f60fa57372701e11c9f5e");
     println!("{:?}", eth2trx(&add));
+    assert_eq!(trx2eth(&eth2trx(&add).unwrap()).unwrap(), "0x641725Ed2b61cF433B0F60Fa57372701E11C9f5E");
     let trx_add = String::from("TR7NHqjeKQxGTCi8q8ZY4pL8otSzgjLj6t");
     let eth_add = trx2eth(&trx_add);
-    println!("eth_add: {:?}", eth_add)
-}
\ No newline at end of file
+    println!("eth_add: {:?}", eth_add);
+    assert_eq!(eth_add.unwrap(), "0xa614f803B6FD780986A42c78Ec9c7f77e6DeD13C");
+
+    // 非法输入返回错误而不是 panic
+    assert!(eth2trx("0xzz").is_err());
+    assert!(eth2trx("0xA41725ed2b61cf433b0f60fa57372701e11c9f5e").is_err());
+    assert!(trx2eth("TR7NHqjeKQxGTCi8q8ZY4pL8otSzgjLj6u").is_err());
+}
+
+
+#[test]
+fn test_validate_btc_address() {
+    use crate::btc_client::network::{Coin, Network};
+
+    let main = Network::Bitcoin.params();
+    let test = Network::Testnet.params();
+
+    let addr = validate_btc_address("1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa", &main).unwrap();
+    assert_eq!(addr.address_type, BtcAddressType::P2pkh);
+    assert_eq!(hex::encode(addr.script_pubkey()), "76a91462e907b15cbf27d5425399ebf6f0fb50ebb88f1888ac");
+
+    let addr = validate_btc_address("BC1QW508D6QEJXTDG4Y5R3ZARVARY0C5XW7KV8F3T4", &main).unwrap();
+    assert_eq!(addr.address_type, BtcAddressType::P2wpkh);
+    assert_eq!(hex::encode(addr.script_pubkey()), "0014751e76e8199196d454941c45d1b3a323f1433bd6");
+
+    let addr = validate_btc_address("tb1qrp33g0q5c5txsp9arysrx4k6zdkfs4nce4xj0gdcccefvpysxf3q0sl5k7", &test).unwrap();
+    assert_eq!(addr.address_type, BtcAddressType::P2wsh);
+
+    let addr = validate_btc_address("bc1p5cyxnuxmeuwuvkwfem96lqzszd02n6xdcjrs20cac6yqjjwudpxqkedrcr", &main).unwrap();
+    assert_eq!(addr.address_type, BtcAddressType::P2tr);
+    assert_eq!(hex::encode(addr.script_pubkey()), "5120a60869f0dbcf1dc659c9cecbaf8050135ea9e8cdc487053f1dc6880949dc684c");
+
+    let invalid = [
+        "1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNb", // 校验和错误
+        "tb1qrp33g0q5c5txsp9arysrx4k6zdkfs4nce4xj0gdcccefvpysxf3q0sl5k7", // 网络不符
+        "BC1QR508D6QEJXTDG4Y5R3ZARVARYV98GJ9P", // v0 程序长度错误
+        "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3T4", // 大小写混合
+        "1",
+    ];
+    for address in invalid {
+        assert!(validate_btc_address(address, &main).is_err(), "{}", address);
+    }
+
+    let bch = Coin::BitcoinCash.params(Network::Testnet);
+    let addr = validate_btc_address("bchtest:pp3wjpa3tjlj042z2wv7hahsldgwhwy0rqk8axh4c9", &bch).unwrap();
+    assert_eq!(addr.address_type, BtcAddressType::P2sh);
+    assert_eq!(hex::encode(addr.script_pubkey()), "a91462e907b15cbf27d5425399ebf6f0fb50ebb88f1887");
+    assert!(validate_btc_address("bitcoincash:qp3wjpa3tjlj042z2wv7hahsldgwhwy0rq9sywjpyy", &bch).is_err());
+
+    // 见证版本与编码变体不符
+    let mut data = vec![1];
+    data.extend(convert_bits(&[0xab; 32], 8, 5, true).unwrap());
+    assert!(validate_btc_address(&bech32_encode("bc", &data, Bech32Variant::Bech32), &main).is_err());
+    data[0] = 0;
+    assert!(validate_btc_address(&bech32_encode("bc", &data, Bech32Variant::Bech32m), &main).is_err());
+    assert!(validate_btc_address(&bech32_encode("bc", &data, Bech32Variant::Bech32), &main).is_ok());
+}
@@ -0,0 +1,373 @@
+use std::{fmt, str::FromStr};
+
+use anyhow::{anyhow, bail};
+use hmac::{Hmac, Mac};
+use k256::{
+    elliptic_curve::{
+        sec1::{FromEncodedPoint, ToEncodedPoint},
+        PrimeField,
+    },
+    AffinePoint, EncodedPoint, FieldBytes, ProjectivePoint, Scalar,
+};
+use sha2::Sha512;
+use sha3::{Digest, Keccak256};
+
+use crate::btc_client::{network::NetworkParams, script::ScriptType};
+
+use super::address_convert::{b58decode_check, convert_b58encode, eip55_address, eth2trx, hash160};
+
+/// 硬化派生索引起点
+pub const HARDENED: u32 = 0x8000_0000;
+
+/// 主网 xpub 版本字节
+pub const XPUB_VERSION: [u8; 4] = [0x04, 0x88, 0xb2, 0x1e];
+/// 测试网 tpub 版本字节
+pub const TPUB_VERSION: [u8; 4] = [0x04, 0x35, 0x87, 0xcf];
+/// SLIP-132 主网 P2SH-P2WPKH (BIP-49)
+pub const YPUB_VERSION: [u8; 4] = [0x04, 0x9d, 0x7c, 0xb2];
+/// SLIP-132 主网 P2WPKH (BIP-84)
+pub const ZPUB_VERSION: [u8; 4] = [0x04, 0xb2, 0x47, 0x46];
+/// SLIP-132 测试网 P2SH-P2WPKH
+pub const UPUB_VERSION: [u8; 4] = [0x04, 0x4a, 0x52, 0x62];
+/// SLIP-132 测试网 P2WPKH
+pub const VPUB_VERSION: [u8; 4] = [0x04, 0x5f, 0x1c, 0xf6];
+
+/// 扩展公钥版本字节隐含的地址类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum XpubScript {
+    /// xpub/tpub
+    P2pkh,
+    /// ypub/upub
+    P2shP2wpkh,
+    /// zpub/vpub
+    P2wpkh,
+}
+
+/// BIP-32 扩展公钥, 只支持非硬化派生
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtendedPubKey {
+    pub version: [u8; 4],
+    pub depth: u8,
+    pub parent_fingerprint: [u8; 4],
+    pub child_number: u32,
+    pub chain_code: [u8; 32],
+    /// 压缩公钥
+    pub public_key: [u8; 33],
+}
+
+// 压缩公钥转为曲线上的点, 同时校验公钥合法
+fn decode_point(public_key: &[u8]) -> Result<AffinePoint, anyhow::Error> {
+    let encoded = EncodedPoint::from_bytes(public_key).map_err(|_| anyhow!("invalid public key encoding"))?;
+    Option::from(AffinePoint::from_encoded_point(&encoded)).ok_or_else(|| anyhow!("public key not on curve"))
+}
+
+// 32 字节转为标量, 超出曲线阶时返回 None
+pub(crate) fn scalar_from_bytes(bytes: &[u8]) -> Option<Scalar> {
+    Option::from(Scalar::from_repr(*FieldBytes::from_slice(bytes)))
+}
+
+/// 公钥加上 tweak * G, 用于 BIP-32 子公钥和 taproot 输出公钥
+pub(crate) fn tweak_add(public_key: &[u8], tweak: &[u8; 32]) -> Result<[u8; 33], anyhow::Error> {
+    let point = decode_point(public_key)?;
+    let tweak = scalar_from_bytes(tweak).ok_or_else(|| anyhow!("tweak out of range"))?;
+
+    let child = ProjectivePoint::GENERATOR * tweak + ProjectivePoint::from(point);
+    if child == ProjectivePoint::IDENTITY {
+        bail!("tweaked key is the point at infinity");
+    }
+    Ok(child.to_affine().to_encoded_point(true).as_bytes().try_into()?)
+}
+
+impl ExtendedPubKey {
+    pub fn from_bytes(data: &[u8]) -> Result<Self, anyhow::Error> {
+        if data.len() != 78 {
+            bail!("invalid extended key length {}", data.len());
+        }
+
+        let version: [u8; 4] = data[..4].try_into()?;
+        let known = [
+            XPUB_VERSION,
+            TPUB_VERSION,
+            YPUB_VERSION,
+            ZPUB_VERSION,
+            UPUB_VERSION,
+            VPUB_VERSION,
+        ];
+        if !known.contains(&version) {
+            bail!("unsupported extended public key version {}", hex::encode(version));
+        }
+
+        let public_key: [u8; 33] = data[45..].try_into()?;
+        decode_point(&public_key)?;
+
+        let key = ExtendedPubKey {
+            version,
+            depth: data[4],
+            parent_fingerprint: data[5..9].try_into()?,
+            child_number: u32::from_be_bytes(data[9..13].try_into()?),
+            chain_code: data[13..45].try_into()?,
+            public_key,
+        };
+        if key.depth == 0 && (key.parent_fingerprint != [0; 4] || key.child_number != 0) {
+            bail!("master key with non-zero parent fingerprint or index");
+        }
+        Ok(key)
+    }
+
+    pub fn script(&self) -> XpubScript {
+        match self.version {
+            YPUB_VERSION | UPUB_VERSION => XpubScript::P2shP2wpkh,
+            ZPUB_VERSION | VPUB_VERSION => XpubScript::P2wpkh,
+            _ => XpubScript::P2pkh,
+        }
+    }
+
+    pub fn is_testnet(&self) -> bool {
+        matches!(self.version, TPUB_VERSION | UPUB_VERSION | VPUB_VERSION)
+    }
+
+    /// 转为 xpub/tpub 版本, 描述符和节点只接受这两种
+    pub fn to_standard(&self) -> Self {
+        let version = if self.is_testnet() { TPUB_VERSION } else { XPUB_VERSION };
+        ExtendedPubKey {
+            version,
+            ..self.clone()
+        }
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(78);
+        buf.extend(self.version);
+        buf.push(self.depth);
+        buf.extend(self.parent_fingerprint);
+        buf.extend(self.child_number.to_be_bytes());
+        buf.extend(self.chain_code);
+        buf.extend(self.public_key);
+        buf
+    }
+
+    /// 公钥的 hash160 前 4 字节, 即子密钥的 parent_fingerprint
+    pub fn fingerprint(&self) -> [u8; 4] {
+        let hash = hash160(&self.public_key);
+        [hash[0], hash[1], hash[2], hash[3]]
+    }
+
+    /// 派生非硬化子公钥
+    pub fn derive_child(&self, index: u32) -> Result<Self, anyhow::Error> {
+        if index >= HARDENED {
+            bail!("hardened derivation {}h requires private key", index - HARDENED);
+        }
+        if self.depth == u8::MAX {
+            bail!("maximum derivation depth reached");
+        }
+
+        let mut mac = Hmac::<Sha512>::new_from_slice(&self.chain_code)?;
+        mac.update(&self.public_key);
+        mac.update(&index.to_be_bytes());
+        let digest = mac.finalize().into_bytes();
+
+        // IL >= n 或结果为无穷远点时该索引无效, 概率可忽略, 按规范返回错误由调用方跳过
+        let tweak: [u8; 32] = digest[..32].try_into()?;
+        let public_key =
+            tweak_add(&self.public_key, &tweak).map_err(|err| anyhow!("invalid child {}: {}", index, err))?;
+
+        Ok(ExtendedPubKey {
+            version: self.version,
+            depth: self.depth + 1,
+            parent_fingerprint: self.fingerprint(),
+            child_number: index,
+            chain_code: digest[32..].try_into()?,
+            public_key,
+        })
+    }
+
+    pub fn derive_path(&self, path: &[u32]) -> Result<Self, anyhow::Error> {
+        let mut key = self.clone();
+        for &index in path {
+            key = key.derive_child(index)?;
+        }
+        Ok(key)
+    }
+
+    /// 派生 BTC 地址, 地址类型由版本字节决定. 通常为账户级密钥下的 0/i (收款) 或 1/i (找零)
+    pub fn btc_address(&self, path: &[u32], params: &NetworkParams) -> Result<String, anyhow::Error> {
+        let hash = hash160(&self.derive_path(path)?.public_key);
+        let script_type = match self.script() {
+            XpubScript::P2pkh => ScriptType::P2pkh { hash },
+            XpubScript::P2wpkh => ScriptType::P2wpkh { program: hash },
+            XpubScript::P2shP2wpkh => {
+                let redeem = ScriptType::P2wpkh { program: hash }
+                    .script_pubkey()
+                    .ok_or_else(|| anyhow!("invalid redeem script"))?;
+                ScriptType::P2sh { hash: hash160(&redeem) }
+            }
+        };
+        script_type
+            .address(params)
+            .ok_or_else(|| anyhow!("no address for {}", script_type.as_str()))
+    }
+
+    /// 派生 EIP-55 以太坊地址, EVM 链通用. 通常为 m/44'/60'/0' 账户密钥下的 0/i
+    pub fn eth_address(&self, path: &[u32]) -> Result<String, anyhow::Error> {
+        eth_address(&self.derive_path(path)?.public_key)
+    }
+
+    /// 派生波场地址. 通常为 m/44'/195'/0' 账户密钥下的 0/i
+    pub fn tron_address(&self, path: &[u32]) -> Result<String, anyhow::Error> {
+        tron_address(&self.derive_path(path)?.public_key)
+    }
+}
+
+/// 公钥 (压缩或非压缩) 对应的 EIP-55 以太坊地址: keccak256(x || y) 的后 20 字节
+pub fn eth_address(public_key: &[u8]) -> Result<String, anyhow::Error> {
+    let uncompressed = decode_point(public_key)?.to_encoded_point(false);
+    let hash = Keccak256::digest(&uncompressed.as_bytes()[1..]);
+    Ok(eip55_address(&hash[12..]))
+}
+
+/// 公钥对应的波场 base58 地址, 与以太坊地址同源, 前缀 0x41
+pub fn tron_address(public_key: &[u8]) -> Result<String, anyhow::Error> {
+    Ok(eth2trx(&eth_address(public_key)?)?)
+}
+
+impl FromStr for ExtendedPubKey {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let data = b58decode_check(s).map_err(|err| anyhow!("{}: {}", err, s))?;
+        Self::from_bytes(&data)
+    }
+}
+
+impl fmt::Display for ExtendedPubKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&convert_b58encode(self.to_bytes()))
+    }
+}
+
+/// 解析派生路径, 如 "m/84'/0'/0'/0" 或 "84h/0h/0h", 硬化标记支持 ' 和 h
+pub fn parse_path(path: &str) -> Result<Vec<u32>, anyhow::Error> {
+    let path = path.strip_prefix("m").unwrap_or(path);
+    let path = path.strip_prefix('/').unwrap_or(path);
+    if path.is_empty() {
+        return Ok(vec![]);
+    }
+
+    path.split('/')
+        .map(|step| {
+            let (index, hardened) = match step.strip_suffix(['\'', 'h', 'H']) {
+                Some(index) => (index, true),
+                None => (step, false),
+            };
+            let index: u32 = index.parse().map_err(|_| anyhow!("invalid path step '{}'", step))?;
+            if index >= HARDENED {
+                bail!("path index {} out of range", index);
+            }
+            Ok(if hardened { index + HARDENED } else { index })
+        })
+        .collect()
+}
+
+/// 派生路径转为字符串, 硬化索引使用 h 标记
+pub fn format_path(path: &[u32]) -> String {
+    path.iter()
+        .map(|&index| match index >= HARDENED {
+            true => format!("{}h", index - HARDENED),
+            false => index.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::btc_client::network::Network;
+
+    use super::*;
+
+    // BIP-32 测试向量 1, m/0h 及 m/0h/1
+    const M_0H: &str = "xpub68Gmy5EdvgibQVfPdqkBBCHxA5htiqg55crXYuXoQRKfDBFA1WEjWgP6LHhwBZeNK1VTsfTFUHCdrfp1bgwQ9xv5ski8PX9rL2dZXvgGDnw";
+    const M_0H_1: &str = "xpub6ASuArnXKPbfEwhqN6e3mwBcDTgzisQN1wXN9BJcM47sSikHjJf3UFHKkNAWbWMiGj7Wf5uMash7SyYq527Hqck2AxYysAA7xmALppuCkwQ";
+
+    #[test]
+    fn test_derive() {
+        let parent: ExtendedPubKey = M_0H.parse().unwrap();
+        assert_eq!(parent.to_string(), M_0H);
+        assert_eq!(parent.depth, 1);
+        assert_eq!(parent.child_number, HARDENED);
+
+        let child = parent.derive_child(1).unwrap();
+        assert_eq!(child.to_string(), M_0H_1);
+        assert_eq!(child.parent_fingerprint, parent.fingerprint());
+        assert_eq!(parent.derive_path(&[1]).unwrap(), child);
+
+        assert!(parent.derive_child(HARDENED).is_err());
+        assert!(M_0H.replace('Q', "R").parse::<ExtendedPubKey>().is_err());
+    }
+
+    #[test]
+    fn test_path() {
+        assert_eq!(
+            parse_path("m/84'/0h/0H/1/5").unwrap(),
+            vec![84 + HARDENED, HARDENED, HARDENED, 1, 5]
+        );
+        assert_eq!(parse_path("m").unwrap(), Vec::<u32>::new());
+        assert!(parse_path("0/*").is_err());
+        assert!(parse_path("2147483648").is_err());
+        assert_eq!(format_path(&[84 + HARDENED, 0, 7]), "84h/0/7");
+    }
+
+    #[test]
+    fn test_slip132() {
+        let main = Network::Bitcoin.params();
+        let cases = [
+            // BIP-44/49/84 测试向量, "abandon ... about" 助记词
+            ("xpub6BosfCnifzxcFwrSzQiqu2DBVTshkCXacvNsWGYJVVhhawA7d4R5WSWGFNbi8Aw6ZRc1brxMyWMzG3DSSSSoekkudhUd9yLb6qx39T9nMdj", XpubScript::P2pkh, "1LqBGSKuX5yYUonjxT5qGfpUsXKYYWeabA"),
+            ("ypub6Ww3ibxVfGzLrAH1PNcjyAWenMTbbAosGNB6VvmSEgytSER9azLDWCxoJwW7Ke7icmizBMXrzBx9979FfaHxHcrArf3zbeJJJUZPf663zsP", XpubScript::P2shP2wpkh, "37VucYSaXLCAsxYyAPfbSi9eh4iEcbShgf"),
+            ("zpub6rFR7y4Q2AijBEqTUquhVz398htDFrtymD9xYYfG1m4wAcvPhXNfE3EfH1r1ADqtfSdVCToUG868RvUUkgDKf31mGDtKsAYz2oz2AGutZYs", XpubScript::P2wpkh, "bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu"),
+        ];
+        for (key, script, address) in cases {
+            let key: ExtendedPubKey = key.parse().unwrap();
+            assert_eq!(key.script(), script);
+            assert!(!key.is_testnet());
+            assert_eq!(key.btc_address(&[0, 0], &main).unwrap(), address);
+        }
+
+        let zpub: ExtendedPubKey = cases[2].0.parse().unwrap();
+        assert_eq!(
+            zpub.to_standard().to_string(),
+            "xpub6CatWdiZiodmUeTDp8LT5or8nmbKNcuyvz7WyksVFkKB4RHwCD3XyuvPEbvqAQY3rAPshWcMLoP2fMFMKHPJ4ZeZXYVUhLv1VMrjPC7PW6V"
+        );
+
+        let vpub: ExtendedPubKey = "vpub5Y6cjg78GGuNLsaPhmYsiw4gYX3HoQiRBiSwDaBXKUafCt9bNwWQiitDk5VZ5BVxYnQdwoTyXSs2JHRPAgjAvtbBrf8ZhDYe2jWAqvZVnsc".parse().unwrap();
+        assert!(vpub.is_testnet());
+        assert_eq!(
+            vpub.btc_address(&[0, 0], &Network::Testnet.params()).unwrap(),
+            "tb1q6rz28mcfaxtmd6v789l9rrlrusdprr9pqcpvkl"
+        );
+    }
+
+    #[test]
+    fn test_eth_tron_address() {
+        // m/44'/60'/0' 及 m/44'/195'/0', "abandon ... about" 助记词
+        let eth: ExtendedPubKey = "xpub6DCoCpSuQZB2jawqnGMEPS63ePKWkwWPH4TU45Q7LPXWuNd8TMtVxRrgjtEshuqpK3mdhaWHPFsBngh5GFZaM6si3yZdUsT8ddYM3PwnATt".parse().unwrap();
+        assert_eq!(
+            eth.eth_address(&[0, 0]).unwrap(),
+            "0x9858EfFD232B4033E47d90003D41EC34EcaEda94"
+        );
+
+        let tron: ExtendedPubKey = "xpub6D1AabNHCupeiLM65ZR9UStMhJ1vCpyV4XbZdyhMZBiJXALQtmn9p42VTQckoHVn8WNqS7dqnJokZHAHcHGoaQgmv8D45oNUKx6DZMNZBCd".parse().unwrap();
+        assert_eq!(
+            tron.tron_address(&[0, 0]).unwrap(),
+            "TUEZSdKsoDHQMeZwihtdoBiN46zxhGWYdH"
+        );
+
+        // 非压缩公钥得到相同地址
+        let key = eth.derive_path(&[0, 0]).unwrap().public_key;
+        let uncompressed = decode_point(&key).unwrap().to_encoded_point(false);
+        assert_eq!(
+            eth_address(uncompressed.as_bytes()).unwrap(),
+            eth_address(&key).unwrap()
+        );
+    }
+}
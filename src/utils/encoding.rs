@@ -0,0 +1,247 @@
+// 地址编码的公共原语: Base58Check 编码、Bech32/Bech32m 及哈希, 供 address 和 address_convert 共用
+use base58::ToBase58;
+use digest::Digest;
+use ripemd::Ripemd160;
+use sha2::Sha256;
+use sha3::Keccak256;
+
+use crate::btc_client::consensus::sha256d;
+
+pub fn convert_b58encode<T: AsRef<[u8]>>(raw: T) -> String {
+    let mut raw = raw.as_ref().to_owned();
+    raw.extend(&sha256d(&raw)[..4]);
+    raw.to_base58()
+}
+
+/// ripemd160(sha256(data)), 用于公钥哈希和脚本哈希
+pub fn hash160(data: &[u8]) -> [u8; 20] {
+    Ripemd160::digest(Sha256::digest(data)).into()
+}
+
+/// Bech32 与 CashAddr 共用的字符集
+pub(crate) const BECH32_CHARSET: &[u8] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+
+/// BIP173 (见证版本0) 使用 Bech32, BIP350 (见证版本1+) 使用 Bech32m
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bech32Variant {
+    Bech32,
+    Bech32m,
+}
+
+impl Bech32Variant {
+    fn constant(&self) -> u32 {
+        match self {
+            Bech32Variant::Bech32 => 1,
+            Bech32Variant::Bech32m => 0x2bc830a3,
+        }
+    }
+}
+
+fn bech32_polymod(values: &[u8]) -> u32 {
+    const GEN: [u32; 5] = [0x3b6a57b2, 0x26508e6d, 0x1ea119fa, 0x3d4233dd, 0x2a1462b3];
+
+    let mut chk: u32 = 1;
+    for &v in values {
+        let b = chk >> 25;
+        chk = ((chk & 0x1ffffff) << 5) ^ v as u32;
+        for (i, g) in GEN.iter().enumerate() {
+            if (b >> i) & 1 == 1 {
+                chk ^= g;
+            }
+        }
+    }
+    chk
+}
+
+fn bech32_hrp_expand(hrp: &str) -> Vec<u8> {
+    let mut res = hrp.bytes().map(|b| b >> 5).collect::<Vec<_>>();
+    res.push(0);
+    res.extend(hrp.bytes().map(|b| b & 31));
+    res
+}
+
+/// 编码 5 位分组数据, hrp 需为小写
+pub fn bech32_encode(hrp: &str, data: &[u8], variant: Bech32Variant) -> String {
+    let mut values = bech32_hrp_expand(hrp);
+    values.extend(data);
+    values.extend([0; 6]);
+    let polymod = bech32_polymod(&values) ^ variant.constant();
+
+    let checksum = (0..6).map(|i| ((polymod >> (5 * (5 - i))) & 31) as u8);
+    let encoded = data
+        .iter()
+        .copied()
+        .chain(checksum)
+        .map(|d| BECH32_CHARSET[d as usize] as char)
+        .collect::<String>();
+
+    format!("{}1{}", hrp, encoded)
+}
+
+/// 位宽转换, 如 8 位字节 <-> 5 位分组
+pub fn convert_bits(data: &[u8], from: u32, to: u32, pad: bool) -> Result<Vec<u8>, String> {
+    let mut acc: u32 = 0;
+    let mut bits: u32 = 0;
+    let maxv: u32 = (1 << to) - 1;
+    let mut res = Vec::new();
+
+    for &value in data {
+        if (value as u32) >> from != 0 {
+            return Err(format!("invalid data value {} for {} bits", value, from));
+        }
+        acc = (acc << from) | value as u32;
+        bits += from;
+        while bits >= to {
+            bits -= to;
+            res.push(((acc >> bits) & maxv) as u8);
+        }
+    }
+
+    if pad {
+        if bits > 0 {
+            res.push(((acc << (to - bits)) & maxv) as u8);
+        }
+    } else if bits >= from || ((acc << (to - bits)) & maxv) != 0 {
+        return Err("invalid padding".to_string());
+    }
+
+    Ok(res)
+}
+
+/// 隔离见证地址编码, 版本0使用 Bech32, 版本1-16使用 Bech32m
+pub fn segwit_encode(hrp: &str, version: u8, program: &[u8]) -> Result<String, String> {
+    if version > 16 {
+        return Err(format!("invalid witness version {}", version));
+    }
+    if program.len() < 2 || program.len() > 40 || (version == 0 && program.len() != 20 && program.len() != 32) {
+        return Err(format!("invalid witness program length {}", program.len()));
+    }
+
+    let variant = if version == 0 {
+        Bech32Variant::Bech32
+    } else {
+        Bech32Variant::Bech32m
+    };
+    let mut data = vec![version];
+    data.extend(convert_bits(program, 8, 5, true)?);
+
+    Ok(bech32_encode(&hrp.to_lowercase(), &data, variant))
+}
+
+/// 解码 bech32/bech32m 字符串, 返回 (小写hrp, 5位分组数据(不含校验和), 变体)
+pub fn bech32_decode(s: &str) -> Result<(String, Vec<u8>, Bech32Variant), String> {
+    if s.len() > 90 {
+        return Err(format!("bech32 string too long: {}", s.len()));
+    }
+    if s.chars().any(|c| c.is_ascii_lowercase()) && s.chars().any(|c| c.is_ascii_uppercase()) {
+        return Err("bech32 string has mixed case".to_string());
+    }
+
+    let s = s.to_lowercase();
+    let pos = s.rfind('1').ok_or("bech32 separator not found")?;
+    let (hrp, data) = (&s[..pos], &s[pos + 1..]);
+
+    if hrp.is_empty() || hrp.bytes().any(|b| !(33..=126).contains(&b)) {
+        return Err(format!("invalid bech32 hrp: {}", hrp));
+    }
+    if data.len() < 6 {
+        return Err("bech32 data too short".to_string());
+    }
+
+    let data = data
+        .bytes()
+        .map(|b| {
+            BECH32_CHARSET
+                .iter()
+                .position(|&c| c == b)
+                .map(|v| v as u8)
+                .ok_or(format!("invalid bech32 character: {}", b as char))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut values = bech32_hrp_expand(hrp);
+    values.extend(&data);
+    let variant = match bech32_polymod(&values) {
+        1 => Bech32Variant::Bech32,
+        0x2bc830a3 => Bech32Variant::Bech32m,
+        _ => return Err("invalid bech32 checksum".to_string()),
+    };
+
+    Ok((hrp.to_string(), data[..data.len() - 6].to_vec(), variant))
+}
+
+/// 隔离见证地址解码, 返回 (见证版本, 见证程序)
+pub fn segwit_decode(hrp: &str, address: &str) -> Result<(u8, Vec<u8>), String> {
+    let (addr_hrp, data, variant) = bech32_decode(address)?;
+    if addr_hrp != hrp.to_lowercase() {
+        return Err(format!("hrp mismatch: expected {} got {}", hrp, addr_hrp));
+    }
+
+    let (&version, data) = data.split_first().ok_or("empty witness data")?;
+    if version > 16 {
+        return Err(format!("invalid witness version {}", version));
+    }
+
+    let program = convert_bits(data, 5, 8, false)?;
+    if program.len() < 2 || program.len() > 40 || (version == 0 && program.len() != 20 && program.len() != 32) {
+        return Err(format!("invalid witness program length {}", program.len()));
+    }
+
+    let expected = if version == 0 {
+        Bech32Variant::Bech32
+    } else {
+        Bech32Variant::Bech32m
+    };
+    if variant != expected {
+        return Err(format!("witness version {} must use {:?}", version, expected));
+    }
+
+    Ok((version, program))
+}
+
+/// 20 字节地址转为 EIP-55 校验和格式的以太坊地址
+pub fn eip55_address(address: &[u8]) -> String {
+    let mut hex_address = hex::encode(address).into_bytes();
+    eip55_checksum(&mut hex_address);
+    format!("0x{}", String::from_utf8_lossy(&hex_address))
+}
+
+fn eip55_checksum(hex_address: &mut [u8]) {
+    let mut hasher = Keccak256::new();
+    hasher.update(&hex_address);
+    let hashed_address = hex::encode(hasher.finalize());
+
+    hex_address
+        .iter_mut()
+        .zip(hashed_address.as_bytes().iter())
+        .for_each(|(c, &h)| match *c {
+            b'a'..=b'f' if h > b'7' => {
+                *c = c.to_ascii_uppercase();
+            }
+            _ => (),
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_segwit_encode() {
+        // BIP173/BIP350 示例
+        let program = hex::decode("751e76e8199196d454941c45d1b3a323f1433bd6").unwrap();
+        assert_eq!(
+            segwit_encode("bc", 0, &program).unwrap(),
+            "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4"
+        );
+
+        let program =
+            hex::decode("751e76e8199196d454941c45d1b3a323f1433bd6751e76e8199196d454941c45d1b3a323f1433bd6").unwrap();
+        assert_eq!(
+            segwit_encode("bc", 1, &program).unwrap(),
+            "bc1pw508d6qejxtdg4y5r3zarvary0c5xw7kw508d6qejxtdg4y5r3zarvary0c5xw7kt5nd6y"
+        );
+
+        assert!(segwit_encode("bc", 0, &[0; 21]).is_err());
+    }
+}
@@ -1,5 +1,11 @@
 pub mod retry_fn;
+pub mod rpc_batch;
+#[cfg(test)]
+pub(crate) mod test_node;
+pub mod address;
 pub mod address_convert;
+pub mod encoding;
+pub mod bip32;
 pub mod calculate_contract;
 pub mod convert_hex;
 
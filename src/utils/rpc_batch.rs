@@ -0,0 +1,276 @@
+use std::{collections::HashMap, fmt, future::Future, time::Duration};
+
+use futures::StreamExt;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::{json, Value};
+
+/// 批量请求配置
+#[derive(Debug, Clone)]
+pub struct BatchConfig {
+    /// 单个批次的最大请求数
+    pub chunk_size: usize,
+    /// 同时发送的批次数
+    pub concurrency: usize,
+    /// 失败条目的最大重试次数
+    pub max_retries: usize,
+    /// 重试前休眠时间(None则不休眠)
+    pub retry_sleep: Option<Duration>,
+}
+
+impl Default for BatchConfig {
+    fn default() -> Self {
+        BatchConfig {
+            chunk_size: 100,
+            concurrency: 4,
+            max_retries: 3,
+            retry_sleep: Some(Duration::from_secs(1)),
+        }
+    }
+}
+
+/// 批量中的单个请求, id 由批量引擎分配
+#[derive(Debug, Clone, Serialize)]
+pub struct RpcRequest {
+    pub method: String,
+    pub params: Value,
+}
+
+impl RpcRequest {
+    pub fn new(method: &str, params: Value) -> Self {
+        RpcRequest {
+            method: method.to_owned(),
+            params,
+        }
+    }
+}
+
+/// 批量中单个条目的错误
+#[derive(Debug, Clone, PartialEq)]
+pub enum BatchError {
+    /// 所在批次请求失败
+    Transport(String),
+    /// 响应中缺少该 id
+    Missing,
+    /// 响应中该 id 重复出现
+    Duplicate,
+    /// 节点返回的 error
+    Rpc { code: i64, message: String },
+    /// result 无法解析为目标类型
+    Decode(String),
+}
+
+impl BatchError {
+    /// 网络错误或响应缺失/重复可以重试, 节点明确返回的错误不重试
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, BatchError::Transport(_) | BatchError::Missing | BatchError::Duplicate)
+    }
+}
+
+impl fmt::Display for BatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BatchError::Transport(err) => write!(f, "batch transport error: {}", err),
+            BatchError::Missing => write!(f, "response missing for id"),
+            BatchError::Duplicate => write!(f, "duplicate responses for id"),
+            BatchError::Rpc { code, message } => write!(f, "rpc error {}: {}", code, message),
+            BatchError::Decode(err) => write!(f, "decode error: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for BatchError {}
+
+/// 通用 JSON-RPC 批量请求: 按 chunk_size 分批, 以 concurrency 并发发送, 按 id 还原请求顺序,
+/// 仅对失败的条目重试. send 负责发送一个批次并返回原始响应数组.
+/// 返回值与 requests 一一对应.
+pub async fn batch_call<T, F, Fut>(
+    requests: Vec<RpcRequest>,
+    config: &BatchConfig,
+    send: F,
+) -> Vec<Result<T, BatchError>>
+where
+    T: DeserializeOwned,
+    F: Fn(Vec<Value>) -> Fut,
+    Fut: Future<Output = Result<Vec<Value>, anyhow::Error>>,
+{
+    let mut results: Vec<Result<Value, BatchError>> = vec![Err(BatchError::Missing); requests.len()];
+    let mut pending = (0..requests.len()).collect::<Vec<_>>();
+    let chunk_size = config.chunk_size.max(1);
+    let concurrency = config.concurrency.max(1);
+
+    let mut count = 0;
+    while !pending.is_empty() {
+        let chunks = pending.chunks(chunk_size).map(|chunk| chunk.to_vec()).collect::<Vec<_>>();
+
+        let responses = futures::stream::iter(chunks)
+            .map(|chunk| {
+                // 使用请求下标作为 id, 保证跨批次唯一
+                let payload = chunk
+                    .iter()
+                    .map(|&index| {
+                        json!({
+                            "jsonrpc": "2.0",
+                            "id": index,
+                            "method": requests[index].method,
+                            "params": requests[index].params
+                        })
+                    })
+                    .collect::<Vec<_>>();
+                let task = send(payload);
+                async move { (chunk, task.await) }
+            })
+            .buffer_unordered(concurrency)
+            .collect::<Vec<_>>()
+            .await;
+
+        for (chunk, res) in responses {
+            match res {
+                Ok(items) => {
+                    let mut by_id = match_responses(items);
+                    for index in chunk {
+                        results[index] = match by_id.remove(&index) {
+                            Some(mut items) if items.len() == 1 => parse_item(items.remove(0)),
+                            Some(_) => Err(BatchError::Duplicate),
+                            None => Err(BatchError::Missing),
+                        };
+                    }
+                    if !by_id.is_empty() {
+                        log::warn!("[batch_call] unexpected response ids: {:?}", by_id.keys());
+                    }
+                }
+                Err(err) => {
+                    log::warn!("[batch_call] chunk of {} failed: {}", chunk.len(), err);
+                    for index in chunk {
+                        results[index] = Err(BatchError::Transport(err.to_string()));
+                    }
+                }
+            }
+        }
+
+        pending.retain(|&index| results[index].as_ref().is_err_and(|err| err.is_retryable()));
+
+        // 达到最大重试次数, 保留最后一次错误
+        if pending.is_empty() || count >= config.max_retries {
+            break;
+        }
+        count += 1;
+
+        log::debug!("[batch_call] retry {} failed entries, count {}", pending.len(), count);
+        if let Some(sleep_time) = config.retry_sleep {
+            async_std::task::sleep(sleep_time).await;
+        }
+    }
+
+    results
+        .into_iter()
+        .map(|res| res.and_then(|val| serde_json::from_value::<T>(val).map_err(|err| BatchError::Decode(err.to_string()))))
+        .collect()
+}
+
+// 按 id 归类响应, 无法识别 id 的响应丢弃
+fn match_responses(items: Vec<Value>) -> HashMap<usize, Vec<Value>> {
+    let mut by_id: HashMap<usize, Vec<Value>> = HashMap::new();
+
+    for item in items {
+        let id = match &item["id"] {
+            Value::Number(id) => id.as_u64().map(|id| id as usize),
+            Value::String(id) => id.parse::<usize>().ok(),
+            _ => None,
+        };
+
+        match id {
+            Some(id) => by_id.entry(id).or_default().push(item),
+            None => log::warn!("[batch_call] response without usable id: {}", item),
+        }
+    }
+
+    by_id
+}
+
+fn parse_item(mut item: Value) -> Result<Value, BatchError> {
+    match item["error"].take() {
+        Value::Null => Ok(item["result"].take()),
+        error => Err(BatchError::Rpc {
+            code: error["code"].as_i64().unwrap_or_default(),
+            message: error["message"].as_str().unwrap_or_default().to_owned(),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+
+    fn requests(n: usize) -> Vec<RpcRequest> {
+        (0..n).map(|i| RpcRequest::new("echo", json!([i]))).collect()
+    }
+
+    fn config() -> BatchConfig {
+        BatchConfig {
+            chunk_size: 3,
+            concurrency: 2,
+            max_retries: 2,
+            retry_sleep: None,
+        }
+    }
+
+    // 逆序返回, 结果为 params[0]
+    fn reply(payload: &[Value]) -> Vec<Value> {
+        payload
+            .iter()
+            .rev()
+            .map(|req| json!({"id": req["id"], "result": req["params"][0], "error": null}))
+            .collect()
+    }
+
+    #[async_std::test]
+    async fn test_order() {
+        let res = batch_call::<usize, _, _>(requests(10), &config(), |payload| async move { Ok(reply(&payload)) }).await;
+
+        assert_eq!(res.into_iter().map(|r| r.unwrap()).collect::<Vec<_>>(), (0..10).collect::<Vec<_>>());
+    }
+
+    #[async_std::test]
+    async fn test_retry_failed_only() {
+        let sent = AtomicUsize::new(0);
+        let res = batch_call::<usize, _, _>(requests(5), &config(), |payload| {
+            let first = sent.fetch_add(payload.len(), Ordering::SeqCst) == 0;
+            async move {
+                let mut items = reply(&payload);
+                if first {
+                    // 第一次: 丢失 id 0, 重复 id 1, id 2 返回节点错误
+                    items.retain(|item| item["id"] != 0);
+                    items.push(json!({"id": 1, "result": 1, "error": null}));
+                    for item in items.iter_mut().filter(|item| item["id"] == 2) {
+                        *item = json!({"id": 2, "result": null, "error": {"code": -5, "message": "not found"}});
+                    }
+                }
+                Ok(items)
+            }
+        })
+        .await;
+
+        assert_eq!(res[0], Ok(0));
+        assert_eq!(res[1], Ok(1));
+        assert_eq!(res[2], Err(BatchError::Rpc { code: -5, message: "not found".to_string() }));
+        assert_eq!(res[4], Ok(4));
+        // 3 + 2 首次, 仅重试 id 0 和 id 1
+        assert_eq!(sent.load(Ordering::SeqCst), 7);
+    }
+
+    #[async_std::test]
+    async fn test_transport_error() {
+        let res = batch_call::<usize, _, _>(requests(4), &config(), |payload| async move {
+            if payload.iter().any(|req| req["id"] == 3) {
+                return Err(anyhow::anyhow!("connection reset"));
+            }
+            Ok(reply(&payload))
+        })
+        .await;
+
+        assert_eq!(res[0], Ok(0));
+        assert!(matches!(res[3], Err(BatchError::Transport(_))));
+    }
+}
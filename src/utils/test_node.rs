@@ -0,0 +1,124 @@
+// 测试用的模拟节点, 在本地端口上实现最简单的 HTTP/1.1 JSON-RPC 服务
+use std::sync::{Arc, Mutex};
+
+use async_std::net::{TcpListener, TcpStream};
+use futures::{AsyncReadExt, AsyncWriteExt};
+use serde_json::{json, Value};
+
+/// 收到的请求: 小写的请求头和 JSON 请求体
+pub(crate) struct FakeRequest {
+    pub headers: String,
+    pub body: Value,
+}
+
+/// 模拟 HTTP 服务: 由 handler 根据请求生成状态码和响应体. 返回服务地址及收到的请求体
+pub(crate) async fn fake_http<F>(handler: F) -> (String, Arc<Mutex<Vec<Value>>>)
+where
+    F: Fn(&FakeRequest) -> (u16, String) + Send + Sync + 'static,
+{
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let requests = Arc::new(Mutex::new(Vec::new()));
+
+    let received = requests.clone();
+    async_std::task::spawn(async move {
+        loop {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let request = read_request(&mut stream).await;
+            received.lock().unwrap().push(request.body.clone());
+
+            let (status, body) = handler(&request);
+            let response = format!(
+                "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                status,
+                if status == 200 { "OK" } else { "Error" },
+                body.len(),
+                body
+            );
+            stream.write_all(response.as_bytes()).await.unwrap();
+        }
+    });
+
+    (url, requests)
+}
+
+/// 模拟 JSON-RPC 节点: 由 handler 根据 method 和 params 生成 result 或 (code, message) 错误, 支持批量请求
+pub(crate) async fn fake_node<F>(handler: F) -> (String, Arc<Mutex<Vec<Value>>>)
+where
+    F: Fn(&str, &Value) -> Result<Value, (i32, &'static str)> + Send + Sync + 'static,
+{
+    fake_http(move |request| {
+        let response = match &request.body {
+            Value::Array(batch) => Value::Array(batch.iter().map(|req| rpc_response(&handler, req)).collect()),
+            req => rpc_response(&handler, req),
+        };
+        (200, response.to_string())
+    })
+    .await
+}
+
+/// 接受连接后立即关闭, 模拟不可用的节点. 端口一直被占用, 不会被其他进程复用
+pub(crate) async fn down_node() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    async_std::task::spawn(async move {
+        loop {
+            drop(listener.accept().await);
+        }
+    });
+    url
+}
+
+/// 接受连接但不响应, 模拟执行请求后卡住的节点
+pub(crate) async fn silent_node() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    async_std::task::spawn(async move {
+        let mut streams = Vec::new();
+        loop {
+            let (stream, _) = listener.accept().await.unwrap();
+            streams.push(stream);
+        }
+    });
+    url
+}
+
+fn rpc_response<F>(handler: &F, request: &Value) -> Value
+where
+    F: Fn(&str, &Value) -> Result<Value, (i32, &'static str)>,
+{
+    let id = request["id"].clone();
+    match handler(request["method"].as_str().unwrap(), &request["params"]) {
+        Ok(result) => json!({"jsonrpc": "2.0", "result": result, "error": null, "id": id}),
+        Err((code, message)) => {
+            json!({"jsonrpc": "2.0", "result": null, "error": {"code": code, "message": message}, "id": id})
+        }
+    }
+}
+
+// 按 Content-Length 读取完整请求
+async fn read_request(stream: &mut TcpStream) -> FakeRequest {
+    let mut data = Vec::new();
+    let mut buf = [0u8; 4096];
+    let body_start = loop {
+        let n = stream.read(&mut buf).await.unwrap();
+        data.extend(&buf[..n]);
+        if let Some(index) = data.windows(4).position(|w| w == b"\r\n\r\n") {
+            break index + 4;
+        }
+    };
+    let headers = String::from_utf8_lossy(&data[..body_start]).to_lowercase();
+    let length = headers
+        .lines()
+        .find_map(|line| line.strip_prefix("content-length:"))
+        .map_or(0, |len| len.trim().parse::<usize>().unwrap());
+    while data.len() < body_start + length {
+        let n = stream.read(&mut buf).await.unwrap();
+        data.extend(&buf[..n]);
+    }
+
+    FakeRequest {
+        headers,
+        body: serde_json::from_slice(&data[body_start..]).unwrap_or(Value::Null),
+    }
+}